@@ -0,0 +1,174 @@
+// Bindings, vertex stage and lighting shared by the scene shaders. Each scene
+// shader is this file followed by its own `fs_main`.
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    camera_pos: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct Material {
+    base_color: vec4<f32>,
+    roughness: f32,
+    metallic: f32,
+    flags: u32,
+    tile_size: f32,
+}
+
+const MATERIAL_FLAG_GRID: u32 = 1u;
+
+@group(1) @binding(0)
+var<storage, read> materials: array<Material>;
+
+struct Light {
+    position: vec3<f32>,
+    kind: u32,
+    // Direction the light travels in
+    direction: vec3<f32>,
+    range: f32,
+    color: vec3<f32>,
+    intensity: f32,
+    cos_inner: f32,
+    cos_outer: f32,
+}
+
+const PI: f32 = 3.14159265;
+
+const LIGHT_AMBIENT: u32 = 0u;
+const LIGHT_SUN: u32 = 1u;
+const LIGHT_POINT: u32 = 2u;
+const LIGHT_SPOT: u32 = 3u;
+
+@group(1) @binding(1)
+var<storage, read> lights: array<Light>;
+
+struct ShadowUniform {
+    light_view_proj: mat4x4<f32>,
+    // Index into `lights` of the light casting shadows
+    light_index: u32,
+    depth_bias: f32,
+    texel_size: f32,
+}
+
+@group(1) @binding(2)
+var<uniform> shadow: ShadowUniform;
+@group(1) @binding(3)
+var shadow_map: texture_depth_2d;
+@group(1) @binding(4)
+var shadow_sampler: sampler_comparison;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) tex_coords: vec2<f32>,
+    @location(3) material: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_normal: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) world_position: vec3<f32>,
+    @location(3) @interpolate(flat) material: u32,
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(in.position, 1.0);
+    out.world_normal = in.normal;
+    out.tex_coords = in.tex_coords;
+    out.world_position = in.position;
+    out.material = in.material;
+    return out;
+}
+
+// Fraction of the shadow-casting light reaching `world_position`, filtered
+// over 3x3 shadow map texels (PCF). Fragments outside the light's frustum are lit.
+fn shadow_factor(world_position: vec3<f32>) -> f32 {
+    let light_position = shadow.light_view_proj * vec4<f32>(world_position, 1.0);
+    let ndc = light_position.xyz / light_position.w;
+    let uv = ndc.xy * vec2<f32>(0.5, -0.5) + vec2<f32>(0.5);
+    if (any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) || ndc.z > 1.0) {
+        return 1.0;
+    }
+
+    let depth = ndc.z - shadow.depth_bias;
+    var lit = 0.0;
+    for (var y = -1; y <= 1; y = y + 1) {
+        for (var x = -1; x <= 1; x = x + 1) {
+            let offset = vec2<f32>(f32(x), f32(y)) * shadow.texel_size;
+            lit = lit + textureSampleCompareLevel(shadow_map, shadow_sampler, uv + offset, depth);
+        }
+    }
+    return lit / 9.0;
+}
+
+// Blinn-Phong shading of a surface of `base_color` by every scene light.
+// Rougher materials get wider, dimmer highlights; metals tint theirs with the
+// base colour and have no diffuse term.
+fn shade(material: Material, base_color: vec3<f32>, world_position: vec3<f32>, world_normal: vec3<f32>) -> vec3<f32> {
+    let normal = normalize(world_normal);
+    let view_dir = normalize(camera.camera_pos.xyz - world_position);
+    let diffuse_color = base_color * (1.0 - material.metallic);
+    let specular_color = mix(vec3<f32>(0.04), base_color, material.metallic);
+    let shininess = exp2(10.0 * (1.0 - material.roughness) + 1.0);
+
+    var color = vec3<f32>(0.0);
+    for (var i = 0u; i < arrayLength(&lights); i = i + 1u) {
+        let light = lights[i];
+        let radiance = light.color * light.intensity;
+
+        if (light.kind == LIGHT_AMBIENT) {
+            color = color + base_color * radiance;
+            continue;
+        }
+
+        var light_dir = -light.direction;
+        var attenuation = 1.0;
+        if (light.kind == LIGHT_POINT || light.kind == LIGHT_SPOT) {
+            let to_light = light.position - world_position;
+            let distance = length(to_light);
+            light_dir = to_light / max(distance, 0.0001);
+            // Inverse square, windowed to reach zero at the light's range
+            let window = saturate(1.0 - pow(distance / light.range, 4.0));
+            attenuation = window * window / max(distance * distance, 0.01);
+            if (light.kind == LIGHT_SPOT) {
+                attenuation = attenuation
+                    * smoothstep(light.cos_outer, light.cos_inner, dot(-light_dir, light.direction));
+            }
+        }
+
+        let n_dot_l = max(dot(normal, light_dir), 0.0);
+        if (n_dot_l <= 0.0 || attenuation <= 0.0) {
+            continue;
+        }
+        if (i == shadow.light_index) {
+            attenuation = attenuation * shadow_factor(world_position);
+        }
+        let half_dir = normalize(light_dir + view_dir);
+        let n_dot_h = max(dot(normal, half_dir), 0.0);
+        let specular = specular_color * pow(n_dot_h, shininess) * (shininess + 8.0) / (8.0 * PI);
+
+        color = color + (diffuse_color + specular) * radiance * n_dot_l * attenuation;
+    }
+    return color;
+}
+
+// Opacity of a fragment, fading ceilings out when the camera is close so
+// interiors stay visible from above.
+fn surface_alpha(alpha: f32, world_position: vec3<f32>, world_normal: vec3<f32>) -> f32 {
+    // Check if this is a ceiling/roof (normal pointing down AND above 2m)
+    let is_ceiling = world_normal.y < -0.9 && world_position.y > 2.0;
+
+    // Calculate distance from camera to fragment
+    let camera_distance = length(camera.camera_pos.xyz - world_position);
+
+    // Fade ceiling when camera is close (within 15 meters)
+    if (is_ceiling && camera_distance < 15.0) {
+        return alpha * smoothstep(5.0, 15.0, camera_distance);
+    }
+    return alpha;
+}
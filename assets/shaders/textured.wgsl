@@ -1,34 +1,18 @@
-struct VertexInput {
-    @location(0) position: vec3<f32>,
-    @location(1) tex_coords: vec2<f32>,
-}
-
-struct VertexOutput {
-    @builtin(position) clip_position: vec4<f32>,
-    @location(0) tex_coords: vec2<f32>,
-}
-
-struct CameraUniform {
-    view_proj: mat4x4<f32>,
-}
+// Fragment stage for textured materials, appended to `scene_common.wgsl`.
+// Multiplies the material colour by the material's texture, tiled
+// `tile_size` metres per repeat.
 
-@group(0) @binding(0)
-var<uniform> camera: CameraUniform;
-
-@vertex
-fn vs_main(model: VertexInput) -> VertexOutput {
-    var out: VertexOutput;
-    out.tex_coords = model.tex_coords;
-    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
-    return out;
-}
-
-@group(1) @binding(0)
+@group(2) @binding(0)
 var t_diffuse: texture_2d<f32>;
-@group(1) @binding(1)
+@group(2) @binding(1)
 var s_diffuse: sampler;
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
-}
\ No newline at end of file
+    let material = materials[in.material];
+    let texel = textureSample(t_diffuse, s_diffuse, in.tex_coords / material.tile_size);
+    let color = material.base_color * texel;
+
+    let alpha = surface_alpha(color.a, in.world_position, in.world_normal);
+    return vec4<f32>(shade(material, color.rgb, in.world_position, in.world_normal), alpha);
+}
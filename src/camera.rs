@@ -29,6 +29,13 @@ impl CameraUniform {
     }
 }
 
+impl Default for CameraUniform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct Camera {
     pub position: Vec3,
     pub target: Vec3,
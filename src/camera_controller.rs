@@ -1,4 +1,5 @@
 use glam::Vec3;
+use std::f32::consts::{PI, TAU};
 use winit::event::{ElementState, MouseButton};
 use winit::keyboard::KeyCode;
 
@@ -12,7 +13,6 @@ pub struct CameraController {
     up_pressed: bool,
     down_pressed: bool,
     mouse_pressed: bool,
-    last_mouse_pos: (f32, f32),
     yaw: f32,
     pitch: f32,
 }
@@ -29,7 +29,6 @@ impl CameraController {
             up_pressed: false,
             down_pressed: false,
             mouse_pressed: false,
-            last_mouse_pos: (0.0, 0.0),
             yaw: 0.0,
             pitch: 0.0,
         }
@@ -93,22 +92,29 @@ impl CameraController {
         }
 
         // Update camera target based on yaw and pitch
-        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
-        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
-        
-        let look_dir = Vec3::new(
-            cos_pitch * sin_yaw,
-            sin_pitch,
-            cos_pitch * cos_yaw,
-        );
-        
-        camera.target = camera.position + look_dir;
+        camera.target = camera.position + look_direction(self.yaw, self.pitch);
     }
 
     pub fn set_initial_direction(&mut self, camera: &crate::camera::Camera) {
-        let dir = (camera.target - camera.position).normalize();
-        self.yaw = dir.z.atan2(dir.x);
-        self.pitch = dir.y.asin();
+        (self.yaw, self.pitch) = yaw_pitch(camera.target - camera.position);
+    }
+
+    /// Where `camera`, steered by this controller, is and looks.
+    pub fn pose(&self, camera: &crate::camera::Camera) -> CameraPose {
+        CameraPose {
+            position: camera.position,
+            yaw: self.yaw,
+            pitch: self.pitch,
+            fov: camera.fov,
+        }
+    }
+
+    /// Put `camera` in `pose`, to be steered on from there.
+    pub fn set_pose(&mut self, camera: &mut crate::camera::Camera, pose: CameraPose) {
+        self.set_yaw_pitch(pose.yaw, pose.pitch);
+        camera.position = pose.position;
+        camera.fov = pose.fov;
+        camera.target = camera.position + look_direction(self.yaw, self.pitch);
     }
 
     pub fn get_yaw(&self) -> f32 {
@@ -123,4 +129,98 @@ impl CameraController {
         self.yaw = yaw;
         self.pitch = pitch.clamp(-89.0_f32.to_radians(), 89.0_f32.to_radians());
     }
-}
\ No newline at end of file
+}
+
+/// Direction a camera turned by `yaw` and `pitch` looks in. A yaw of 0 looks
+/// along +Z and a positive pitch looks up.
+pub fn look_direction(yaw: f32, pitch: f32) -> Vec3 {
+    let (sin_pitch, cos_pitch) = pitch.sin_cos();
+    let (sin_yaw, cos_yaw) = yaw.sin_cos();
+    Vec3::new(cos_pitch * sin_yaw, sin_pitch, cos_pitch * cos_yaw)
+}
+
+/// Yaw and pitch of a camera looking along `direction`.
+fn yaw_pitch(direction: Vec3) -> (f32, f32) {
+    let direction = direction.normalize_or(Vec3::Z);
+    (direction.x.atan2(direction.z), direction.y.clamp(-1.0, 1.0).asin())
+}
+
+/// Where a camera stands, which way it looks, in radians as steered by a
+/// [`CameraController`], and how wide its view is, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraPose {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov: f32,
+}
+
+impl CameraPose {
+    /// Pose of a camera in a scene file, looking at its target.
+    pub fn from_scene(camera: &crate::scene::Camera) -> Self {
+        let (yaw, pitch) = yaw_pitch(camera.target - camera.position);
+        Self {
+            position: camera.position,
+            yaw,
+            pitch,
+            fov: camera.fov,
+        }
+    }
+
+    /// The pose as a camera for a scene file, looking at a target one metre
+    /// ahead.
+    pub fn to_scene(&self) -> crate::scene::Camera {
+        crate::scene::Camera {
+            position: self.position,
+            target: self.position + look_direction(self.yaw, self.pitch),
+            fov: self.fov,
+        }
+    }
+
+    /// The pose `t` of the way from `self` to `other`, turning the shorter
+    /// way round.
+    pub fn lerp(&self, other: &CameraPose, t: f32) -> CameraPose {
+        let turn = (other.yaw - self.yaw + PI).rem_euclid(TAU) - PI;
+        CameraPose {
+            position: self.position.lerp(other.position, t),
+            yaw: self.yaw + turn * t,
+            pitch: self.pitch + (other.pitch - self.pitch) * t,
+            fov: self.fov + (other.fov - self.fov) * t,
+        }
+    }
+}
+
+/// A move of the camera from one pose to another over `duration` seconds,
+/// easing in and out.
+#[derive(Debug, Clone)]
+pub struct CameraTransition {
+    from: CameraPose,
+    to: CameraPose,
+    duration: f32,
+    elapsed: f32,
+}
+
+impl CameraTransition {
+    pub fn new(from: CameraPose, to: CameraPose, duration: f32) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Move `dt` seconds further along and return the pose reached.
+    pub fn advance(&mut self, dt: f32) -> CameraPose {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+        if self.is_finished() {
+            return self.to;
+        }
+        let t = self.elapsed / self.duration;
+        self.from.lerp(&self.to, t * t * (3.0 - 2.0 * t))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
@@ -1,5 +1,25 @@
-use wgpu::{Device, Queue, Instance, Adapter};
-use winit::window::Window;
+use std::fmt;
+use wgpu::{Adapter, Device, Instance, Queue, Surface};
+
+#[derive(Debug)]
+pub enum GpuError {
+    /// Neither a hardware nor a fallback (software) adapter is available.
+    NoAdapter,
+    RequestDevice(wgpu::RequestDeviceError),
+    CreateSurface(wgpu::CreateSurfaceError),
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuError::NoAdapter => write!(f, "no GPU or fallback adapter available"),
+            GpuError::RequestDevice(e) => write!(f, "failed to create device: {}", e),
+            GpuError::CreateSurface(e) => write!(f, "failed to create surface: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GpuError {}
 
 pub struct GpuState {
     pub instance: Instance,
@@ -9,39 +29,58 @@ pub struct GpuState {
 }
 
 impl GpuState {
-    pub async fn new(_window: &Window) -> Self {
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+    pub fn create_instance() -> Instance {
+        wgpu::Instance::new(wgpu::InstanceDescriptor {
             backends: wgpu::Backends::all(),
             ..Default::default()
-        });
-
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: None,
-                force_fallback_adapter: false,
-            })
-            .await
-            .unwrap();
+        })
+    }
+
+    /// Device for `instance` able to present to `compatible_surface`, or for
+    /// headless rendering when there is none.
+    ///
+    /// Prefers a hardware adapter and falls back to wgpu's software adapter, so
+    /// it also runs on CI machines without a GPU.
+    pub async fn new(instance: Instance, compatible_surface: Option<&Surface<'_>>) -> Result<Self, GpuError> {
+        let mut adapter = None;
+        for force_fallback_adapter in [false, true] {
+            adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::HighPerformance,
+                    compatible_surface,
+                    force_fallback_adapter,
+                })
+                .await;
+            if adapter.is_some() {
+                break;
+            }
+        }
+        let adapter = adapter.ok_or(GpuError::NoAdapter)?;
 
+        // Software adapters often fall short of the default limits; ask only
+        // for what this one has
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
+                    required_limits: adapter.limits(),
                     label: None,
                     memory_hints: Default::default(),
                 },
                 None,
             )
             .await
-            .unwrap();
+            .map_err(GpuError::RequestDevice)?;
 
-        Self {
+        Ok(Self {
             instance,
             adapter,
             device,
             queue,
-        }
+        })
     }
-}
\ No newline at end of file
+
+    pub async fn headless() -> Result<Self, GpuError> {
+        Self::new(Self::create_instance(), None).await
+    }
+}
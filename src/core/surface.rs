@@ -1,5 +1,5 @@
-use wgpu::{Surface, SurfaceConfiguration};
-use winit::window::Window;
+use wgpu::{Surface, SurfaceConfiguration, TextureFormat};
+use winit::dpi::PhysicalSize;
 
 pub struct RenderSurface<'window> {
     pub surface: Surface<'window>,
@@ -7,28 +7,28 @@ pub struct RenderSurface<'window> {
 }
 
 impl<'window> RenderSurface<'window> {
+    /// Configure `surface` for `size`, preferring 8-bit sRGB RGBA or BGRA,
+    /// which [`crate::Renderer::capture`] can read back, then any sRGB format.
     pub fn new(
-        window: &'window Window,
-        instance: &wgpu::Instance,
+        surface: Surface<'window>,
         adapter: &wgpu::Adapter,
         device: &wgpu::Device,
+        size: PhysicalSize<u32>,
     ) -> Self {
-        let surface = instance.create_surface(window).unwrap();
-        
         let surface_caps = surface.get_capabilities(adapter);
         let surface_format = surface_caps
             .formats
             .iter()
-            .find(|f| f.is_srgb())
+            .find(|f| matches!(f, TextureFormat::Bgra8UnormSrgb | TextureFormat::Rgba8UnormSrgb))
+            .or_else(|| surface_caps.formats.iter().find(|f| f.is_srgb()))
             .copied()
             .unwrap_or(surface_caps.formats[0]);
 
-        let size = window.inner_size();
         let config = SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
-            width: size.width,
-            height: size.height,
+            width: size.width.max(1),
+            height: size.height.max(1),
             present_mode: wgpu::PresentMode::AutoVsync,
             desired_maximum_frame_latency: 2,
             alpha_mode: surface_caps.alpha_modes[0],
@@ -40,11 +40,19 @@ impl<'window> RenderSurface<'window> {
         Self { surface, config }
     }
 
-    pub fn resize(&mut self, device: &wgpu::Device, new_size: winit::dpi::PhysicalSize<u32>) {
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.config.format
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(self.config.width, self.config.height)
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, new_size: PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
             self.surface.configure(device, &self.config);
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,285 @@
+use super::element_name;
+use crate::scene::loader::{element_meshes, ground_mesh};
+use crate::scene::material::MaterialLibrary;
+use crate::scene::primitives::Mesh;
+use crate::scene::Scene;
+use serde_json::{json, Value};
+use std::path::Path;
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_VERSION: u32 = 2;
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// `scene` as a binary glTF 2.0 file.
+///
+/// The file has a root node named after the scene with a child for the
+/// ground plane and one for every element, named after its type and index
+/// (`room_0`, `sofa_7`) and carrying the index in its `extras`. Each node's
+/// mesh has a primitive per material, in world coordinates: Y up, metres.
+/// Textures are embedded, with texture coordinates scaled by the material's
+/// `tile_size`; textures that can't be read are left out.
+pub fn scene_to_glb(scene: &Scene, asset_dir: &Path) -> Vec<u8> {
+    let materials = MaterialLibrary::from_scene(scene);
+    let mut builder = GltfBuilder::new(&materials);
+
+    let mut children = vec![builder.node("ground", &ground_mesh(), None)];
+    for (index, (element, mesh)) in scene.elements.iter().zip(element_meshes(scene)).enumerate() {
+        children.push(builder.node(&element_name(element, index), &mesh, Some(index)));
+    }
+    builder.nodes.insert(0, json!({ "name": scene.name, "children": children }));
+
+    builder.finish(asset_dir)
+}
+
+/// Collects the JSON and binary chunk of a glTF file.
+struct GltfBuilder<'a> {
+    library: &'a MaterialLibrary,
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    meshes: Vec<Value>,
+    /// Element nodes, numbered from 1 so the root can be inserted in front.
+    nodes: Vec<Value>,
+    /// Material IDs used so far; glTF material `i` is `used_materials[i]`.
+    used_materials: Vec<u32>,
+}
+
+impl<'a> GltfBuilder<'a> {
+    fn new(library: &'a MaterialLibrary) -> Self {
+        Self {
+            library,
+            buffer: Vec::new(),
+            buffer_views: Vec::new(),
+            accessors: Vec::new(),
+            meshes: Vec::new(),
+            nodes: Vec::new(),
+            used_materials: Vec::new(),
+        }
+    }
+
+    /// Add a node showing `mesh`, returning its index once the root is in place.
+    fn node(&mut self, name: &str, mesh: &Mesh, element: Option<usize>) -> usize {
+        let mut node = json!({ "name": name });
+        if let Some(element) = element {
+            node["extras"] = json!({ "element": element });
+        }
+        if !mesh.indices.is_empty() {
+            node["mesh"] = json!(self.mesh(name, mesh));
+        }
+        self.nodes.push(node);
+        self.nodes.len()
+    }
+
+    /// Add `mesh` split into one primitive per material.
+    fn mesh(&mut self, name: &str, mesh: &Mesh) -> usize {
+        let mut triangles_by_material: Vec<(u32, Vec<u32>)> = Vec::new();
+        for triangle in mesh.indices.chunks_exact(3) {
+            let material = mesh.vertices[triangle[0] as usize].material;
+            match triangles_by_material.iter_mut().find(|(id, _)| *id == material) {
+                Some((_, indices)) => indices.extend_from_slice(triangle),
+                None => triangles_by_material.push((material, triangle.to_vec())),
+            }
+        }
+
+        let primitives: Vec<Value> = triangles_by_material
+            .into_iter()
+            .map(|(material, indices)| self.primitive(mesh, material, &indices))
+            .collect();
+        self.meshes.push(json!({ "name": name, "primitives": primitives }));
+        self.meshes.len() - 1
+    }
+
+    /// Primitive drawing the triangles `indices` of `mesh` with `material`,
+    /// holding only the vertices they use.
+    fn primitive(&mut self, mesh: &Mesh, material: u32, indices: &[u32]) -> Value {
+        let tile_size = self.library.materials()[material as usize].tile_size;
+        let mut remap = vec![u32::MAX; mesh.vertices.len()];
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut tex_coords = Vec::new();
+        let mut local_indices = Vec::with_capacity(indices.len());
+
+        for &index in indices {
+            if remap[index as usize] == u32::MAX {
+                let vertex = &mesh.vertices[index as usize];
+                remap[index as usize] = (positions.len() / 3) as u32;
+                positions.extend_from_slice(&vertex.position);
+                normals.extend_from_slice(&vertex.normal);
+                tex_coords.extend(vertex.tex_coords.iter().map(|t| t / tile_size));
+            }
+            local_indices.push(remap[index as usize]);
+        }
+
+        let (min, max) = bounds(&positions);
+        let position = self.accessor(bytemuck::cast_slice(&positions), ARRAY_BUFFER, FLOAT, "VEC3", positions.len() / 3);
+        self.accessors[position]["min"] = json!(min);
+        self.accessors[position]["max"] = json!(max);
+        let normal = self.accessor(bytemuck::cast_slice(&normals), ARRAY_BUFFER, FLOAT, "VEC3", normals.len() / 3);
+        let tex_coord = self.accessor(bytemuck::cast_slice(&tex_coords), ARRAY_BUFFER, FLOAT, "VEC2", tex_coords.len() / 2);
+        let indices = self.accessor(
+            bytemuck::cast_slice(&local_indices),
+            ELEMENT_ARRAY_BUFFER,
+            UNSIGNED_INT,
+            "SCALAR",
+            local_indices.len(),
+        );
+
+        json!({
+            "attributes": { "POSITION": position, "NORMAL": normal, "TEXCOORD_0": tex_coord },
+            "indices": indices,
+            "material": self.material(material),
+        })
+    }
+
+    /// glTF index of material `id`, adding it on first use.
+    fn material(&mut self, id: u32) -> usize {
+        match self.used_materials.iter().position(|&used| used == id) {
+            Some(index) => index,
+            None => {
+                self.used_materials.push(id);
+                self.used_materials.len() - 1
+            }
+        }
+    }
+
+    /// Append `bytes` as a buffer view and return the accessor reading it.
+    fn accessor(&mut self, bytes: &[u8], target: u32, component_type: u32, kind: &str, count: usize) -> usize {
+        let view = self.buffer_view(bytes, Some(target));
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": component_type,
+            "count": count,
+            "type": kind,
+        }));
+        self.accessors.len() - 1
+    }
+
+    fn buffer_view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        let offset = self.buffer.len();
+        self.buffer.extend_from_slice(bytes);
+        pad(&mut self.buffer, 0);
+
+        let mut view = json!({ "buffer": 0, "byteOffset": offset, "byteLength": bytes.len() });
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
+
+    /// Materials, embedded textures and the GLB container around everything.
+    fn finish(mut self, asset_dir: &Path) -> Vec<u8> {
+        let mut images: Vec<(String, Value)> = Vec::new();
+        let mut materials = Vec::new();
+
+        for id in self.used_materials.clone() {
+            let material = &self.library.materials()[id as usize];
+            let mut pbr = json!({
+                "baseColorFactor": material.base_color.extend(material.opacity).to_array(),
+                "metallicFactor": material.metallic,
+                "roughnessFactor": material.roughness,
+            });
+            if let Some(texture) = &material.texture {
+                let image = images.iter().position(|(path, _)| path == texture).or_else(|| {
+                    let image = self.image(&asset_dir.join(texture))?;
+                    images.push((texture.clone(), image));
+                    Some(images.len() - 1)
+                });
+                // Every image is used by one texture of the same index
+                if let Some(image) = image {
+                    pbr["baseColorTexture"] = json!({ "index": image });
+                }
+            }
+
+            let mut gltf_material = json!({ "name": material.name, "pbrMetallicRoughness": pbr });
+            if material.opacity < 1.0 {
+                gltf_material["alphaMode"] = json!("BLEND");
+            }
+            materials.push(gltf_material);
+        }
+
+        let mut document = json!({
+            "asset": {
+                "version": "2.0",
+                "generator": concat!("webgpu-rust-architecture ", env!("CARGO_PKG_VERSION")),
+            },
+            "scene": 0,
+            "scenes": [{ "nodes": [0] }],
+            "nodes": self.nodes,
+            "meshes": self.meshes,
+            "materials": materials,
+            "accessors": self.accessors,
+            "bufferViews": self.buffer_views,
+            "buffers": [{ "byteLength": self.buffer.len() }],
+        });
+        if !images.is_empty() {
+            // Textures repeat, which is glTF's default wrapping
+            document["samplers"] = json!([{}]);
+            document["textures"] = (0..images.len()).map(|source| json!({ "sampler": 0, "source": source })).collect();
+            document["images"] = images.into_iter().map(|(_, image)| image).collect();
+        }
+
+        let mut json = serde_json::to_vec(&document).expect("glTF documents serialize to JSON");
+        pad(&mut json, b' ');
+
+        let mut glb = Vec::with_capacity(12 + 8 + json.len() + 8 + self.buffer.len());
+        let length = 12 + 8 + json.len() + 8 + self.buffer.len();
+        for word in [GLB_MAGIC, GLB_VERSION, length as u32, json.len() as u32, CHUNK_JSON] {
+            glb.extend_from_slice(&word.to_le_bytes());
+        }
+        glb.extend_from_slice(&json);
+        for word in [self.buffer.len() as u32, CHUNK_BIN] {
+            glb.extend_from_slice(&word.to_le_bytes());
+        }
+        glb.extend_from_slice(&self.buffer);
+        glb
+    }
+
+    /// Embed the PNG or JPEG image at `path`, returning its glTF image.
+    fn image(&mut self, path: &Path) -> Option<Value> {
+        let mime_type = match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            _ => {
+                log::warn!("Can't embed texture {}: glTF only supports PNG and JPEG", path.display());
+                return None;
+            }
+        };
+        match std::fs::read(path) {
+            Ok(bytes) => {
+                let view = self.buffer_view(&bytes, None);
+                Some(json!({ "bufferView": view, "mimeType": mime_type }))
+            }
+            Err(e) => {
+                log::warn!("Failed to embed texture {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+}
+
+/// Pad `bytes` with `filler` to the 4-byte alignment GLB chunks and buffer views need.
+fn pad(bytes: &mut Vec<u8>, filler: u8) {
+    while !bytes.len().is_multiple_of(4) {
+        bytes.push(filler);
+    }
+}
+
+/// Per-axis minimum and maximum of packed XYZ `positions`.
+fn bounds(positions: &[f32]) -> ([f32; 3], [f32; 3]) {
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for position in positions.chunks_exact(3) {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(position[axis]);
+            max[axis] = max[axis].max(position[axis]);
+        }
+    }
+    (min, max)
+}
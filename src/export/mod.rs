@@ -0,0 +1,119 @@
+pub mod gltf;
+pub mod obj;
+pub mod shell;
+pub mod stl;
+
+use crate::scene::loader::element_meshes;
+use crate::scene::primitives::Mesh;
+use crate::scene::{Element, Scene};
+use glam::Mat4;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum ExportError {
+    Io { path: PathBuf, source: std::io::Error },
+    /// The file extension doesn't name a format the exporter writes;
+    /// `expected` lists the ones it does.
+    UnsupportedFormat { path: PathBuf, expected: &'static str },
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io { path, source } => write!(f, "failed to write {}: {}", path.display(), source),
+            ExportError::UnsupportedFormat { path, expected } => {
+                write!(f, "don't know how to export {}, expected {}", path.display(), expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExportError::Io { source, .. } => Some(source),
+            ExportError::UnsupportedFormat { .. } => None,
+        }
+    }
+}
+
+/// How the meshes of OBJ and STL files are prepared.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MeshExportOptions {
+    /// Scale of a printed model, as the `100` of 1:100. Scaled models are
+    /// written in millimetres; unscaled ones in metres.
+    pub print_scale: Option<f32>,
+    /// Close every element into solid shells with [`shell::close_shells`].
+    pub watertight: bool,
+}
+
+impl MeshExportOptions {
+    /// File units per metre of the building.
+    pub fn units_per_metre(&self) -> f32 {
+        match self.print_scale {
+            Some(scale) => 1000.0 / scale,
+            None => 1.0,
+        }
+    }
+}
+
+/// Name of an element in exported files: its type, or the type of
+/// furniture, and index, such as `room_0` or `sofa_7`.
+pub fn element_name(element: &Element, index: usize) -> String {
+    let kind = match element {
+        Element::Furniture(furniture) => furniture.furniture_type.kind(),
+        _ => element.kind(),
+    };
+    format!("{}_{}", kind, index)
+}
+
+/// The mesh of every element with geometry, named by [`element_name`] and
+/// prepared as `options` ask. The ground plane is left out.
+pub fn element_objects(scene: &Scene, options: MeshExportOptions) -> Vec<(String, Mesh)> {
+    let scale = Mat4::from_scale(glam::Vec3::splat(options.units_per_metre()));
+    scene
+        .elements
+        .iter()
+        .zip(element_meshes(scene))
+        .enumerate()
+        .filter(|(_, (_, mesh))| !mesh.indices.is_empty())
+        .map(|(index, (element, mut mesh))| {
+            if options.watertight {
+                mesh = shell::close_shells(&mesh, shell::SHEET_THICKNESS);
+            }
+            mesh.transform(scale);
+            (element_name(element, index), mesh)
+        })
+        .collect()
+}
+
+/// Write `scene` to `path` in the format its extension names: `.glb`, or
+/// `.obj` (with its materials in a `.mtl` file next to it) and `.stl`
+/// prepared as `options` ask. Texture paths in the scene's materials are
+/// relative to `asset_dir`.
+pub fn export_scene(scene: &Scene, asset_dir: &Path, path: &Path, options: MeshExportOptions) -> Result<(), ExportError> {
+    let write = |path: &Path, contents: &[u8]| {
+        std::fs::write(path, contents).map_err(|source| ExportError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    };
+
+    let extension = path.extension().and_then(|extension| extension.to_str()).map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("glb") => write(path, &gltf::scene_to_glb(scene, asset_dir)),
+        Some("obj") => {
+            let mtl_path = path.with_extension("mtl");
+            let mtl_name = mtl_path.file_name().and_then(|name| name.to_str()).unwrap_or("scene.mtl");
+            let (obj, mtl) = obj::scene_to_obj(scene, asset_dir, mtl_name, options);
+            write(path, obj.as_bytes())?;
+            write(&mtl_path, mtl.as_bytes())
+        }
+        Some("stl") => write(path, &stl::scene_to_stl(scene, options)),
+        _ => Err(ExportError::UnsupportedFormat {
+            path: path.to_path_buf(),
+            expected: "a .glb, .obj or .stl file",
+        }),
+    }
+}
@@ -0,0 +1,123 @@
+use super::{element_objects, MeshExportOptions};
+use crate::scene::material::MaterialLibrary;
+use crate::scene::primitives::Mesh;
+use crate::scene::Scene;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// `scene` as a Wavefront OBJ file and the MTL file it names `mtl_name`.
+///
+/// Every element with geometry is an object named like its glTF node
+/// (`room_0`, `sofa_7`), with its faces grouped by the scene's material
+/// names. The ground plane is left out. Coordinates are Y up, in metres or
+/// millimetres at `options.print_scale`. Texture paths are relative to
+/// `asset_dir`.
+pub fn scene_to_obj(scene: &Scene, asset_dir: &Path, mtl_name: &str, options: MeshExportOptions) -> (String, String) {
+    let materials = MaterialLibrary::from_scene(scene);
+    let objects = element_objects(scene, options);
+    let obj = objects_to_obj(&objects, &materials, mtl_name);
+    let mtl = materials_to_mtl(&materials, &used_materials(&objects), asset_dir);
+    (obj, mtl)
+}
+
+/// Named meshes as OBJ objects, using materials from `library` by name.
+pub fn objects_to_obj(objects: &[(String, Mesh)], library: &MaterialLibrary, mtl_name: &str) -> String {
+    let mut obj = String::new();
+    writeln!(obj, "# {} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")).unwrap();
+    writeln!(obj, "mtllib {}", mtl_name).unwrap();
+
+    // OBJ indices count from 1 across the whole file
+    let (mut positions, mut tex_coords, mut normals) = (1, 1, 1);
+    for (name, mesh) in objects {
+        writeln!(obj, "o {}", obj_name(name)).unwrap();
+
+        let mut position_indices: HashMap<[u32; 3], usize> = HashMap::new();
+        let mut vertex_positions = Vec::with_capacity(mesh.vertices.len());
+        for vertex in &mesh.vertices {
+            let next = positions + position_indices.len();
+            let index = *position_indices.entry(vertex.position.map(f32::to_bits)).or_insert_with(|| {
+                let [x, y, z] = vertex.position;
+                writeln!(obj, "v {} {} {}", x, y, z).unwrap();
+                next
+            });
+            vertex_positions.push(index);
+        }
+        positions += position_indices.len();
+
+        for vertex in &mesh.vertices {
+            let tile_size = library.materials()[vertex.material as usize].tile_size;
+            let [u, v] = vertex.tex_coords.map(|t| t / tile_size);
+            // OBJ texture coordinates start at the bottom of the image
+            writeln!(obj, "vt {} {}", u, 1.0 - v).unwrap();
+        }
+        for vertex in &mesh.vertices {
+            let [x, y, z] = vertex.normal;
+            writeln!(obj, "vn {} {} {}", x, y, z).unwrap();
+        }
+
+        let mut current_material = None;
+        for triangle in mesh.indices.chunks_exact(3) {
+            let material = mesh.vertices[triangle[0] as usize].material;
+            if current_material != Some(material) {
+                writeln!(obj, "usemtl {}", obj_name(&library.materials()[material as usize].name)).unwrap();
+                current_material = Some(material);
+            }
+            let corners: Vec<String> = triangle
+                .iter()
+                .map(|&index| {
+                    let index = index as usize;
+                    format!("{}/{}/{}", vertex_positions[index], tex_coords + index, normals + index)
+                })
+                .collect();
+            writeln!(obj, "f {}", corners.join(" ")).unwrap();
+        }
+        tex_coords += mesh.vertices.len();
+        normals += mesh.vertices.len();
+    }
+    obj
+}
+
+/// MTL definitions of the materials `ids` of `library`, with texture paths
+/// relative to `asset_dir` made absolute where they exist.
+pub fn materials_to_mtl(library: &MaterialLibrary, ids: &[u32], asset_dir: &Path) -> String {
+    let mut mtl = String::new();
+    writeln!(mtl, "# {} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")).unwrap();
+    for &id in ids {
+        let material = &library.materials()[id as usize];
+        let [r, g, b] = material.base_color.to_array();
+        writeln!(mtl).unwrap();
+        writeln!(mtl, "newmtl {}", obj_name(&material.name)).unwrap();
+        writeln!(mtl, "Kd {} {} {}", r, g, b).unwrap();
+        writeln!(mtl, "Ns {}", (1.0 - material.roughness).powi(2) * 1000.0).unwrap();
+        writeln!(mtl, "Pr {}", material.roughness).unwrap();
+        writeln!(mtl, "Pm {}", material.metallic).unwrap();
+        writeln!(mtl, "d {}", material.opacity).unwrap();
+        writeln!(mtl, "illum 2").unwrap();
+        if let Some(texture) = &material.texture {
+            let path = asset_dir.join(texture);
+            let path = path.canonicalize().unwrap_or(path);
+            writeln!(mtl, "map_Kd {}", path.display()).unwrap();
+        }
+    }
+    mtl
+}
+
+/// Material IDs used by `objects`, in order of first use.
+fn used_materials(objects: &[(String, Mesh)]) -> Vec<u32> {
+    let mut used = Vec::new();
+    for (_, mesh) in objects {
+        for triangle in mesh.indices.chunks_exact(3) {
+            let material = mesh.vertices[triangle[0] as usize].material;
+            if !used.contains(&material) {
+                used.push(material);
+            }
+        }
+    }
+    used
+}
+
+/// `name` without whitespace, which OBJ and MTL statements split on.
+fn obj_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("_")
+}
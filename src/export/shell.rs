@@ -0,0 +1,261 @@
+use crate::scene::primitives::{Mesh, Vertex};
+use glam::Vec3;
+use std::collections::HashMap;
+
+/// Positions closer than this (in metres) are welded into one.
+const WELD_TOLERANCE: f32 = 1e-4;
+
+/// Thickness given to single-sided surfaces such as floors and ceilings when
+/// they are closed into solids: 1mm at 1:100.
+pub const SHEET_THICKNESS: f32 = 0.1;
+
+/// The mesh's triangles as indices into its distinct positions.
+///
+/// Meshes keep separate vertices where normals or texture coordinates
+/// change, so connectivity is only visible after welding.
+pub struct WeldedMesh {
+    pub positions: Vec<Vec3>,
+    /// For every vertex of the source mesh, the index of its position.
+    pub vertex_positions: Vec<u32>,
+    /// Triangles with positive area, in welded indices.
+    pub triangles: Vec<[u32; 3]>,
+    /// Source mesh indices of the three vertices of each triangle in
+    /// `triangles`.
+    pub sources: Vec<[u32; 3]>,
+}
+
+impl WeldedMesh {
+    pub fn new(mesh: &Mesh) -> Self {
+        let mut positions = Vec::new();
+        let mut lookup: HashMap<[i64; 3], u32> = HashMap::new();
+        let vertex_positions = mesh
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let position = Vec3::from(vertex.position);
+                let key = (position / WELD_TOLERANCE).round().as_i64vec3().to_array();
+                *lookup.entry(key).or_insert_with(|| {
+                    positions.push(position);
+                    positions.len() as u32 - 1
+                })
+            })
+            .collect::<Vec<u32>>();
+
+        let mut triangles = Vec::new();
+        let mut sources = Vec::new();
+        for triangle in mesh.indices.chunks_exact(3) {
+            let welded = [0, 1, 2].map(|k| vertex_positions[triangle[k] as usize]);
+            if welded[0] == welded[1] || welded[1] == welded[2] || welded[2] == welded[0] {
+                continue;
+            }
+            triangles.push(welded);
+            sources.push([triangle[0], triangle[1], triangle[2]]);
+        }
+
+        Self {
+            positions,
+            vertex_positions,
+            triangles,
+            sources,
+        }
+    }
+
+    /// How many times each directed edge is used by a triangle.
+    fn directed_edges(&self) -> HashMap<(u32, u32), u32> {
+        let mut edges = HashMap::new();
+        for triangle in &self.triangles {
+            for k in 0..3 {
+                *edges.entry((triangle[k], triangle[(k + 1) % 3])).or_insert(0) += 1;
+            }
+        }
+        edges
+    }
+
+    /// Unit vector in the plane of `triangle`, perpendicular to its edge
+    /// `a`-`b` and pointing into the triangle.
+    fn inward(&self, triangle: usize, a: u32, b: u32) -> Vec3 {
+        let third = self.triangles[triangle].into_iter().find(|&i| i != a && i != b).unwrap();
+        let (a, b, c) = (self.positions[a as usize], self.positions[b as usize], self.positions[third as usize]);
+        let edge = (b - a).normalize_or_zero();
+        let across = c - a;
+        (across - edge * across.dot(edge)).normalize_or_zero()
+    }
+
+    /// Whether every edge is shared by exactly two triangles running along it
+    /// in opposite directions, so the surface encloses a volume.
+    pub fn is_watertight(&self) -> bool {
+        let edges = self.directed_edges();
+        edges
+            .iter()
+            .all(|(&(a, b), &count)| count == 1 && edges.get(&(b, a)) == Some(&1))
+    }
+
+    /// Groups of triangles forming one closed surface each, so solids that
+    /// touch along an edge or face, like mitered walls or the legs of a table
+    /// and its top, stay separate shells.
+    ///
+    /// Where more than two triangles share an edge, each triangle is joined to
+    /// the first one met when turning about the edge into the solid behind it.
+    /// Faces lying back to back, as where two solids touch, are passed over.
+    fn components(&self) -> Vec<Vec<usize>> {
+        let mut edge_triangles: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            for k in 0..3 {
+                edge_triangles.entry((triangle[k], triangle[(k + 1) % 3])).or_default().push(index);
+            }
+        }
+
+        let mut parent: Vec<usize> = (0..self.triangles.len()).collect();
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            for k in 0..3 {
+                let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+                let Some(twins) = edge_triangles.get(&(b, a)) else {
+                    continue;
+                };
+                let inward = self.inward(index, a, b);
+                let next = twins
+                    .iter()
+                    .map(|&twin| (twin, turn(inward, self.inward(twin, b, a), a, b, &self.positions)))
+                    .filter(|&(_, angle)| angle > 1e-4)
+                    .min_by(|x, y| x.1.total_cmp(&y.1));
+                if let Some((twin, _)) = next {
+                    let (x, y) = (root(&mut parent, index), root(&mut parent, twin));
+                    parent[x] = y;
+                }
+            }
+        }
+
+        let mut components: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut order = Vec::new();
+        for index in 0..self.triangles.len() {
+            let group = root(&mut parent, index);
+            components
+                .entry(group)
+                .or_insert_with(|| {
+                    order.push(group);
+                    Vec::new()
+                })
+                .push(index);
+        }
+        order.into_iter().map(|group| components.remove(&group).unwrap()).collect()
+    }
+}
+
+/// Whether every shell of `mesh` encloses a volume. Shells may touch or
+/// intersect each other, which slicers for 3D printing handle as a union.
+pub fn is_watertight(mesh: &Mesh) -> bool {
+    WeldedMesh::new(mesh).shells().iter().all(WeldedMesh::is_watertight)
+}
+
+/// `mesh` with every open shell closed into a solid.
+///
+/// Shells that already enclose a volume, such as walls, boxes and columns,
+/// are kept as they are. Open ones, such as the single-sided planes of
+/// floors and ceilings, are thickened by `thickness` behind their faces, with
+/// side walls along their open edges. Zero-area triangles are dropped.
+pub fn close_shells(mesh: &Mesh, thickness: f32) -> Mesh {
+    let mut closed = Mesh::new();
+    for shell in WeldedMesh::new(mesh).shells() {
+        if shell.is_watertight() {
+            closed.append(&shell.source_mesh(mesh));
+        } else {
+            closed.append(&shell.thickened(mesh, thickness));
+        }
+    }
+    closed
+}
+
+impl WeldedMesh {
+    /// The mesh split into its shells.
+    fn shells(&self) -> Vec<WeldedMesh> {
+        self.components()
+            .into_iter()
+            .map(|component| WeldedMesh {
+                positions: self.positions.clone(),
+                vertex_positions: self.vertex_positions.clone(),
+                triangles: component.iter().map(|&t| self.triangles[t]).collect(),
+                sources: component.iter().map(|&t| self.sources[t]).collect(),
+            })
+            .collect()
+    }
+
+    /// The triangles as a mesh with the source mesh's vertex attributes.
+    fn source_mesh(&self, mesh: &Mesh) -> Mesh {
+        let mut part = Mesh::new();
+        let mut remap: HashMap<u32, u32> = HashMap::new();
+        for source in &self.sources {
+            let indices = source.map(|index| *remap.entry(index).or_insert_with(|| part.add_vertex(mesh.vertices[index as usize])));
+            part.add_triangle(indices[0], indices[1], indices[2]);
+        }
+        part
+    }
+
+    /// The open surface and a copy of it offset by `thickness` against its
+    /// normals, facing the other way, joined along the open edges.
+    fn thickened(&self, mesh: &Mesh, thickness: f32) -> Mesh {
+        // Offset each position against the area-weighted normal of its faces
+        let mut offsets = vec![Vec3::ZERO; self.positions.len()];
+        for triangle in &self.triangles {
+            let [a, b, c] = triangle.map(|i| self.positions[i as usize]);
+            let normal = (b - a).cross(c - a);
+            for &i in triangle {
+                offsets[i as usize] -= normal;
+            }
+        }
+        let offsets: Vec<Vec3> = offsets.into_iter().map(|offset| offset.normalize_or_zero() * thickness).collect();
+
+        let mut part = Mesh::new();
+        let mut front: HashMap<u32, u32> = HashMap::new();
+        for source in &self.sources {
+            let indices = source.map(|index| *front.entry(index).or_insert_with(|| part.add_vertex(mesh.vertices[index as usize])));
+            part.add_triangle(indices[0], indices[1], indices[2]);
+        }
+        let mut back: HashMap<u32, u32> = HashMap::new();
+        for source in &self.sources {
+            let indices = source.map(|index| {
+                *back.entry(index).or_insert_with(|| {
+                    let mut vertex = mesh.vertices[index as usize];
+                    let offset = offsets[self.vertex_positions[index as usize] as usize];
+                    vertex.position = (Vec3::from(vertex.position) + offset).to_array();
+                    vertex.normal = (-Vec3::from(vertex.normal)).to_array();
+                    part.add_vertex(vertex)
+                })
+            });
+            part.add_triangle(indices[0], indices[2], indices[1]);
+        }
+
+        // A directed edge without its twin lies on the open boundary
+        let edges = self.directed_edges();
+        let material = mesh.vertices[self.sources[0][0] as usize].material;
+        for &(a, b) in edges.keys().filter(|&&(a, b)| !edges.contains_key(&(b, a))) {
+            let (a_back, b_back) = (self.positions[a as usize] + offsets[a as usize], self.positions[b as usize] + offsets[b as usize]);
+            let (a, b) = (self.positions[a as usize], self.positions[b as usize]);
+            let normal = (a - b).cross(a_back - b).normalize_or_zero();
+            let side = [b, a, a_back, b_back].map(|position| {
+                let mut vertex = Vertex::new(position, normal, glam::Vec2::ZERO);
+                vertex.material = material;
+                part.add_vertex(vertex)
+            });
+            part.add_quad(side[0], side[1], side[2], side[3]);
+        }
+        part
+    }
+}
+
+/// Angle in `0..TAU` turning about the edge `a`-`b` from the face going
+/// `from` to the face going `to`, starting into the solid behind the first.
+///
+/// A triangle with edge `a`→`b` faces `(b - a) × from`, so its solid lies on
+/// the side of a clockwise turn about `b - a`.
+fn turn(from: Vec3, to: Vec3, a: u32, b: u32, positions: &[Vec3]) -> f32 {
+    let axis = (positions[b as usize] - positions[a as usize]).normalize_or_zero();
+    let angle = (-axis.dot(from.cross(to))).atan2(from.dot(to));
+    angle.rem_euclid(std::f32::consts::TAU)
+}
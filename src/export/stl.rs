@@ -0,0 +1,56 @@
+use super::{element_objects, MeshExportOptions};
+use crate::scene::primitives::Mesh;
+use crate::scene::Scene;
+use glam::Vec3;
+
+/// Size of the free-form header that starts a binary STL file.
+const HEADER_SIZE: usize = 80;
+
+/// `scene` as a binary STL file of every element but the ground plane, in
+/// metres or millimetres at `options.print_scale`.
+pub fn scene_to_stl(scene: &Scene, options: MeshExportOptions) -> Vec<u8> {
+    let objects = element_objects(scene, options);
+    let header = format!("{} {}: {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), scene.name);
+    meshes_to_stl(objects.iter().map(|(_, mesh)| mesh), &header)
+}
+
+/// The triangles of `meshes` as a binary STL file.
+///
+/// STL has no notion of up, but slicers and printers expect Z up, so the
+/// scene's Y up coordinates are turned to keep floors on the build plate.
+/// Facet normals are recomputed from the winding and zero-area triangles
+/// are dropped. `header` is cut to the 80 bytes STL leaves for it.
+pub fn meshes_to_stl<'a>(meshes: impl IntoIterator<Item = &'a Mesh>, header: &str) -> Vec<u8> {
+    let mut facets = Vec::new();
+    let mut count: u32 = 0;
+    for mesh in meshes {
+        for triangle in mesh.indices.chunks_exact(3) {
+            let corners = [0, 1, 2].map(|k| z_up(Vec3::from(mesh.vertices[triangle[k] as usize].position)));
+            let normal = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+            if normal.length_squared() == 0.0 {
+                continue;
+            }
+            for vector in [normal.normalize()].into_iter().chain(corners) {
+                for component in vector.to_array() {
+                    facets.extend_from_slice(&component.to_le_bytes());
+                }
+            }
+            // Attribute byte count, unused
+            facets.extend_from_slice(&0u16.to_le_bytes());
+            count += 1;
+        }
+    }
+
+    let mut stl = Vec::with_capacity(HEADER_SIZE + 4 + facets.len());
+    let header = header.as_bytes();
+    stl.extend_from_slice(&header[..header.len().min(HEADER_SIZE)]);
+    stl.resize(HEADER_SIZE, b' ');
+    stl.extend_from_slice(&count.to_le_bytes());
+    stl.extend_from_slice(&facets);
+    stl
+}
+
+/// A Y up position turned Z up, keeping the coordinate system right-handed.
+fn z_up(position: Vec3) -> Vec3 {
+    Vec3::new(position.x, -position.z, position.y)
+}
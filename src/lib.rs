@@ -2,6 +2,10 @@ pub mod core;
 pub mod renderer;
 pub mod utils;
 pub mod scene;
+pub mod export;
+pub mod plan;
+pub mod takeoff;
+pub mod screenshot;
 pub mod shaders;
 pub mod camera;
 pub mod camera_controller;
@@ -1,38 +1,418 @@
 use webgpu_rust_architecture::{
-    camera::{Camera, CameraUniform},
-    camera_controller::CameraController,
-    scene::{loader::{load_scene_from_file, scene_to_mesh}, primitives::Vertex},
-    shaders::SCENE_SHADER,
+    camera::Camera,
+    export::{export_scene, MeshExportOptions},
+    plan::{write_floor_plans, DEFAULT_CUT_HEIGHT},
+    takeoff::{takeoff, takeoff_to_csv, takeoff_to_json},
+    screenshot::{read_screenshot_info, save_screenshot, scene_hash, ScreenshotInfo},
+    camera_controller::{CameraController, CameraPose, CameraTransition},
+    renderer::{offscreen::OffscreenRenderer, shadow::ShadowSettings, Renderer},
+    scene::{
+        catalog::{SceneCatalog, EXAMPLES_DIR},
+        levels::levels,
+        loader::{element_meshes, load_scene_from_file, upgrade_scene_file},
+        viewpoints::{load_saved_viewpoints, save_viewpoints, scene_viewpoints},
+        watch::SceneWatcher,
+        Scene, Viewpoint,
+    },
 };
+use chrono::{NaiveDate, NaiveTime};
+use clap::{error::ErrorKind, parser::ValueSource, ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand};
 use glam::Vec3;
 use winit::{
+    application::ApplicationHandler,
     dpi::PhysicalSize,
-    event::{Event, WindowEvent, ElementState, DeviceEvent},
-    event_loop::EventLoop,
-    keyboard::{PhysicalKey, KeyCode},
-    window::Window,
+    event::{DeviceEvent, DeviceId, ElementState, WindowEvent},
+    event_loop::{ActiveEventLoop, EventLoop},
+    keyboard::{KeyCode, PhysicalKey},
+    window::{Window, WindowId},
 };
-use wgpu::util::DeviceExt;
-use std::path::Path;
-
-fn create_depth_texture(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::TextureView {
-    let size = wgpu::Extent3d {
-        width: config.width,
-        height: config.height,
-        depth_or_array_layers: 1,
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Example shown when no scene is named.
+const DEFAULT_SCENE: &str = "3_simple_room";
+
+/// Size of the window, and of images rendered without one.
+const DEFAULT_RESOLUTION: &str = "1024x768";
+
+/// How often the viewer checks whether the scene's files have changed.
+const RELOAD_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long the viewer takes to move the camera to a viewpoint, in seconds.
+const VIEWPOINT_TRANSITION_SECONDS: f32 = 1.0;
+
+/// View, render and take apart architectural scenes described in JSON.
+///
+/// Scenes are named by the path of their file, or by the number or name of a
+/// scene in `--scene-dir`, such as `7`, `7_multi_level` or `multi_level`.
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Directory of scenes to find by number or name, and to page through in
+    /// the viewer
+    #[arg(long, global = true, value_name = "DIR", default_value = EXAMPLES_DIR)]
+    scene_dir: PathBuf,
+    /// Without a command, the scene is opened in the viewer.
+    #[command(flatten)]
+    view: ViewArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Open a scene in a window (the default)
+    View(ViewArgs),
+    /// Render a scene to PNG without opening a window
+    Render(RenderArgs),
+    /// Check scenes for problems, or every scene in --scene-dir if none are named
+    Validate(ValidateArgs),
+    /// Export a scene's geometry as glTF, OBJ or STL
+    Export(ExportArgs),
+    /// Print what a scene is made of
+    Stats(SceneArg),
+    /// Draw floor plans of each level as SVG or DXF
+    Plan(PlanArgs),
+    /// Report areas, volumes and counts for estimating
+    Takeoff(TakeoffArgs),
+    /// Rewrite scene files in the current format
+    Upgrade(UpgradeArgs),
+}
+
+#[derive(Args)]
+struct SceneArg {
+    /// Scene file, or the number or name of a scene in --scene-dir [default: 3_simple_room]
+    scene: Option<String>,
+}
+
+/// Where to look from instead of the scene's own camera.
+#[derive(Args, Clone, Default)]
+struct CameraArgs {
+    /// Start from a viewpoint of the scene, or one saved for it, by name
+    #[arg(long, value_name = "NAME")]
+    viewpoint: Option<String>,
+    /// Camera position
+    #[arg(long, value_name = "X,Y,Z", value_parser = parse_vec3, allow_hyphen_values = true)]
+    position: Option<Vec3>,
+    /// Camera heading in degrees, 0 looking along +Z
+    #[arg(long, value_name = "DEGREES", allow_negative_numbers = true)]
+    yaw: Option<f32>,
+    /// Camera tilt in degrees, positive looking up
+    #[arg(long, value_name = "DEGREES", allow_negative_numbers = true)]
+    pitch: Option<f32>,
+    /// Vertical field of view in degrees
+    #[arg(long, value_name = "DEGREES")]
+    fov: Option<f32>,
+}
+
+/// Quality of the sun's shadows.
+#[derive(Args, Clone, Copy)]
+struct ShadowArgs {
+    /// Width and height of the sun's shadow map in texels
+    #[arg(long, value_name = "TEXELS", default_value_t = ShadowSettings::default().resolution, value_parser = clap::value_parser!(u32).range(1..))]
+    shadow_resolution: u32,
+    /// Metres surfaces move towards the sun before testing for shadow
+    #[arg(long, value_name = "METRES", default_value_t = ShadowSettings::default().bias, value_parser = parse_non_negative)]
+    shadow_bias: f32,
+}
+
+impl ShadowArgs {
+    fn settings(&self) -> ShadowSettings {
+        ShadowSettings {
+            resolution: self.shadow_resolution,
+            bias: self.shadow_bias,
+        }
+    }
+}
+
+#[derive(Args)]
+struct ViewArgs {
+    #[command(flatten)]
+    scene: SceneArg,
+    /// Window size
+    #[arg(long, value_name = "WIDTHxHEIGHT", value_parser = parse_resolution, default_value = DEFAULT_RESOLUTION)]
+    resolution: Resolution,
+    #[command(flatten)]
+    camera: CameraArgs,
+    #[command(flatten)]
+    shadows: ShadowArgs,
+}
+
+#[derive(Args)]
+struct RenderArgs {
+    #[command(flatten)]
+    scene: SceneArg,
+    /// PNG to write, or directory for a sun study [default: a file in
+    /// screenshots/ named after the camera]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Image size [default: 1024x768, or the screenshot's]
+    #[arg(long, value_name = "WIDTHxHEIGHT", value_parser = parse_resolution)]
+    resolution: Option<Resolution>,
+    /// Render the view recorded in a screenshot, or failing that in its
+    /// filename; a scene and camera options given as well take precedence
+    #[arg(long, value_name = "PNG")]
+    screenshot: Option<PathBuf>,
+    #[command(flatten)]
+    camera: CameraArgs,
+    /// Render the day's sunlight at the scene's site instead, one image per step
+    #[arg(long, value_name = "YYYY-MM-DD", value_parser = parse_date)]
+    sun_study: Option<NaiveDate>,
+    /// Minutes between the images of a sun study
+    #[arg(long, value_name = "MINUTES", default_value_t = 60, requires = "sun_study", value_parser = clap::value_parser!(u32).range(1..))]
+    step: u32,
+    #[command(flatten)]
+    shadows: ShadowArgs,
+}
+
+#[derive(Args)]
+struct ValidateArgs {
+    /// Scene files, or numbers or names of scenes in --scene-dir
+    scenes: Vec<String>,
+}
+
+#[derive(Args)]
+struct ExportArgs {
+    #[command(flatten)]
+    scene: SceneArg,
+    /// File to write, ending in .glb, .obj or .stl
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Scale OBJ and STL models to millimetres at this scale
+    #[arg(long, value_name = "1:N", value_parser = parse_print_scale)]
+    print_scale: Option<f32>,
+    /// Close every element into solid shells, giving floors and ceilings a thickness
+    #[arg(long)]
+    watertight: bool,
+}
+
+#[derive(Args)]
+struct PlanArgs {
+    #[command(flatten)]
+    scene: SceneArg,
+    /// File to write, ending in .svg or .dxf; scenes with several levels get
+    /// a numbered file per level
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Height above each level the walls are cut at, in metres
+    #[arg(long, value_name = "METRES", default_value_t = DEFAULT_CUT_HEIGHT, value_parser = parse_positive)]
+    cut_height: f32,
+    /// Drawing scale of SVG plans
+    #[arg(long, value_name = "1:N", value_parser = parse_print_scale, default_value = "1:100")]
+    print_scale: f32,
+}
+
+#[derive(Args)]
+struct TakeoffArgs {
+    #[command(flatten)]
+    scene: SceneArg,
+    /// Write CSV instead of JSON, as does an output file ending in .csv
+    #[arg(long)]
+    csv: bool,
+    /// File to write instead of printing the report
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct UpgradeArgs {
+    /// Scene files, or directories of them
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+}
+
+/// Size of an image or window in pixels.
+#[derive(Debug, Clone, Copy)]
+struct Resolution {
+    width: u32,
+    height: u32,
+}
+
+impl Resolution {
+    fn aspect(self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+}
+
+fn parse_resolution(text: &str) -> Result<Resolution, String> {
+    let size = |value: &str| value.trim().parse::<u32>().ok().filter(|&pixels| pixels > 0);
+    match text.split_once(['x', 'X']).map(|(width, height)| (size(width), size(height))) {
+        Some((Some(width), Some(height))) => Ok(Resolution { width, height }),
+        _ => Err("expected a size such as 1920x1080".to_string()),
+    }
+}
+
+fn parse_vec3(text: &str) -> Result<Vec3, String> {
+    let values: Vec<f32> = text
+        .split(',')
+        .map(|value| value.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    match values[..] {
+        [x, y, z] => Ok(Vec3::new(x, y, z)),
+        _ => Err("expected three numbers such as 1.5,2,-4".to_string()),
+    }
+}
+
+fn parse_date(text: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").map_err(|_| "expected a date such as 2024-06-21".to_string())
+}
+
+fn parse_non_negative(text: &str) -> Result<f32, String> {
+    match text.parse::<f32>() {
+        Ok(value) if value >= 0.0 && value.is_finite() => Ok(value),
+        _ => Err("expected a number of 0 or more".to_string()),
+    }
+}
+
+fn parse_positive(text: &str) -> Result<f32, String> {
+    match text.parse::<f32>() {
+        Ok(value) if value > 0.0 && value.is_finite() => Ok(value),
+        _ => Err("expected a positive number".to_string()),
+    }
+}
+
+/// Parse a print scale such as "1:100" or "100" into its denominator.
+fn parse_print_scale(scale: &str) -> Result<f32, String> {
+    parse_positive(scale.strip_prefix("1:").unwrap_or(scale)).map_err(|_| "expected a scale such as 1:100".to_string())
+}
+
+/// Directory texture paths in a scene file are relative to.
+fn scene_dir(scene_file: &str) -> &Path {
+    Path::new(scene_file).parent().unwrap_or(Path::new(""))
+}
+
+/// Scene file named on the command line: the path of a scene file, or the
+/// number or name of a scene in `catalog`.
+fn resolve_scene_file(catalog: &SceneCatalog, arg: Option<&str>) -> Result<String, String> {
+    let arg = arg.unwrap_or(DEFAULT_SCENE);
+    if Path::new(arg).is_file() {
+        return Ok(arg.to_string());
+    }
+    if arg.ends_with(".json") {
+        return Err(format!("Scene file '{}' not found", arg));
+    }
+
+    match catalog.find(arg) {
+        Some(file) => Ok(file.to_string_lossy().into_owned()),
+        None if catalog.is_empty() => Err(format!(
+            "No scene file named '{}', and no scenes in {}",
+            arg,
+            catalog.dir().display()
+        )),
+        None => {
+            let names: Vec<String> = catalog
+                .files()
+                .iter()
+                .filter_map(|file| file.file_stem())
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .collect();
+            Err(format!(
+                "No scene file or scene named '{}'. Scenes in {}: {}",
+                arg,
+                catalog.dir().display(),
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+/// Resolve `scene` with [`resolve_scene_file`], or report why it can't be and exit.
+fn scene_file_or_exit(catalog: &SceneCatalog, scene: &SceneArg) -> String {
+    match resolve_scene_file(catalog, scene.scene.as_deref()) {
+        Ok(scene_file) => scene_file,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Load the scene at `scene_file`, or report why it can't be and exit.
+fn load_scene_or_exit(scene_file: &str) -> Scene {
+    match load_scene_from_file(Path::new(scene_file)) {
+        Ok(scene) => scene,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn offscreen_renderer_or_exit(scene_file: &str, shadow_settings: ShadowSettings) -> OffscreenRenderer {
+    match pollster::block_on(OffscreenRenderer::new()) {
+        Ok(renderer) => renderer
+            .with_asset_dir(scene_dir(scene_file))
+            .with_shadow_settings(shadow_settings),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The scene's camera with `overrides` applied, and a controller steering it.
+///
+/// A new position keeps looking at the scene camera's target unless a yaw or
+/// pitch is given too.
+fn scene_camera(scene_file: &str, scene: &Scene, aspect: f32, overrides: &CameraArgs) -> (Camera, CameraController) {
+    let scene_camera = match &overrides.viewpoint {
+        Some(name) => viewpoint_or_exit(scene_file, scene, name).camera,
+        None => scene.camera.clone(),
     };
-    let desc = wgpu::TextureDescriptor {
-        label: Some("Depth Texture"),
-        size,
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Depth32Float,
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-        view_formats: &[],
+    let mut camera = Camera::from_scene(&scene_camera, aspect);
+    let mut camera_controller = CameraController::new(10.0, 0.003);
+    if let Some(position) = overrides.position {
+        camera.position = position;
+    }
+    if let Some(fov) = overrides.fov {
+        camera.fov = fov;
+    }
+    camera_controller.set_initial_direction(&camera);
+
+    if overrides.yaw.is_some() || overrides.pitch.is_some() {
+        let yaw = overrides.yaw.map_or(camera_controller.get_yaw(), f32::to_radians);
+        let pitch = overrides.pitch.map_or(camera_controller.get_pitch(), f32::to_radians);
+        camera_controller.set_yaw_pitch(yaw, pitch);
+        camera_controller.update_camera(&mut camera, 0.0);
+    }
+    (camera, camera_controller)
+}
+
+/// The viewpoint of the scene at `scene_file`, or saved for it, called `name`.
+fn viewpoint_or_exit(scene_file: &str, scene: &Scene, name: &str) -> Viewpoint {
+    let viewpoints = match scene_viewpoints(Path::new(scene_file), scene) {
+        Ok(viewpoints) => viewpoints,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
     };
-    let texture = device.create_texture(&desc);
-    texture.create_view(&wgpu::TextureViewDescriptor::default())
+    if let Some(viewpoint) = viewpoints.iter().find(|viewpoint| viewpoint.name == name) {
+        return viewpoint.clone();
+    }
+
+    let names: Vec<&str> = viewpoints.iter().map(|viewpoint| viewpoint.name.as_str()).collect();
+    if names.is_empty() {
+        eprintln!("Error: Scene '{}' has no viewpoints", scene_file);
+    } else {
+        eprintln!("Error: Scene '{}' has no viewpoint '{}'; it has: {}", scene_file, name, names.join(", "));
+    }
+    std::process::exit(1);
+}
+
+/// Screenshot path recording the camera, readable by `parse_screenshot_filename`.
+fn screenshot_filename(position: Vec3, yaw: f32, pitch: f32) -> String {
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    // Camera info is rounded to 2 decimal places
+    format!(
+        "screenshots/screenshot_{}_pos_{:.2}_{:.2}_{:.2}_rot_{:.2}_{:.2}.png",
+        timestamp,
+        position.x, position.y, position.z,
+        yaw.to_degrees(), pitch.to_degrees()
+    )
 }
 
 fn parse_screenshot_filename(filename: &str) -> Option<(f32, f32, f32, f32, f32)> {
@@ -40,720 +420,819 @@ fn parse_screenshot_filename(filename: &str) -> Option<(f32, f32, f32, f32, f32)
     let filename = std::path::Path::new(filename)
         .file_stem()?
         .to_str()?;
-    
+
     // Find pos and rot markers
     let pos_start = filename.find("_pos_")?;
     let rot_start = filename.find("_rot_")?;
-    
+
     // Extract the position substring
     let pos_str = &filename[pos_start + 5..rot_start];
     let pos_parts: Vec<&str> = pos_str.split('_').collect();
     if pos_parts.len() != 3 {
         return None;
     }
-    
+
     // Extract the rotation substring
     let rot_str = &filename[rot_start + 5..];
     let rot_parts: Vec<&str> = rot_str.split('_').collect();
     if rot_parts.len() < 2 {
         return None;
     }
-    
+
     // Parse position values
     let x = pos_parts[0].parse::<f32>().ok()?;
     let y = pos_parts[1].parse::<f32>().ok()?;
     let z = pos_parts[2].parse::<f32>().ok()?;
-    
+
     // Parse rotation values (convert from degrees to radians)
     let yaw_deg = rot_parts[0].parse::<f32>().ok()?;
     let pitch_deg = rot_parts[1].parse::<f32>().ok()?;
-    
+
     Some((x, y, z, yaw_deg.to_radians(), pitch_deg.to_radians()))
 }
 
-fn main() {
-    env_logger::init();
-    
-    // Parse command line arguments
-    let args: Vec<String> = std::env::args().collect();
-    let mut scene_file = String::new();
-    let mut screenshot_info: Option<(f32, f32, f32, f32, f32)> = None;
-    
-    let mut i = 1;
-    while i < args.len() {
-        if args[i] == "--screenshot" && i + 1 < args.len() {
-            // Parse screenshot filename for camera info
-            let screenshot_arg = &args[i + 1];
-            if let Some(camera_info) = parse_screenshot_filename(screenshot_arg) {
-                screenshot_info = Some(camera_info);
-                println!("Reproducing camera from screenshot: {}", screenshot_arg);
-            } else {
-                eprintln!("Error: Invalid screenshot filename format");
-                eprintln!("Expected format: screenshot_TIMESTAMP_pos_X_Y_Z_rot_YAW_PITCH.png");
-                std::process::exit(1);
+/// The `render` command: draw the scene once without opening a window, or
+/// every step of a sun study.
+fn render(args: RenderArgs, catalog: &SceneCatalog) {
+    let mut overrides = args.camera.clone();
+    let mut resolution = args.resolution;
+    let mut recorded = None;
+    if let Some(screenshot) = &args.screenshot {
+        println!("Reproducing camera from screenshot: {}", screenshot.display());
+        match read_screenshot_info(screenshot) {
+            Ok(Some(info)) => {
+                overrides.position.get_or_insert(info.pose.position);
+                overrides.yaw.get_or_insert(info.pose.yaw.to_degrees());
+                overrides.pitch.get_or_insert(info.pose.pitch.to_degrees());
+                overrides.fov.get_or_insert(info.pose.fov);
+                resolution.get_or_insert(Resolution {
+                    width: info.width,
+                    height: info.height,
+                });
+                recorded = Some(info);
             }
-            i += 2;
-        } else if scene_file.is_empty() {
-            // Handle scene file argument
-            let arg = &args[i];
-            scene_file = if arg.ends_with(".json") {
-                // Full path to JSON file
-                arg.clone()
-            } else if let Ok(num) = arg.parse::<u32>() {
-                // Just a number - use example file
-                if num == 0 {
-                    "examples/10_full_house.json".to_string()
-                } else if num <= 10 {
-                    format!("examples/{}_*.json", num)
-                        .replace("*", match num {
-                            1 => "single_wall",
-                            2 => "wall_with_window",
-                            3 => "simple_room",
-                            4 => "room_with_door_window",
-                            5 => "two_rooms",
-                            6 => "room_with_furniture",
-                            7 => "multi_level",
-                            8 => "building_with_columns",
-                            9 => "complex_floor_plan",
-                            10 => "full_house",
-                            _ => "simple_room",
-                        })
-                } else {
-                    eprintln!("Example number must be between 1-10 (or 0 for example 10)");
+            result => {
+                let Some((x, y, z, yaw, pitch)) = parse_screenshot_filename(&screenshot.to_string_lossy()) else {
+                    match result {
+                        Err(e) => eprintln!("Error: {}", e),
+                        _ => eprintln!("Error: {} doesn't record a camera", screenshot.display()),
+                    }
+                    eprintln!("Expected a screenshot from this renderer, or a filename such as");
+                    eprintln!("screenshot_TIMESTAMP_pos_X_Y_Z_rot_YAW_PITCH.png");
                     std::process::exit(1);
-                }
-            } else {
-                // Try to find the file in examples directory
-                format!("examples/{}.json", arg)
-            };
-            i += 1;
-        } else {
-            eprintln!("Unknown argument: {}", args[i]);
-            i += 1;
-        }
-    }
-    
-    if scene_file.is_empty() {
-        scene_file = "examples/3_simple_room.json".to_string();
-    }
-    
-    // Check if file exists
-    if !std::path::Path::new(&scene_file).exists() {
-        eprintln!("Error: Scene file '{}' not found!", scene_file);
-        eprintln!("\nUsage:");
-        eprintln!("  {} [scene_file.json]                    # Load specific JSON file", args[0]);
-        eprintln!("  {} [1-10]                               # Load example by number", args[0]);
-        eprintln!("  {} 3_simple_room                        # Load example by name", args[0]);
-        eprintln!("  {} --screenshot <screenshot.png> [scene]  # Reproduce camera from screenshot", args[0]);
-        eprintln!("\nExamples:");
-        eprintln!("  {} examples/5_two_rooms.json", args[0]);
-        eprintln!("  {} 7", args[0]);
-        eprintln!("  {} --screenshot screenshot_20240701_120000_pos_1.00_2.00_3.00_rot_45.00_30.00.png", args[0]);
-        std::process::exit(1);
+                };
+                overrides.position.get_or_insert(Vec3::new(x, y, z));
+                overrides.yaw.get_or_insert(yaw.to_degrees());
+                overrides.pitch.get_or_insert(pitch.to_degrees());
+            }
+        }
     }
-    
+
+    // The scene the screenshot is of, unless another is named
+    let scene_file = match &recorded {
+        Some(info) if args.scene.scene.is_none() && Path::new(&info.scene).is_file() => info.scene.clone(),
+        _ => scene_file_or_exit(catalog, &args.scene),
+    };
     println!("Loading scene: {}", scene_file);
-    
-    let event_loop = EventLoop::new().unwrap();
-    let window = event_loop.create_window(Window::default_attributes()
-        .with_title(format!("WebGPU Architecture - {}", scene_file))
-        .with_inner_size(PhysicalSize::new(1024, 768)))
-        .unwrap();
+    if let Some(info) = recorded.filter(|info| info.scene == scene_file) {
+        let contents = std::fs::read(&scene_file).unwrap_or_default();
+        if scene_hash(&contents) != info.scene_hash {
+            println!("Note: {} has changed since the screenshot was taken", scene_file);
+        }
+    }
 
-    let window = std::sync::Arc::new(window);
-    let mut state = pollster::block_on(State::new(window.clone(), &scene_file, screenshot_info));
+    let resolution = resolution.unwrap_or_else(|| parse_resolution(DEFAULT_RESOLUTION).unwrap());
+    let shadow_settings = args.shadows.settings();
+    match args.sun_study {
+        Some(date) => render_sun_study(&scene_file, &overrides, resolution, shadow_settings, date, args.step, args.output),
+        None => render_screenshot(&scene_file, &overrides, resolution, shadow_settings, args.output),
+    }
+}
 
-    let _ = event_loop.run(move |event, target| {
-        match event {
-            Event::WindowEvent {
-                ref event,
-                window_id,
-            } if window_id == window.id() => match event {
-                WindowEvent::CloseRequested => target.exit(),
-                WindowEvent::Resized(physical_size) => {
-                    state.resize(*physical_size);
-                }
-                WindowEvent::KeyboardInput {
-                    event,
-                    ..
-                } => {
-                    if let PhysicalKey::Code(keycode) = event.physical_key {
-                        // Handle scene switching only on press
-                        if event.state == ElementState::Pressed {
-                            match keycode {
-                                KeyCode::Escape => target.exit(),
-                                KeyCode::Digit1 => state.load_scene("examples/1_single_wall.json"),
-                                KeyCode::Digit2 => state.load_scene("examples/2_wall_with_window.json"),
-                                KeyCode::Digit3 => state.load_scene("examples/3_simple_room.json"),
-                                KeyCode::Digit4 => state.load_scene("examples/4_room_with_door_window.json"),
-                                KeyCode::Digit5 => state.load_scene("examples/5_two_rooms.json"),
-                                KeyCode::Digit6 => state.load_scene("examples/6_room_with_furniture.json"),
-                                KeyCode::Digit7 => state.load_scene("examples/7_multi_level.json"),
-                                KeyCode::Digit8 => state.load_scene("examples/8_building_with_columns.json"),
-                                KeyCode::Digit9 => state.load_scene("examples/9_complex_floor_plan.json"),
-                                KeyCode::Digit0 => state.load_scene("examples/10_full_house.json"),
-                                KeyCode::KeyP => state.take_screenshot(),
-                                _ => {}
-                            }
-                        }
-                        // Always process camera controls (except P key)
-                        if keycode != KeyCode::KeyP {
-                            state.camera_controller.process_keyboard(keycode, event.state);
-                        }
-                    }
-                }
-                WindowEvent::MouseInput { button, state: button_state, .. } => {
-                    state.camera_controller.process_mouse(*button, *button_state);
-                }
-                WindowEvent::RedrawRequested => {
-                    match state.render() {
-                        Ok(_) => {}
-                        Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
-                            state.resize(window.inner_size());
-                        }
-                        Err(wgpu::SurfaceError::OutOfMemory) => {
-                            target.exit();
-                        }
-                        Err(e) => eprintln!("{:?}", e),
-                    }
-                }
-                _ => {}
-            },
-            Event::DeviceEvent {
-                event: DeviceEvent::MouseMotion { delta },
-                ..
-            } => {
-                state.camera_controller.process_mouse_motion(delta.0, delta.1);
-            }
-            Event::AboutToWait => {
-                // Update camera
-                let now = std::time::Instant::now();
-                let dt = (now - state.last_update).as_secs_f32();
-                state.last_update = now;
-                
-                state.camera_controller.update_camera(&mut state.camera, dt);
-                state.camera_uniform.update_view_proj(&state.camera);
-                state.queue.write_buffer(
-                    &state.camera_buffer,
-                    0,
-                    bytemuck::cast_slice(&[state.camera_uniform]),
-                );
-                
-                window.request_redraw();
-            }
-            _ => {}
+/// What to record in a screenshot of the scene file at `scene_file`, taken
+/// with `camera`.
+fn screenshot_info(scene_file: &str, camera: &Camera, camera_controller: &CameraController, size: (u32, u32)) -> ScreenshotInfo {
+    let contents = std::fs::read(scene_file).unwrap_or_default();
+    ScreenshotInfo::new(scene_file, &contents, camera_controller.pose(camera), size.0, size.1)
+}
+
+/// Render the scene once and save it to `output`, or to a screenshot named
+/// after the camera.
+fn render_screenshot(
+    scene_file: &str,
+    overrides: &CameraArgs,
+    resolution: Resolution,
+    shadow_settings: ShadowSettings,
+    output: Option<PathBuf>,
+) {
+    let scene = load_scene_or_exit(scene_file);
+    let (camera, camera_controller) = scene_camera(scene_file, &scene, resolution.aspect(), overrides);
+    let (yaw, pitch) = (camera_controller.get_yaw(), camera_controller.get_pitch());
+    println!("Camera positioned at ({:.2}, {:.2}, {:.2}) with rotation ({:.1}°, {:.1}°)",
+             camera.position.x, camera.position.y, camera.position.z, yaw.to_degrees(), pitch.to_degrees());
+
+    let renderer = offscreen_renderer_or_exit(scene_file, shadow_settings);
+    let image = renderer.render(&scene, &camera, resolution.width, resolution.height);
+
+    let filename = output.unwrap_or_else(|| PathBuf::from(screenshot_filename(camera.position, yaw, pitch)));
+    if let Some(dir) = filename.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            eprintln!("Error: failed to create {}: {}", dir.display(), e);
+            std::process::exit(1);
         }
+    }
+    let info = screenshot_info(scene_file, &camera, &camera_controller, (resolution.width, resolution.height));
+    if let Err(e) = save_screenshot(&image, &filename, &info) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    println!("Screenshot saved to: {}", filename.display());
+}
+
+/// Render the scene every `step_minutes` of `date` while the sun is up at
+/// the scene's site, one PNG per frame in `output` or a directory named
+/// after the scene and date.
+fn render_sun_study(
+    scene_file: &str,
+    overrides: &CameraArgs,
+    resolution: Resolution,
+    shadow_settings: ShadowSettings,
+    date: NaiveDate,
+    step_minutes: u32,
+    output: Option<PathBuf>,
+) {
+    let mut scene = load_scene_or_exit(scene_file);
+    let Some(site) = scene.site.clone() else {
+        eprintln!("Error: Scene '{}' has no site to place the sun", scene_file);
+        std::process::exit(1);
+    };
+
+    let (camera, camera_controller) = scene_camera(scene_file, &scene, resolution.aspect(), overrides);
+    let info = screenshot_info(scene_file, &camera, &camera_controller, (resolution.width, resolution.height));
+    let renderer = offscreen_renderer_or_exit(scene_file, shadow_settings);
+
+    let dir = output.unwrap_or_else(|| {
+        let scene_name = Path::new(scene_file).file_stem().and_then(|stem| stem.to_str()).unwrap_or("scene");
+        PathBuf::from(format!("screenshots/sun_study_{}_{}", scene_name, date.format("%Y%m%d")))
     });
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        eprintln!("Error: failed to create {}: {}", dir.display(), e);
+        std::process::exit(1);
+    }
+
+    let midnight = date.and_time(NaiveTime::MIN);
+    for minutes in (0..24 * 60).step_by(step_minutes as usize) {
+        let local = midnight + chrono::Duration::minutes(minutes as i64);
+        let position = site.solar_position(local);
+        if !position.is_above_horizon() {
+            continue;
+        }
+
+        if let Some(site) = &mut scene.site {
+            site.date_time = Some(local);
+        }
+        let image = renderer.render(&scene, &camera, resolution.width, resolution.height);
+        let filename = dir.join(format!("sun_{}.png", local.format("%H%M")));
+        if let Err(e) = save_screenshot(&image, &filename, &info) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        println!(
+            "{} sun at azimuth {:.1}°, elevation {:.1}°: {}",
+            local.format("%H:%M"),
+            position.azimuth,
+            position.elevation,
+            filename.display()
+        );
+    }
 }
 
-struct State {
-    surface: wgpu::Surface<'static>,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
-    size: winit::dpi::PhysicalSize<u32>,
-    render_pipeline: wgpu::RenderPipeline,
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
-    num_indices: u32,
-    camera: Camera,
-    camera_uniform: CameraUniform,
-    camera_buffer: wgpu::Buffer,
-    camera_bind_group: wgpu::BindGroup,
-    scene_path: String,
-    depth_texture: wgpu::TextureView,
-    camera_controller: CameraController,
-    last_update: std::time::Instant,
-    screenshot_requested: bool,
-    auto_screenshot_mode: bool,
+/// The `validate` command: load every scene in `scenes`, or in `catalog`,
+/// and report the problems found. Returns whether all of them are valid.
+fn validate_scene_files(catalog: &SceneCatalog, scenes: &[String]) -> bool {
+    let files: Vec<Result<String, String>> = if scenes.is_empty() {
+        if catalog.is_empty() {
+            eprintln!("Error: No scenes in {}", catalog.dir().display());
+            return false;
+        }
+        catalog.files().iter().map(|file| Ok(file.to_string_lossy().into_owned())).collect()
+    } else {
+        scenes.iter().map(|scene| resolve_scene_file(catalog, Some(scene))).collect()
+    };
+
+    let mut all_valid = true;
+    for file in files {
+        match file.and_then(|file| load_scene_from_file(Path::new(&file)).map(|_| file).map_err(|e| e.to_string())) {
+            Ok(file) => println!("{}: ok", file),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                all_valid = false;
+            }
+        }
+    }
+    all_valid
 }
 
-impl State {
-    async fn new(window: std::sync::Arc<winit::window::Window>, scene_file: &str, screenshot_info: Option<(f32, f32, f32, f32, f32)>) -> Self {
-        let size = window.inner_size();
+/// The `stats` command: count what the scene is made of.
+fn print_stats(scene_file: &str) {
+    let scene = load_scene_or_exit(scene_file);
 
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
+    let mut kinds: BTreeMap<&str, usize> = BTreeMap::new();
+    for element in &scene.elements {
+        *kinds.entry(element.kind()).or_default() += 1;
+    }
+    let kinds: Vec<String> = kinds.iter().map(|(kind, count)| format!("{} {}", count, kind)).collect();
+    let levels: Vec<String> = levels(&scene).iter().map(|elevation| format!("{:+.2} m", elevation)).collect();
+
+    let meshes = element_meshes(&scene);
+    let vertices: usize = meshes.iter().map(|mesh| mesh.vertices.len()).sum();
+    let triangles: usize = meshes.iter().map(|mesh| mesh.indices.len() / 3).sum();
+    let bounds = meshes
+        .iter()
+        .flat_map(|mesh| &mesh.vertices)
+        .map(|vertex| Vec3::from(vertex.position))
+        .fold(None, |bounds: Option<(Vec3, Vec3)>, point| match bounds {
+            Some((min, max)) => Some((min.min(point), max.max(point))),
+            None => Some((point, point)),
         });
-        
-        let surface = instance.create_surface(window).unwrap();
-
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .unwrap();
 
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
-                    label: None,
-                    memory_hints: Default::default(),
-                },
-                None,
-            )
-            .await
-            .unwrap();
+    println!("{}: {}", scene_file, scene.name);
+    println!("  Elements:  {} ({})", scene.elements.len(), kinds.join(", "));
+    println!("  Levels:    {}", levels.join(", "));
+    println!("  Materials: {} defined", scene.materials.len());
+    if scene.lights.is_empty() {
+        println!("  Lights:    default");
+    } else {
+        println!("  Lights:    {}", scene.lights.len());
+    }
+    if let Some(site) = &scene.site {
+        println!("  Site:      {:.4}°, {:.4}°", site.latitude, site.longitude);
+    }
+    println!("  Mesh:      {} vertices, {} triangles", vertices, triangles);
+    if let Some((min, max)) = bounds {
+        let size = max - min;
+        println!(
+            "  Bounds:    {:.2} x {:.2} x {:.2} m, from ({:.2}, {:.2}, {:.2}) to ({:.2}, {:.2}, {:.2})",
+            size.x, size.y, size.z, min.x, min.y, min.z, max.x, max.y, max.z
+        );
+    }
+}
 
-        let surface_caps = surface.get_capabilities(&adapter);
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .unwrap_or(surface_caps.formats[0]);
-
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: size.width,
-            height: size.height,
-            present_mode: wgpu::PresentMode::AutoVsync,
-            desired_maximum_frame_latency: 2,
-            alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
-        };
-        surface.configure(&device, &config);
-
-        // Load scene
-        let scene = load_scene_from_file(Path::new(scene_file))
-            .expect("Failed to load scene");
-        
-        let mesh = scene_to_mesh(&scene);
-        
-        // Create camera
-        let aspect = size.width as f32 / size.height as f32;
-        let mut camera = Camera::from_scene(&scene.camera, aspect);
-        let mut camera_uniform = CameraUniform::new();
-        camera_uniform.update_view_proj(&camera);
-
-        // Create camera buffer and bind group
-        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Camera Buffer"),
-            contents: bytemuck::cast_slice(&[camera_uniform]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
+fn export(args: ExportArgs, catalog: &SceneCatalog) {
+    let scene_file = scene_file_or_exit(catalog, &args.scene);
+    let scene = load_scene_or_exit(&scene_file);
+    let options = MeshExportOptions {
+        print_scale: args.print_scale,
+        watertight: args.watertight,
+    };
+    if let Err(e) = export_scene(&scene, scene_dir(&scene_file), &args.output, options) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    println!("Exported to: {}", args.output.display());
+}
 
-        let camera_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
-            label: Some("camera_bind_group_layout"),
-        });
+fn plan(args: PlanArgs, catalog: &SceneCatalog) {
+    let scene_file = scene_file_or_exit(catalog, &args.scene);
+    let scene = load_scene_or_exit(&scene_file);
+    match write_floor_plans(&scene, &args.output, args.cut_height, args.print_scale) {
+        Ok(paths) => {
+            for path in paths {
+                println!("Plan written to: {}", path.display());
+            }
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
 
-        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &camera_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer.as_entire_binding(),
-            }],
-            label: Some("camera_bind_group"),
-        });
+/// The `takeoff` command: print or save the quantities of a scene as JSON,
+/// or as CSV with `--csv` or an output file ending in `.csv`.
+fn write_takeoff(args: TakeoffArgs, catalog: &SceneCatalog) {
+    let scene_file = scene_file_or_exit(catalog, &args.scene);
+    let scene = load_scene_or_exit(&scene_file);
+    let csv = args.csv
+        || args
+            .output
+            .as_ref()
+            .is_some_and(|path| path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv")));
+    let quantities = takeoff(&scene);
+    let report = if csv { takeoff_to_csv(&quantities) } else { takeoff_to_json(&quantities) };
 
-        // Create vertex and index buffers
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Vertex Buffer"),
-            contents: bytemuck::cast_slice(&mesh.vertices),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
+    match args.output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, report) {
+                eprintln!("Error: failed to write {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+            println!("Takeoff written to: {}", path.display());
+        }
+        None => print!("{}", report),
+    }
+}
 
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Index Buffer"),
-            contents: bytemuck::cast_slice(&mesh.indices),
-            usage: wgpu::BufferUsages::INDEX,
-        });
+/// Rewrite every scene in `paths`, files or directories of `.json` files, in
+/// the current format. Returns whether all of them could be upgraded.
+fn upgrade_scene_files(paths: &[PathBuf]) -> bool {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            match SceneCatalog::scan(path) {
+                Ok(catalog) => files.extend_from_slice(catalog.files()),
+                Err(e) => {
+                    eprintln!("Error: Failed to read {}: {}", path.display(), e);
+                    return false;
+                }
+            }
+        } else {
+            files.push(path.clone());
+        }
+    }
 
-        let num_indices = mesh.indices.len() as u32;
+    let mut all_upgraded = true;
+    for file in &files {
+        match upgrade_scene_file(file) {
+            Ok(Some(version)) => println!("{}: upgraded from format version {}", file.display(), version),
+            Ok(None) => println!("{}: already current", file.display()),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                all_upgraded = false;
+            }
+        }
+    }
+    all_upgraded
+}
 
-        // Create shader module
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Scene Shader"),
-            source: wgpu::ShaderSource::Wgsl(SCENE_SHADER.into()),
-        });
+/// The `view` command: open the scene in a window.
+fn view(args: ViewArgs, catalog: SceneCatalog) {
+    let scene_file = scene_file_or_exit(&catalog, &args.scene);
+    println!("Loading scene: {}", scene_file);
+    let scene = load_scene_or_exit(&scene_file);
 
-        // Create pipeline layout
-        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Render Pipeline Layout"),
-            bind_group_layouts: &[&camera_bind_group_layout],
-            push_constant_ranges: &[],
-        });
+    let event_loop = EventLoop::new().unwrap();
+    let mut app = App::new(catalog, scene_file, scene, args.resolution, &args.camera, args.shadows.settings());
+    event_loop.run_app(&mut app).unwrap();
+}
 
-        // Vertex buffer layout
-        let vertex_buffers = [wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float32x3,
-                },
-                wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x3,
-                },
-                wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
-                    shader_location: 2,
-                    format: wgpu::VertexFormat::Float32x2,
-                },
-            ],
-        }];
-
-        // Create render pipeline
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &vertex_buffers,
-                compilation_options: Default::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: Default::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
-        });
+/// Viewer options given on the command line before any command. `--scene-dir`
+/// may go there too, so the command line can't simply conflict with commands.
+fn viewer_options_given(matches: &ArgMatches) -> Vec<String> {
+    ViewArgs::augment_args(clap::Command::new("view"))
+        .get_arguments()
+        .filter(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+        .filter_map(|arg| arg.get_long().map(|long| format!("--{}", long)))
+        .collect()
+}
 
-        // Create depth texture
-        let depth_texture = create_depth_texture(&device, &config);
-
-        // Create camera controller
-        let mut camera_controller = CameraController::new(10.0, 0.003);
-        camera_controller.set_initial_direction(&camera);
-        
-        // Apply screenshot camera info if provided
-        let auto_screenshot = if let Some((x, y, z, yaw, pitch)) = screenshot_info {
-            camera.position = Vec3::new(x, y, z);
-            camera_controller.set_yaw_pitch(yaw, pitch);
-            camera_controller.update_camera(&mut camera, 0.0);
-            camera_uniform.update_view_proj(&camera);
-            queue.write_buffer(
-                &camera_buffer,
-                0,
-                bytemuck::cast_slice(&[camera_uniform]),
-            );
-            println!("Camera positioned at ({:.2}, {:.2}, {:.2}) with rotation ({:.1}°, {:.1}°)", 
-                     x, y, z, yaw.to_degrees(), pitch.to_degrees());
-            true
-        } else {
-            false
-        };
+fn main() {
+    env_logger::init();
 
-        let state = Self {
-            surface,
-            device,
-            queue,
-            config,
-            size,
-            render_pipeline,
-            vertex_buffer,
-            index_buffer,
-            num_indices,
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if cli.command.is_some() && cli.view.scene.scene.is_some() {
+        Cli::command()
+            .error(ErrorKind::ArgumentConflict, "the scene goes after the command, as in `stats 7`")
+            .exit();
+    }
+    let misplaced = viewer_options_given(&matches);
+    if cli.command.is_some() && !misplaced.is_empty() {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                format!("{} must go after the command, as in `render 7 --yaw 90`", misplaced.join(", ")),
+            )
+            .exit();
+    }
+    let catalog = SceneCatalog::scan(&cli.scene_dir).unwrap_or_else(|e| {
+        log::warn!("Failed to read scenes in {}: {}", cli.scene_dir.display(), e);
+        SceneCatalog::empty(&cli.scene_dir)
+    });
+
+    match cli.command.unwrap_or(Command::View(cli.view)) {
+        Command::View(args) => view(args, catalog),
+        Command::Render(args) => render(args, &catalog),
+        Command::Validate(args) => {
+            if !validate_scene_files(&catalog, &args.scenes) {
+                std::process::exit(1);
+            }
+        }
+        Command::Export(args) => export(args, &catalog),
+        Command::Stats(args) => print_stats(&scene_file_or_exit(&catalog, &args)),
+        Command::Plan(args) => plan(args, &catalog),
+        Command::Takeoff(args) => write_takeoff(args, &catalog),
+        Command::Upgrade(args) => {
+            if !upgrade_scene_files(&args.paths) {
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// The number on a digit key of the main keyboard.
+fn digit(keycode: KeyCode) -> Option<u32> {
+    let digits = [
+        KeyCode::Digit0,
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+    digits.iter().position(|&key| key == keycode).map(|number| number as u32)
+}
+
+/// The window and its renderer, created once the event loop is running.
+struct Viewer {
+    window: Arc<Window>,
+    renderer: Renderer<'static>,
+}
+
+struct App {
+    /// Scenes to page through.
+    catalog: SceneCatalog,
+    scene_path: String,
+    /// `name` of the scene shown.
+    scene_name: String,
+    /// Scene to show when the window is created.
+    scene: Option<Scene>,
+    /// Whether the scene file was changed into one that can't be loaded, so
+    /// the last version that could is still shown.
+    reload_failed: bool,
+    watcher: SceneWatcher,
+    last_poll: Instant,
+    /// Viewpoints of the scene and those saved for it, to cycle through.
+    viewpoints: Vec<Viewpoint>,
+    /// Index of the viewpoint last moved to.
+    viewpoint: Option<usize>,
+    /// Move to a viewpoint under way, during which the camera isn't steered.
+    transition: Option<CameraTransition>,
+    viewer: Option<Viewer>,
+    camera: Camera,
+    camera_controller: CameraController,
+    resolution: Resolution,
+    shadow_settings: ShadowSettings,
+    last_update: Instant,
+}
+
+impl App {
+    fn new(
+        catalog: SceneCatalog,
+        scene_path: String,
+        scene: Scene,
+        resolution: Resolution,
+        camera: &CameraArgs,
+        shadow_settings: ShadowSettings,
+    ) -> Self {
+        let (camera, camera_controller) = scene_camera(&scene_path, &scene, resolution.aspect(), camera);
+
+        let viewpoints = scene_viewpoints(Path::new(&scene_path), &scene).unwrap_or_else(|e| {
+            eprintln!("Failed to load viewpoints: {}", e);
+            scene.viewpoints.clone()
+        });
+
+        Self {
+            catalog,
+            watcher: SceneWatcher::new(Path::new(&scene_path), Some(&scene)),
+            last_poll: Instant::now(),
+            viewpoints,
+            viewpoint: None,
+            transition: None,
+            scene_path,
+            scene_name: scene.name.clone(),
+            scene: Some(scene),
+            reload_failed: false,
+            viewer: None,
             camera,
-            camera_uniform,
-            camera_buffer,
-            camera_bind_group,
-            scene_path: scene_file.to_string(),
-            depth_texture,
             camera_controller,
-            last_update: std::time::Instant::now(),
-            screenshot_requested: auto_screenshot,
-            auto_screenshot_mode: auto_screenshot,
-        };
-        
-        // If auto-screenshot is requested, render one frame immediately
-        if auto_screenshot {
-            println!("Taking screenshot with reproduced camera position...");
+            resolution,
+            shadow_settings,
+            last_update: Instant::now(),
         }
-        
-        state
     }
 
     fn load_scene(&mut self, scene_file: &str) {
         println!("Loading scene: {}", scene_file);
-        
+
         match load_scene_from_file(Path::new(scene_file)) {
             Ok(scene) => {
-                let mesh = scene_to_mesh(&scene);
-                
-                // Update camera
-                self.camera = Camera::from_scene(&scene.camera, self.size.width as f32 / self.size.height as f32);
+                self.scene_path = scene_file.to_string();
+                self.camera = Camera::from_scene(&scene.camera, self.camera.aspect);
                 self.camera_controller.set_initial_direction(&self.camera);
-                self.camera_uniform.update_view_proj(&self.camera);
-                self.queue.write_buffer(
-                    &self.camera_buffer,
-                    0,
-                    bytemuck::cast_slice(&[self.camera_uniform]),
-                );
-                
-                // Recreate buffers
-                self.vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Vertex Buffer"),
-                    contents: bytemuck::cast_slice(&mesh.vertices),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
+                self.viewpoint = None;
+                self.transition = None;
+                self.show_scene(&scene);
+            }
+            Err(e) => {
+                eprintln!("Failed to load scene {}: {}", scene_file, e);
+            }
+        }
+    }
 
-                self.index_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Index Buffer"),
-                    contents: bytemuck::cast_slice(&mesh.indices),
-                    usage: wgpu::BufferUsages::INDEX,
-                });
+    /// Load the current scene again after its files changed, keeping the
+    /// camera where it is. A scene that no longer loads is reported and the
+    /// last version that did stays on screen.
+    fn reload_scene(&mut self) {
+        println!("Reloading scene: {}", self.scene_path);
 
-                self.num_indices = mesh.indices.len() as u32;
-                self.scene_path = scene_file.to_string();
+        match load_scene_from_file(Path::new(&self.scene_path)) {
+            Ok(scene) => self.show_scene(&scene),
+            Err(e) => {
+                eprintln!("Failed to reload scene {}: {}", self.scene_path, e);
+                self.reload_failed = true;
+                self.update_title();
+            }
+        }
+    }
+
+    /// Draw `scene`, loaded from `scene_path`, from the current camera and
+    /// watch its files for changes.
+    fn show_scene(&mut self, scene: &Scene) {
+        self.scene_name = scene.name.clone();
+        self.reload_failed = false;
+        self.watcher.watch(Path::new(&self.scene_path), Some(scene));
+        self.viewpoints = scene_viewpoints(Path::new(&self.scene_path), scene).unwrap_or_else(|e| {
+            eprintln!("Failed to load viewpoints: {}", e);
+            scene.viewpoints.clone()
+        });
+        self.viewpoint = self.viewpoint.filter(|&index| index < self.viewpoints.len());
+        if let Some(viewer) = &mut self.viewer {
+            viewer.renderer.load_scene(scene, scene_dir(&self.scene_path));
+            viewer.renderer.update_camera(&self.camera);
+        }
+        self.update_title();
+    }
+
+    /// Load the scene `offset` places from the current one in the catalog.
+    fn page(&mut self, offset: isize) {
+        match self.catalog.step(Path::new(&self.scene_path), offset) {
+            Some(file) => {
+                let file = file.to_string_lossy().into_owned();
+                self.load_scene(&file);
+            }
+            None => eprintln!("No scenes to page through in {}", self.catalog.dir().display()),
+        }
+    }
+
+    /// Load the scene in the catalog whose name starts with `number`.
+    fn load_numbered_scene(&mut self, number: u32) {
+        match self.catalog.find(&number.to_string()) {
+            Some(file) => {
+                let file = file.to_string_lossy().into_owned();
+                self.load_scene(&file);
             }
+            None => eprintln!("No scene numbered {} in {}", number, self.catalog.dir().display()),
+        }
+    }
+
+    /// Move the camera smoothly to the viewpoint `offset` places from the
+    /// last one moved to, wrapping around at either end.
+    fn step_viewpoint(&mut self, offset: isize) {
+        if self.viewpoints.is_empty() {
+            println!("No viewpoints in {}; press B to save one", self.scene_path);
+            return;
+        }
+        let len = self.viewpoints.len() as isize;
+        let index = match self.viewpoint {
+            Some(index) => (index as isize + offset).rem_euclid(len),
+            None if offset > 0 => (offset - 1).rem_euclid(len),
+            None => offset.rem_euclid(len),
+        } as usize;
+
+        let viewpoint = &self.viewpoints[index];
+        println!("Viewpoint: {} ({}/{})", viewpoint.name, index + 1, self.viewpoints.len());
+        self.transition = Some(CameraTransition::new(
+            self.camera_controller.pose(&self.camera),
+            CameraPose::from_scene(&viewpoint.camera),
+            VIEWPOINT_TRANSITION_SECONDS,
+        ));
+        self.viewpoint = Some(index);
+    }
+
+    /// Save the current view as a new viewpoint, next to the scene file.
+    fn save_viewpoint(&mut self) {
+        let scene_path = Path::new(&self.scene_path);
+        let mut saved = match load_saved_viewpoints(scene_path) {
+            Ok(saved) => saved,
             Err(e) => {
-                eprintln!("Failed to load scene {}: {}", scene_file, e);
+                eprintln!("Failed to save viewpoint: {}", e);
+                return;
             }
+        };
+
+        let name = (self.viewpoints.len() + 1..)
+            .map(|number| format!("View {}", number))
+            .find(|name| self.viewpoints.iter().chain(&saved).all(|viewpoint| &viewpoint.name != name))
+            .unwrap();
+        let viewpoint = Viewpoint {
+            name,
+            camera: self.camera_controller.pose(&self.camera).to_scene(),
+        };
+        saved.push(viewpoint.clone());
+        if let Err(e) = save_viewpoints(scene_path, &saved) {
+            eprintln!("Failed to save viewpoint: {}", e);
+            return;
         }
+        // Saving isn't a change to reload the scene for
+        self.watcher.watch(scene_path, None);
+
+        println!("Viewpoint saved: {}", viewpoint.name);
+        self.viewpoints.push(viewpoint);
+        self.viewpoint = Some(self.viewpoints.len() - 1);
     }
 
-    fn take_screenshot(&mut self) {
-        self.screenshot_requested = true;
-        println!("Screenshot requested - will capture next frame");
+    /// Window title naming the scene, and where it is in the catalog.
+    fn window_title(&self) -> String {
+        let mut title = match self.catalog.position(Path::new(&self.scene_path)) {
+            Some(index) => format!("WebGPU Architecture - {} ({}/{})", self.scene_name, index + 1, self.catalog.len()),
+            None => format!("WebGPU Architecture - {}", self.scene_name),
+        };
+        if self.reload_failed {
+            title.push_str(" - reload failed, see console");
+        }
+        title
     }
 
-    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+    fn update_title(&self) {
+        if let Some(viewer) = &self.viewer {
+            viewer.window.set_title(&self.window_title());
+        }
+    }
+
+    fn take_screenshot(&self) {
+        let Some(viewer) = &self.viewer else { return };
+        let Some(image) = viewer.renderer.capture() else {
+            eprintln!("Can't take a screenshot of a {:?} surface", viewer.renderer.surface.format());
+            return;
+        };
+
+        if let Err(e) = std::fs::create_dir_all("screenshots") {
+            eprintln!("Failed to create screenshots directory: {}", e);
+            return;
+        }
+        let filename = screenshot_filename(
+            self.camera.position,
+            self.camera_controller.get_yaw(),
+            self.camera_controller.get_pitch(),
+        );
+        let info = screenshot_info(&self.scene_path, &self.camera, &self.camera_controller, image.dimensions());
+        if let Err(e) = save_screenshot(&image, Path::new(&filename), &info) {
+            eprintln!("Failed to save screenshot: {}", e);
+            return;
+        }
+        println!("Screenshot saved to: {}", filename);
+    }
+
+    fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        let Some(viewer) = &mut self.viewer else { return };
         if new_size.width > 0 && new_size.height > 0 {
-            self.size = new_size;
-            self.config.width = new_size.width;
-            self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
-            
-            // Recreate depth texture
-            self.depth_texture = create_depth_texture(&self.device, &self.config);
-            
-            // Update camera aspect ratio
+            viewer.renderer.resize(new_size);
             self.camera.aspect = new_size.width as f32 / new_size.height as f32;
-            self.camera_uniform.update_view_proj(&self.camera);
-            self.queue.write_buffer(
-                &self.camera_buffer,
-                0,
-                bytemuck::cast_slice(&[self.camera_uniform]),
-            );
-        }
-    }
-
-    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-
-        // Create screenshot texture if requested
-        let screenshot_texture = if self.screenshot_requested {
-            Some(self.device.create_texture(&wgpu::TextureDescriptor {
-                label: Some("Screenshot Texture"),
-                size: wgpu::Extent3d {
-                    width: self.config.width,
-                    height: self.config.height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: self.config.format,
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
-                view_formats: &[],
-            }))
-        } else {
-            None
-        };
+            viewer.renderer.update_camera(&self.camera);
+        }
+    }
+}
 
-        let mut encoder = self.device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
-
-        // Helper function to render the scene
-        let render_to_target = |encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView, depth_texture: &wgpu::TextureView, pipeline: &wgpu::RenderPipeline, bind_group: &wgpu::BindGroup, vertex_buffer: &wgpu::Buffer, index_buffer: &wgpu::Buffer, num_indices: u32| {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: target,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.5,
-                            g: 0.7,
-                            b: 0.9,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: depth_texture,
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store,
-                    }),
-                    stencil_ops: None,
-                }),
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-
-            render_pass.set_pipeline(pipeline);
-            render_pass.set_bind_group(0, bind_group, &[]);
-            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..num_indices, 0, 0..1);
+impl ApplicationHandler for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(scene) = self.scene.take() else { return };
+
+        let window = event_loop
+            .create_window(
+                Window::default_attributes()
+                    .with_title(self.window_title())
+                    .with_inner_size(PhysicalSize::new(self.resolution.width, self.resolution.height)),
+            )
+            .unwrap();
+        let window = Arc::new(window);
+
+        let size = window.inner_size();
+        let mut renderer = match pollster::block_on(Renderer::new(
+            window.clone(),
+            size,
+            &scene,
+            scene_dir(&self.scene_path),
+        )) {
+            Ok(renderer) => renderer,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                event_loop.exit();
+                return;
+            }
         };
 
-        // Always render to the main view
-        render_to_target(&mut encoder, &view, &self.depth_texture, &self.render_pipeline, &self.camera_bind_group, &self.vertex_buffer, &self.index_buffer, self.num_indices);
-
-        // Handle screenshot if requested
-        if self.screenshot_requested && screenshot_texture.is_some() {
-            let texture = screenshot_texture.unwrap();
-            let screenshot_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-            
-            // Also render to screenshot texture
-            render_to_target(&mut encoder, &screenshot_view, &self.depth_texture, &self.render_pipeline, &self.camera_bind_group, &self.vertex_buffer, &self.index_buffer, self.num_indices);
-            
-            // Create buffer for reading texture data
-            let u32_size = std::mem::size_of::<u32>() as u32;
-            let output_buffer_size = (u32_size * self.config.width * self.config.height) as wgpu::BufferAddress;
-            let output_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
-                size: output_buffer_size,
-                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
-                label: Some("Screenshot Buffer"),
-                mapped_at_creation: false,
-            });
-            
-            encoder.copy_texture_to_buffer(
-                wgpu::ImageCopyTexture {
-                    texture: &texture,
-                    mip_level: 0,
-                    origin: wgpu::Origin3d::ZERO,
-                    aspect: wgpu::TextureAspect::All,
-                },
-                wgpu::ImageCopyBuffer {
-                    buffer: &output_buffer,
-                    layout: wgpu::ImageDataLayout {
-                        offset: 0,
-                        bytes_per_row: Some(u32_size * self.config.width),
-                        rows_per_image: Some(self.config.height),
-                    },
-                },
-                wgpu::Extent3d {
-                    width: self.config.width,
-                    height: self.config.height,
-                    depth_or_array_layers: 1,
-                },
-            );
-            
-            let submission_index = self.queue.submit(std::iter::once(encoder.finish()));
-            
-            // Create screenshot directory
-            std::fs::create_dir_all("screenshots").unwrap();
-            let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-            
-            // Format camera position and rotation for filename
-            let pos = self.camera.position;
-            let yaw = self.camera_controller.get_yaw();
-            let pitch = self.camera_controller.get_pitch();
-            
-            // Create filename with camera info (rounded to 2 decimal places)
-            let filename = format!(
-                "screenshots/screenshot_{}_pos_{:.2}_{:.2}_{:.2}_rot_{:.2}_{:.2}.png",
-                timestamp,
-                pos.x, pos.y, pos.z,
-                yaw.to_degrees(), pitch.to_degrees()
-            );
-            
-            // Map buffer and save image
-            let buffer_slice = output_buffer.slice(..);
-            let (sender, receiver) = std::sync::mpsc::channel();
-            buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
-                sender.send(result).unwrap();
-            });
-            
-            self.device.poll(wgpu::Maintain::WaitForSubmissionIndex(submission_index));
-            receiver.recv().unwrap().unwrap();
-            
-            {
-                let data = buffer_slice.get_mapped_range();
-                let buffer: Vec<u8> = data.to_vec();
-                
-                // Convert BGRA to RGBA
-                let mut rgba_data = vec![0u8; buffer.len()];
-                for i in (0..buffer.len()).step_by(4) {
-                    rgba_data[i] = buffer[i + 2];     // R
-                    rgba_data[i + 1] = buffer[i + 1]; // G
-                    rgba_data[i + 2] = buffer[i];     // B
-                    rgba_data[i + 3] = buffer[i + 3]; // A
+        if self.shadow_settings != renderer.scene_renderer.shadow_settings() {
+            renderer.set_shadow_settings(self.shadow_settings);
+        }
+
+        let size = renderer.size();
+        self.camera.aspect = size.width as f32 / size.height as f32;
+        self.viewer = Some(Viewer { window, renderer });
+        self.last_update = Instant::now();
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId, event: WindowEvent) {
+        let Some(viewer) = &self.viewer else { return };
+        if window_id != viewer.window.id() {
+            return;
+        }
+
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::Resized(physical_size) => {
+                self.resize(physical_size);
+            }
+            WindowEvent::KeyboardInput {
+                event,
+                ..
+            } => {
+                if let PhysicalKey::Code(keycode) = event.physical_key {
+                    // Handle scene switching only on press
+                    if event.state == ElementState::Pressed {
+                        match keycode {
+                            KeyCode::Escape => event_loop.exit(),
+                            KeyCode::PageDown => self.page(1),
+                            KeyCode::PageUp => self.page(-1),
+                            KeyCode::KeyP => self.take_screenshot(),
+                            KeyCode::BracketRight => self.step_viewpoint(1),
+                            KeyCode::BracketLeft => self.step_viewpoint(-1),
+                            KeyCode::KeyB => self.save_viewpoint(),
+                            _ => {
+                                if let Some(number) = digit(keycode) {
+                                    self.load_numbered_scene(number);
+                                }
+                            }
+                        }
+                    }
+                    // Always process camera controls (except P key)
+                    if keycode != KeyCode::KeyP {
+                        self.camera_controller.process_keyboard(keycode, event.state);
+                    }
                 }
-                
-                if let Some(img) = image::RgbaImage::from_raw(self.config.width, self.config.height, rgba_data) {
-                    // Note: Removed flip_vertical as it was inverting the image
-                    img.save(&filename).unwrap();
-                    println!("Screenshot saved to: {}", filename);
-                } else {
-                    eprintln!("Failed to create image from raw data");
+            }
+            WindowEvent::MouseInput { button, state, .. } => {
+                self.camera_controller.process_mouse(button, state);
+            }
+            WindowEvent::RedrawRequested => {
+                let Some(viewer) = &mut self.viewer else { return };
+                match viewer.renderer.render() {
+                    Ok(_) => {}
+                    Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                        let size = viewer.window.inner_size();
+                        self.resize(size);
+                    }
+                    Err(wgpu::SurfaceError::OutOfMemory) => {
+                        event_loop.exit();
+                    }
+                    Err(e) => eprintln!("{:?}", e),
                 }
             }
-            
-            output_buffer.unmap();
-            self.screenshot_requested = false;
-            
-            // Exit if in auto-screenshot mode
-            if self.auto_screenshot_mode {
-                println!("Auto-screenshot complete. Exiting...");
-                std::process::exit(0);
+            _ => {}
+        }
+    }
+
+    fn device_event(&mut self, _event_loop: &ActiveEventLoop, _device_id: DeviceId, event: DeviceEvent) {
+        if let DeviceEvent::MouseMotion { delta } = event {
+            self.camera_controller.process_mouse_motion(delta.0, delta.1);
+        }
+    }
+
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        if self.viewer.is_none() {
+            return;
+        }
+
+        let now = Instant::now();
+        if now - self.last_poll >= RELOAD_POLL_INTERVAL {
+            self.last_poll = now;
+            if self.watcher.poll() {
+                self.reload_scene();
+            }
+        }
+
+        let Some(viewer) = &mut self.viewer else { return };
+
+        // Update camera
+        let dt = (now - self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        match &mut self.transition {
+            Some(transition) => {
+                let pose = transition.advance(dt);
+                self.camera_controller.set_pose(&mut self.camera, pose);
+                if transition.is_finished() {
+                    self.transition = None;
+                }
             }
-        } else {
-            self.queue.submit(std::iter::once(encoder.finish()));
+            None => self.camera_controller.update_camera(&mut self.camera, dt),
         }
-        
-        output.present();
+        viewer.renderer.update_camera(&self.camera);
 
-        Ok(())
+        viewer.window.request_redraw();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,177 @@
+use super::{FloorPlan, Layer, Shape};
+use glam::Vec2;
+use std::fmt::Write;
+
+/// `$INSUNITS` code for metres.
+const METRES: u32 = 6;
+
+/// AutoCAD colour index of each layer.
+fn layer_colour(layer: Layer) -> u32 {
+    match layer {
+        Layer::Walls => 7,
+        Layer::Openings => 1,
+        Layer::Columns => 5,
+        Layer::Stairs => 3,
+        Layer::Furniture => 30,
+        Layer::Rooms => 6,
+        Layer::Dimensions => 8,
+    }
+}
+
+/// `plan` as an ASCII DXF drawing in the R12 format every CAD tool reads.
+///
+/// Each plan layer becomes a DXF layer of the same name in capitals, such as
+/// `WALLS` and `FURNITURE`. Drawings are in metres, with Y pointing to the
+/// scene's -Z so the plan reads as seen from above.
+pub fn plan_to_dxf(plan: &FloorPlan) -> String {
+    let mut dxf = Dxf::default();
+    let (min, max) = plan.bounds().unwrap_or((Vec2::ZERO, Vec2::ZERO));
+
+    dxf.section("HEADER");
+    dxf.pair(9, "$ACADVER");
+    dxf.pair(1, "AC1009");
+    dxf.pair(9, "$INSUNITS");
+    dxf.pair(70, METRES);
+    dxf.pair(9, "$EXTMIN");
+    dxf.point(10, Vec2::new(min.x, -max.y));
+    dxf.pair(9, "$EXTMAX");
+    dxf.point(10, Vec2::new(max.x, -min.y));
+    dxf.pair(0, "ENDSEC");
+
+    dxf.section("TABLES");
+    dxf.pair(0, "TABLE");
+    dxf.pair(2, "LTYPE");
+    dxf.pair(70, 1);
+    dxf.pair(0, "LTYPE");
+    dxf.pair(2, "CONTINUOUS");
+    dxf.pair(70, 0);
+    dxf.pair(3, "Solid line");
+    dxf.pair(72, 65);
+    dxf.pair(73, 0);
+    dxf.pair(40, 0.0);
+    dxf.pair(0, "ENDTAB");
+    dxf.pair(0, "TABLE");
+    dxf.pair(2, "LAYER");
+    dxf.pair(70, Layer::ALL.len());
+    for layer in Layer::ALL {
+        dxf.pair(0, "LAYER");
+        dxf.pair(2, layer_name(layer));
+        dxf.pair(70, 0);
+        dxf.pair(62, layer_colour(layer));
+        dxf.pair(6, "CONTINUOUS");
+    }
+    dxf.pair(0, "ENDTAB");
+    dxf.pair(0, "ENDSEC");
+
+    dxf.section("ENTITIES");
+    for item in &plan.items {
+        dxf.shape(&layer_name(item.layer), &item.shape);
+    }
+    dxf.pair(0, "ENDSEC");
+    dxf.pair(0, "EOF");
+    dxf.text
+}
+
+fn layer_name(layer: Layer) -> String {
+    layer.name().to_ascii_uppercase()
+}
+
+/// Writes DXF group code and value pairs.
+#[derive(Default)]
+struct Dxf {
+    text: String,
+}
+
+impl Dxf {
+    fn pair(&mut self, code: u32, value: impl std::fmt::Display) {
+        writeln!(self.text, "{:>3}\n{}", code, value).unwrap();
+    }
+
+    /// A plan point under `code` and the Y and Z codes following it.
+    fn point(&mut self, code: u32, point: Vec2) {
+        self.pair(code, number(point.x));
+        self.pair(code + 10, number(point.y));
+        self.pair(code + 20, "0.0");
+    }
+
+    fn section(&mut self, name: &str) {
+        self.pair(0, "SECTION");
+        self.pair(2, name);
+    }
+
+    fn entity(&mut self, kind: &str, layer: &str) {
+        self.pair(0, kind);
+        self.pair(8, layer);
+    }
+
+    fn shape(&mut self, layer: &str, shape: &Shape) {
+        match shape {
+            Shape::Polyline(points) if points.len() == 2 => {
+                self.entity("LINE", layer);
+                self.point(10, flip(points[0]));
+                self.point(11, flip(points[1]));
+            }
+            Shape::Polygon(points) | Shape::Polyline(points) => {
+                self.entity("POLYLINE", layer);
+                self.pair(66, 1);
+                self.point(10, Vec2::ZERO);
+                self.pair(70, u8::from(matches!(shape, Shape::Polygon(_))));
+                for &point in points {
+                    self.entity("VERTEX", layer);
+                    self.point(10, flip(point));
+                }
+                self.entity("SEQEND", layer);
+            }
+            Shape::Circle { centre, radius } => {
+                self.entity("CIRCLE", layer);
+                self.point(10, flip(*centre));
+                self.pair(40, number(*radius));
+            }
+            Shape::Arc { centre, radius, start, sweep } => {
+                // Flipping Y turns angles the other way; DXF arcs always run
+                // counter-clockwise from their start angle to their end angle
+                let (from, to) = (-start, -(start + sweep));
+                let (from, to) = if *sweep > 0.0 { (to, from) } else { (from, to) };
+                self.entity("ARC", layer);
+                self.point(10, flip(*centre));
+                self.pair(40, number(*radius));
+                self.pair(50, number(from.to_degrees().rem_euclid(360.0)));
+                self.pair(51, number(to.to_degrees().rem_euclid(360.0)));
+            }
+            Shape::Text { position, text, height, angle } => {
+                self.entity("TEXT", layer);
+                self.point(10, flip(*position));
+                self.pair(40, number(*height));
+                self.pair(1, escape(text));
+                self.pair(50, number((-angle.to_degrees()).rem_euclid(360.0)));
+                // Centred on the alignment point, horizontally and vertically
+                self.pair(72, 1);
+                self.point(11, flip(*position));
+                self.pair(73, 2);
+            }
+        }
+    }
+}
+
+/// A plan point in DXF's Y up drawing space.
+fn flip(point: Vec2) -> Vec2 {
+    Vec2::new(point.x, -point.y)
+}
+
+fn number(value: f32) -> String {
+    let text = format!("{:.4}", value);
+    let text = text.trim_end_matches('0');
+    let text = if text.ends_with('.') { format!("{}0", text) } else { text.to_string() };
+    if text == "-0.0" {
+        "0.0".to_string()
+    } else {
+        text
+    }
+}
+
+/// `text` in ASCII, with other characters as `\U+XXXX` escapes.
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_ascii() { c.to_string() } else { format!("\\U+{:04X}", c as u32) })
+        .collect()
+}
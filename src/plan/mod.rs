@@ -0,0 +1,523 @@
+pub mod dxf;
+pub mod svg;
+
+use crate::export::{element_name, ExportError};
+use crate::scene::furniture::furniture_to_mesh;
+use crate::scene::wall::Opening;
+use crate::scene::levels::{level_of, levels};
+use crate::scene::wall_graph::{room_corners, room_wall_segment, GraphWall, WallGraph};
+use crate::utils::polygon_contains;
+use crate::scene::{ColumnShape, DoorType, Element, Furniture, Scene, Stairs, WallFeature, WallSide};
+use glam::{Mat4, Vec2, Vec3};
+use std::path::{Path, PathBuf};
+
+/// Height above each level at which plans cut through the building.
+pub const DEFAULT_CUT_HEIGHT: f32 = 1.2;
+
+/// Distance of dimension lines from the wall centrelines they measure.
+const DIMENSION_OFFSET: f32 = 0.8;
+
+/// Gap between a measured point and the start of its extension line.
+const EXTENSION_GAP: f32 = 0.15;
+
+/// What a plan item shows, which decides how it is drawn and, in CAD files,
+/// the layer it goes on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    /// Cut walls, filled in as poché.
+    Walls,
+    /// Door swings and window symbols in the gaps of the walls.
+    Openings,
+    Columns,
+    Stairs,
+    Furniture,
+    /// Room names and areas.
+    Rooms,
+    Dimensions,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 7] = [
+        Layer::Walls,
+        Layer::Openings,
+        Layer::Columns,
+        Layer::Stairs,
+        Layer::Furniture,
+        Layer::Rooms,
+        Layer::Dimensions,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layer::Walls => "walls",
+            Layer::Openings => "openings",
+            Layer::Columns => "columns",
+            Layer::Stairs => "stairs",
+            Layer::Furniture => "furniture",
+            Layer::Rooms => "rooms",
+            Layer::Dimensions => "dimensions",
+        }
+    }
+}
+
+/// Plan geometry in metres, with plan `x` along the scene's X axis and plan
+/// `y` along its Z axis, as seen from above. Angles turn from +x towards +y.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// Closed outline, filled where the layer calls for it.
+    Polygon(Vec<Vec2>),
+    Polyline(Vec<Vec2>),
+    Circle { centre: Vec2, radius: f32 },
+    /// Arc turning `sweep` radians from `start`, either way round.
+    Arc { centre: Vec2, radius: f32, start: f32, sweep: f32 },
+    /// Text of `height` centred on `position`, running along `angle`.
+    Text { position: Vec2, text: String, height: f32, angle: f32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanItem {
+    pub layer: Layer,
+    pub shape: Shape,
+}
+
+/// A horizontal section through one level of a scene.
+#[derive(Debug, Clone)]
+pub struct FloorPlan {
+    /// Base elevation of the level.
+    pub elevation: f32,
+    /// Height of the section above `elevation`.
+    pub cut_height: f32,
+    pub items: Vec<PlanItem>,
+}
+
+impl FloorPlan {
+    /// Smallest and largest plan coordinates of the items, text positions
+    /// included, or `None` for an empty plan.
+    pub fn bounds(&self) -> Option<(Vec2, Vec2)> {
+        let mut points = Vec::new();
+        for item in &self.items {
+            match &item.shape {
+                Shape::Polygon(outline) | Shape::Polyline(outline) => points.extend_from_slice(outline),
+                Shape::Circle { centre, radius } | Shape::Arc { centre, radius, .. } => {
+                    points.extend([*centre - Vec2::splat(*radius), *centre + Vec2::splat(*radius)])
+                }
+                Shape::Text { position, .. } => points.push(*position),
+            }
+        }
+        let first = *points.first()?;
+        Some(points.iter().fold((first, first), |(min, max), &p| (min.min(p), max.max(p))))
+    }
+
+    fn add(&mut self, layer: Layer, shape: Shape) {
+        self.items.push(PlanItem { layer, shape });
+    }
+}
+
+/// A plan of every level of `scene`, cut `cut_height` above each.
+pub fn floor_plans(scene: &Scene, cut_height: f32) -> Vec<FloorPlan> {
+    let graph = WallGraph::from_scene(scene);
+    levels(scene)
+        .into_iter()
+        .map(|elevation| section(scene, &graph, elevation, cut_height))
+        .collect()
+}
+
+/// Write a plan of every level of `scene` to `path`, as an SVG drawing at
+/// 1:`scale` or a DXF drawing in metres, by the path's extension. Scenes
+/// with several levels get a file per level, numbered from the lowest:
+/// `plan_0.svg`, `plan_1.svg` and so on. Returns the paths written.
+pub fn write_floor_plans(scene: &Scene, path: &Path, cut_height: f32, scale: f32) -> Result<Vec<PathBuf>, ExportError> {
+    let extension = path.extension().and_then(|extension| extension.to_str()).map(str::to_ascii_lowercase);
+    let extension = match extension.as_deref() {
+        Some(extension @ ("svg" | "dxf")) => extension.to_string(),
+        _ => {
+            return Err(ExportError::UnsupportedFormat {
+                path: path.to_path_buf(),
+                expected: "an .svg or .dxf file",
+            })
+        }
+    };
+
+    let plans = floor_plans(scene, cut_height);
+    let mut written = Vec::new();
+    for (index, plan) in plans.iter().enumerate() {
+        let path = if plans.len() == 1 {
+            path.to_path_buf()
+        } else {
+            let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("plan");
+            path.with_file_name(format!("{}_{}.{}", stem, index, extension))
+        };
+        let contents = if extension == "dxf" {
+            dxf::plan_to_dxf(plan)
+        } else {
+            let caption = format!("{}, level {} at {:+.2} m, 1:{}", scene.name, index, plan.elevation, scale);
+            svg::plan_to_svg(plan, &caption, scale)
+        };
+        std::fs::write(&path, contents).map_err(|source| ExportError::Io { path: path.clone(), source })?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Plan of the level of `scene` at `elevation`, cut `cut_height` above it.
+///
+/// Walls and columns crossing the cut are drawn in section, with symbols
+/// for the doors and windows the cut passes through. Stairs, furniture,
+/// room labels and dimensions are drawn for elements standing on the level.
+/// Elements tilted or scaled out of upright by their transform are left out.
+pub fn floor_plan(scene: &Scene, elevation: f32, cut_height: f32) -> FloorPlan {
+    section(scene, &WallGraph::from_scene(scene), elevation, cut_height)
+}
+
+fn section(scene: &Scene, graph: &WallGraph, elevation: f32, cut_height: f32) -> FloorPlan {
+    let mut plan = FloorPlan {
+        elevation,
+        cut_height,
+        items: Vec::new(),
+    };
+    let cut = elevation + cut_height;
+    let levels = levels(scene);
+    let on_level = |element: &Element| level_of(&levels, element.base_elevation()) == elevation;
+
+    // Outlines of the level's rooms, which doors open into
+    let rooms: Vec<Vec<Vec2>> = scene
+        .elements
+        .iter()
+        .filter(|element| on_level(element))
+        .filter_map(|element| match element {
+            Element::Room(room) => {
+                let matrix = element.transform_matrix().unwrap_or(Mat4::IDENTITY);
+                Some(room_corners(room).map(|corner| plan_point(matrix.transform_point3(corner))).to_vec())
+            }
+            _ => None,
+        })
+        .collect();
+    let in_room = |point: Vec2| rooms.iter().any(|outline| polygon_contains(outline, point));
+
+    for wall in &graph.walls {
+        let segment = &wall.segment;
+        let base = segment.base_elevation();
+        if cut < base || cut > base + segment.height || segment.length() <= 0.0 {
+            continue;
+        }
+        cut_wall(&mut plan, wall, cut - base, &in_room);
+    }
+    for hub in &graph.hubs {
+        if cut >= hub.elevation && cut <= hub.elevation + hub.height {
+            plan.add(Layer::Walls, Shape::Polygon(hub.outline.clone()));
+        }
+    }
+
+    let mut dimensions: Vec<(Vec2, Vec2, Vec2)> = Vec::new();
+    for (index, element) in scene.elements.iter().enumerate() {
+        let upright = element.transform().is_none_or(|transform| transform.is_planar_rigid());
+        if !upright || !on_level(element) {
+            continue;
+        }
+        let matrix = element.transform_matrix().unwrap_or(Mat4::IDENTITY);
+        let to_plan = |point: Vec3| plan_point(matrix.transform_point3(point));
+
+        match element {
+            Element::Room(room) => {
+                let centre = to_plan(room.position);
+                let height = 0.25;
+                plan.add(
+                    Layer::Rooms,
+                    text(centre - Vec2::new(0.0, height * 0.75), element_name(element, index), height),
+                );
+                plan.add(
+                    Layer::Rooms,
+                    text(centre + Vec2::new(0.0, height * 0.75), format!("{:.2} m²", room.floor_area()), height * 0.8),
+                );
+
+                // Width along the side at -Z and depth along the side at
+                // -X, outside the room: room walls face outwards
+                for side in [WallSide::South, WallSide::West] {
+                    let segment = room_wall_segment(room, &side);
+                    let (start, end) = (to_plan(segment.start), to_plan(segment.end));
+                    dimensions.push((start, end, left_of(end - start)));
+                }
+            }
+            Element::Wall(wall) => {
+                let (start, end) = (to_plan(wall.start), to_plan(wall.end));
+                dimensions.push((start, end, left_of(end - start) * (1.0 + wall.thickness * 0.5 / DIMENSION_OFFSET)));
+            }
+            Element::Column(column) => {
+                let centre = to_plan(column.position);
+                let shape = match column.shape {
+                    ColumnShape::Round => {
+                        let radius = column.radius * matrix.transform_vector3(Vec3::X).length();
+                        Shape::Circle { centre, radius }
+                    }
+                    ColumnShape::Square => Shape::Polygon(
+                        [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                            .map(|(x, z)| to_plan(column.position + Vec3::new(x, 0.0, z) * column.radius))
+                            .to_vec(),
+                    ),
+                    ColumnShape::Hexagonal => Shape::Polygon(
+                        (0..6)
+                            .map(|k| {
+                                let angle = k as f32 * std::f32::consts::FRAC_PI_3;
+                                to_plan(column.position + Vec3::new(angle.cos(), 0.0, angle.sin()) * column.radius)
+                            })
+                            .collect(),
+                    ),
+                };
+                plan.add(Layer::Columns, shape);
+            }
+            Element::Stairs(stairs) => stairs_outline(&mut plan, stairs, &to_plan),
+            Element::Furniture(furniture) => {
+                plan.add(Layer::Furniture, Shape::Polygon(furniture_footprint(furniture).map(to_plan).to_vec()));
+            }
+            Element::Floor(_) | Element::Ceiling(_) => {}
+        }
+    }
+
+    // Rooms sharing a wall would measure it twice
+    let mut drawn: Vec<(Vec2, Vec2)> = Vec::new();
+    for (start, end, side) in dimensions {
+        let same = |a: Vec2, b: Vec2| a.abs_diff_eq(b, 1e-3);
+        if drawn.iter().any(|&(a, b)| (same(a, start) && same(b, end)) || (same(a, end) && same(b, start))) {
+            continue;
+        }
+        drawn.push((start, end));
+        dimension(&mut plan, start, end, side * DIMENSION_OFFSET);
+    }
+
+    plan
+}
+
+/// Draw the section through `wall` at `height` above its base: the solid
+/// pieces between the openings the cut passes through, and their symbols.
+/// Doors open into the side `in_room` holds, or the back of the wall.
+fn cut_wall(plan: &mut FloorPlan, wall: &GraphWall, height: f32, in_room: &impl Fn(Vec2) -> bool) {
+    let segment = &wall.segment;
+    let matrix = segment.local_to_world();
+    let to_plan = |x: f32, z: f32| plan_point(matrix.transform_point3(Vec3::new(x, 0.0, z)));
+    let half_length = segment.length() * 0.5;
+    let half_t = segment.thickness * 0.5;
+
+    let mut openings: Vec<(f32, f32, &WallFeature)> = wall
+        .features
+        .iter()
+        .filter_map(|feature| {
+            let opening = Opening::from_feature(feature);
+            let (min, max) = (opening.min.x.max(-half_length), opening.max.x.min(half_length));
+            (opening.min.y <= height && opening.max.y >= height && max > min).then_some((min, max, feature))
+        })
+        .collect();
+    openings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Solid pieces run from the mitered wall ends or the edge of an opening
+    let ends = segment.ends;
+    let mut from = (-half_length - ends.start_front, -half_length - ends.start_back);
+    for &(min, max, _) in &openings {
+        if min > from.0.max(from.1) {
+            plan.add(
+                Layer::Walls,
+                Shape::Polygon(vec![to_plan(from.0, half_t), to_plan(min, half_t), to_plan(min, -half_t), to_plan(from.1, -half_t)]),
+            );
+        }
+        from = (from.0.max(max), from.1.max(max));
+    }
+    let to = (half_length + ends.end_front, half_length + ends.end_back);
+    if to.0 > from.0 && to.1 > from.1 {
+        plan.add(
+            Layer::Walls,
+            Shape::Polygon(vec![to_plan(from.0, half_t), to_plan(to.0, half_t), to_plan(to.1, -half_t), to_plan(from.1, -half_t)]),
+        );
+    }
+
+    for (min, max, feature) in openings {
+        match feature {
+            WallFeature::Window(_) => {
+                // Both wall faces carried across, and the glass between them
+                for z in [half_t, 0.0, -half_t] {
+                    plan.add(Layer::Openings, Shape::Polyline(vec![to_plan(min, z), to_plan(max, z)]));
+                }
+                for x in [min, max] {
+                    plan.add(Layer::Openings, Shape::Polyline(vec![to_plan(x, half_t), to_plan(x, -half_t)]));
+                }
+            }
+            WallFeature::Door(door) => {
+                let middle = (min + max) * 0.5;
+                let reach = half_t + (max - min) * 0.5;
+                if in_room(to_plan(middle, reach)) && !in_room(to_plan(middle, -reach)) {
+                    door_symbol(plan, &door.door_type, min, max, half_t, &to_plan);
+                } else {
+                    door_symbol(plan, &door.door_type, min, max, half_t, &|x, z| to_plan(x, -z));
+                }
+            }
+        }
+    }
+}
+
+/// Draw a door of `door_type` filling `min..max` along a wall of half
+/// thickness `half_t`, opening towards +Z of the frame `to_plan` maps from.
+fn door_symbol(plan: &mut FloorPlan, door_type: &DoorType, min: f32, max: f32, half_t: f32, to_plan: &impl Fn(f32, f32) -> Vec2) {
+    let width = max - min;
+    // A leaf hinged at `hinge`, standing open across `reach` and swinging
+    // back onto the wall towards `closed`
+    let mut leaf = |hinge: f32, reach: f32, closed: f32| {
+        let centre = to_plan(hinge, half_t);
+        let open = to_plan(hinge, half_t + reach);
+        let shut = to_plan(closed, half_t);
+        plan.add(Layer::Openings, Shape::Polyline(vec![centre, open]));
+        let start = angle_of(open - centre);
+        plan.add(
+            Layer::Openings,
+            Shape::Arc {
+                centre,
+                radius: reach,
+                start,
+                sweep: signed_angle(open - centre, shut - centre),
+            },
+        );
+    };
+
+    match door_type {
+        DoorType::Single => leaf(min, width, max),
+        DoorType::Double => {
+            let middle = (min + max) * 0.5;
+            leaf(min, width * 0.5, middle);
+            leaf(max, width * 0.5, middle);
+        }
+        DoorType::Sliding => {
+            // Two panels on either side of the centreline, overlapping in the middle
+            let (panel, overlap) = (half_t * 0.4, width * 0.05);
+            let middle = (min + max) * 0.5;
+            for (from, to, z) in [(min, middle + overlap, half_t * 0.5), (middle - overlap, max, -half_t * 0.5)] {
+                plan.add(
+                    Layer::Openings,
+                    Shape::Polygon(vec![
+                        to_plan(from, z - panel * 0.5),
+                        to_plan(to, z - panel * 0.5),
+                        to_plan(to, z + panel * 0.5),
+                        to_plan(from, z + panel * 0.5),
+                    ]),
+                );
+            }
+        }
+        DoorType::Revolving => {
+            let middle = (min + max) * 0.5;
+            let radius = width * 0.5;
+            plan.add(Layer::Openings, Shape::Circle { centre: to_plan(middle, 0.0), radius });
+            // Four wings turned 45 degrees from the wall
+            let wing = radius * std::f32::consts::FRAC_1_SQRT_2;
+            for (x, z) in [(wing, wing), (wing, -wing)] {
+                plan.add(Layer::Openings, Shape::Polyline(vec![to_plan(middle - x, -z), to_plan(middle + x, z)]));
+            }
+        }
+    }
+}
+
+/// Outline of a flight of stairs with a line at every tread and an arrow
+/// pointing up the flight.
+fn stairs_outline(plan: &mut FloorPlan, stairs: &Stairs, to_plan: &impl Fn(Vec3) -> Vec2) {
+    let half_w = stairs.width * 0.5;
+    let run = stairs.steps as f32 * stairs.step_depth;
+    let at = |x: f32, z: f32| to_plan(stairs.position + Vec3::new(x, 0.0, z));
+
+    plan.add(
+        Layer::Stairs,
+        Shape::Polygon(vec![at(-half_w, 0.0), at(half_w, 0.0), at(half_w, run), at(-half_w, run)]),
+    );
+    for step in 1..stairs.steps {
+        let z = step as f32 * stairs.step_depth;
+        plan.add(Layer::Stairs, Shape::Polyline(vec![at(-half_w, z), at(half_w, z)]));
+    }
+    let head = stairs.step_depth.min(half_w) * 0.8;
+    plan.add(
+        Layer::Stairs,
+        Shape::Polyline(vec![at(0.0, stairs.step_depth * 0.5), at(0.0, run - stairs.step_depth * 0.5)]),
+    );
+    plan.add(
+        Layer::Stairs,
+        Shape::Polyline(vec![
+            at(-head * 0.5, run - stairs.step_depth * 0.5 - head),
+            at(0.0, run - stairs.step_depth * 0.5),
+            at(head * 0.5, run - stairs.step_depth * 0.5 - head),
+        ]),
+    );
+}
+
+/// Corners of the area a piece of furniture covers, in scene coordinates
+/// before the element's transform.
+pub fn furniture_footprint(furniture: &Furniture) -> [Vec3; 4] {
+    let local = Furniture {
+        position: Vec3::ZERO,
+        rotation: 0.0,
+        transform: None,
+        ..furniture.clone()
+    };
+    let mesh = furniture_to_mesh(&local);
+    let (min, max) = mesh.vertices.iter().fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), vertex| {
+        (min.min(Vec3::from(vertex.position)), max.max(Vec3::from(vertex.position)))
+    });
+    let placement = Mat4::from_rotation_translation(glam::Quat::from_rotation_y(furniture.rotation.to_radians()), furniture.position);
+    [(min.x, min.z), (max.x, min.z), (max.x, max.z), (min.x, max.z)]
+        .map(|(x, z)| placement.transform_point3(Vec3::new(x, 0.0, z)))
+}
+
+/// Dimension line measuring `start` to `end`, drawn `offset` away from them
+/// with extension lines, tick marks and the length in metres.
+fn dimension(plan: &mut FloorPlan, start: Vec2, end: Vec2, offset: Vec2) {
+    let along = (end - start).normalize_or_zero();
+    let outward = offset.normalize_or_zero();
+    let (a, b) = (start + offset, end + offset);
+
+    plan.add(Layer::Dimensions, Shape::Polyline(vec![a - along * 0.1, b + along * 0.1]));
+    for (point, on_line) in [(start, a), (end, b)] {
+        plan.add(
+            Layer::Dimensions,
+            Shape::Polyline(vec![point + outward * EXTENSION_GAP, on_line + outward * 0.1]),
+        );
+        let tick = (along + outward).normalize_or_zero() * 0.08;
+        plan.add(Layer::Dimensions, Shape::Polyline(vec![on_line - tick, on_line + tick]));
+    }
+
+    // Text reads from the left or from below, never upside down
+    let mut angle = angle_of(along);
+    if angle >= std::f32::consts::FRAC_PI_2 - 1e-3 {
+        angle -= std::f32::consts::PI;
+    } else if angle < -std::f32::consts::FRAC_PI_2 - 1e-3 {
+        angle += std::f32::consts::PI;
+    }
+    let height = 0.2;
+    plan.add(
+        Layer::Dimensions,
+        Shape::Text {
+            position: (a + b) * 0.5 + outward * height * 0.8,
+            text: format!("{:.2}", start.distance(end)),
+            height,
+            angle,
+        },
+    );
+}
+
+fn text(position: Vec2, text: String, height: f32) -> Shape {
+    Shape::Text {
+        position,
+        text,
+        height,
+        angle: 0.0,
+    }
+}
+
+fn plan_point(point: Vec3) -> Vec2 {
+    Vec2::new(point.x, point.z)
+}
+
+/// Left-hand normal of a plan direction.
+fn left_of(direction: Vec2) -> Vec2 {
+    Vec2::new(-direction.y, direction.x).normalize_or_zero()
+}
+
+fn angle_of(direction: Vec2) -> f32 {
+    direction.y.atan2(direction.x)
+}
+
+/// Angle turning from `from` to `to`, in `-PI..=PI`.
+fn signed_angle(from: Vec2, to: Vec2) -> f32 {
+    from.perp_dot(to).atan2(from.dot(to))
+}
@@ -0,0 +1,157 @@
+use super::{FloorPlan, Layer, Shape};
+use glam::Vec2;
+use std::fmt::Write;
+
+/// Space left around the drawing, in metres.
+const MARGIN: f32 = 1.0;
+
+/// Pen width of each layer on paper, in millimetres.
+fn pen_width(layer: Layer) -> f32 {
+    match layer {
+        Layer::Walls | Layer::Columns => 0.5,
+        Layer::Openings => 0.25,
+        Layer::Stairs | Layer::Furniture => 0.18,
+        Layer::Rooms | Layer::Dimensions => 0.13,
+    }
+}
+
+/// `plan` as an SVG drawing at 1:`scale`, sized in millimetres on paper, with
+/// `caption` written underneath.
+///
+/// Each layer is a group with the layer's name as its `id`. Cut walls and
+/// columns are filled in solid; everything else is drawn in outline.
+pub fn plan_to_svg(plan: &FloorPlan, caption: &str, scale: f32) -> String {
+    let (min, max) = plan.bounds().unwrap_or((Vec2::ZERO, Vec2::ZERO));
+    let caption_height = 0.35;
+    let min = min - Vec2::splat(MARGIN);
+    let max = max + Vec2::new(MARGIN, MARGIN + caption_height * 2.0);
+    let size = max - min;
+    let millimetres = 1000.0 / scale;
+
+    let mut svg = String::new();
+    writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}mm" height="{}mm" viewBox="{} {} {} {}">"#,
+        number(size.x * millimetres),
+        number(size.y * millimetres),
+        number(min.x),
+        number(min.y),
+        number(size.x),
+        number(size.y)
+    )
+    .unwrap();
+    writeln!(svg, "<title>{}</title>", escape(caption)).unwrap();
+
+    // Pen widths are given on paper, so they stay the same at any scale
+    let stroke = |layer: Layer| number(pen_width(layer) / millimetres);
+    writeln!(svg, "<style>").unwrap();
+    writeln!(svg, "  g {{ fill: none; stroke: #000; stroke-linecap: round; stroke-linejoin: round; }}").unwrap();
+    for layer in Layer::ALL {
+        writeln!(svg, "  #{} {{ stroke-width: {}; }}", layer.name(), stroke(layer)).unwrap();
+    }
+    writeln!(svg, "  #walls, #columns {{ fill: #1a1a1a; stroke: #1a1a1a; }}").unwrap();
+    writeln!(svg, "  #furniture, #stairs {{ stroke: #555; }}").unwrap();
+    writeln!(svg, "  #dimensions {{ stroke: #333; }}").unwrap();
+    writeln!(
+        svg,
+        "  text {{ fill: #000; stroke: none; font-family: sans-serif; text-anchor: middle; dominant-baseline: central; }}"
+    )
+    .unwrap();
+    writeln!(svg, "</style>").unwrap();
+
+    for layer in Layer::ALL {
+        let shapes: Vec<&Shape> = plan.items.iter().filter(|item| item.layer == layer).map(|item| &item.shape).collect();
+        if shapes.is_empty() {
+            continue;
+        }
+        writeln!(svg, r#"<g id="{}">"#, layer.name()).unwrap();
+        for shape in shapes {
+            writeln!(svg, "  {}", element(shape)).unwrap();
+        }
+        writeln!(svg, "</g>").unwrap();
+    }
+
+    let caption = Shape::Text {
+        position: Vec2::new((min.x + max.x) * 0.5, max.y - MARGIN * 0.5 - caption_height),
+        text: caption.to_string(),
+        height: caption_height,
+        angle: 0.0,
+    };
+    writeln!(svg, "<g>{}</g>", element(&caption)).unwrap();
+    writeln!(svg, "</svg>").unwrap();
+    svg
+}
+
+/// SVG element drawing `shape`. Plan coordinates map straight onto SVG's, so
+/// the view from above isn't mirrored and plan angles turn the same way.
+fn element(shape: &Shape) -> String {
+    match shape {
+        Shape::Polygon(points) => format!(r#"<polygon points="{}"/>"#, points_list(points)),
+        Shape::Polyline(points) => format!(r#"<polyline points="{}"/>"#, points_list(points)),
+        Shape::Circle { centre, radius } => format!(
+            r#"<circle cx="{}" cy="{}" r="{}"/>"#,
+            number(centre.x),
+            number(centre.y),
+            number(*radius)
+        ),
+        Shape::Arc { centre, radius, start, sweep } => {
+            let from = *centre + Vec2::from_angle(*start) * *radius;
+            let to = *centre + Vec2::from_angle(start + sweep) * *radius;
+            format!(
+                r#"<path d="M {} {} A {} {} 0 {} {} {} {}"/>"#,
+                number(from.x),
+                number(from.y),
+                number(*radius),
+                number(*radius),
+                u8::from(sweep.abs() > std::f32::consts::PI),
+                u8::from(*sweep > 0.0),
+                number(to.x),
+                number(to.y)
+            )
+        }
+        Shape::Text { position, text, height, angle } => {
+            let mut element = format!(
+                r#"<text x="{}" y="{}" font-size="{}""#,
+                number(position.x),
+                number(position.y),
+                number(*height)
+            );
+            if *angle != 0.0 {
+                write!(
+                    element,
+                    r#" transform="rotate({} {} {})""#,
+                    number(angle.to_degrees()),
+                    number(position.x),
+                    number(position.y)
+                )
+                .unwrap();
+            }
+            write!(element, ">{}</text>", escape(text)).unwrap();
+            element
+        }
+    }
+}
+
+fn points_list(points: &[Vec2]) -> String {
+    points
+        .iter()
+        .map(|point| format!("{},{}", number(point.x), number(point.y)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `value` to the millimetre and a bit, without trailing zeros.
+fn number(value: f32) -> String {
+    let text = format!("{:.4}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -30,7 +30,7 @@ pub struct IndexBuffer {
 }
 
 impl IndexBuffer {
-    pub fn new(device: &Device, indices: &[u16]) -> Self {
+    pub fn new(device: &Device, indices: &[u32]) -> Self {
         let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Index Buffer"),
             contents: bytemuck::cast_slice(indices),
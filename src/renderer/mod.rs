@@ -1,24 +1,82 @@
 pub mod pipeline;
 pub mod buffer;
+pub mod texture;
+pub mod scene_renderer;
+pub mod offscreen;
+pub mod shadow;
 
-use crate::core::{state::GpuState, surface::RenderSurface};
-use winit::window::Window;
+use crate::camera::Camera;
+use crate::core::{state::{GpuError, GpuState}, surface::RenderSurface};
+use crate::scene::Scene;
+use scene_renderer::SceneRenderer;
+use shadow::ShadowSettings;
+use std::path::Path;
+use winit::dpi::PhysicalSize;
 
+/// Draws a scene into a window: the GPU device, the window's surface and a
+/// [`SceneRenderer`] targeting it.
 pub struct Renderer<'window> {
     pub gpu_state: GpuState,
     pub surface: RenderSurface<'window>,
+    pub scene_renderer: SceneRenderer,
 }
 
 impl<'window> Renderer<'window> {
-    pub async fn new(window: &'window Window) -> Self {
-        let gpu_state = GpuState::new(window).await;
-        let surface = RenderSurface::new(window, &gpu_state.instance, &gpu_state.adapter, &gpu_state.device);
+    /// Renderer presenting `scene` to `target`, usually a window, of `size`.
+    /// Texture paths in the scene's materials are relative to `asset_dir`.
+    pub async fn new(
+        target: impl Into<wgpu::SurfaceTarget<'window>>,
+        size: PhysicalSize<u32>,
+        scene: &Scene,
+        asset_dir: &Path,
+    ) -> Result<Self, GpuError> {
+        let instance = GpuState::create_instance();
+        let surface = instance.create_surface(target).map_err(GpuError::CreateSurface)?;
+        let gpu_state = GpuState::new(instance, Some(&surface)).await?;
+        let surface = RenderSurface::new(surface, &gpu_state.adapter, &gpu_state.device, size);
 
-        Self { gpu_state, surface }
+        let size = surface.size();
+        let scene_renderer = SceneRenderer::new(
+            &gpu_state.device,
+            &gpu_state.queue,
+            surface.format(),
+            size.width,
+            size.height,
+            scene,
+            asset_dir,
+        );
+
+        Ok(Self {
+            gpu_state,
+            surface,
+            scene_renderer,
+        })
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.surface.size()
+    }
+
+    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.surface.resize(&self.gpu_state.device, new_size);
+            self.scene_renderer
+                .resize(&self.gpu_state.device, new_size.width, new_size.height);
+        }
+    }
+
+    pub fn load_scene(&mut self, scene: &Scene, asset_dir: &Path) {
+        self.scene_renderer
+            .load_scene(&self.gpu_state.device, &self.gpu_state.queue, scene, asset_dir);
     }
 
-    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        self.surface.resize(&self.gpu_state.device, new_size);
+    pub fn set_shadow_settings(&mut self, settings: ShadowSettings) {
+        self.scene_renderer
+            .set_shadow_settings(&self.gpu_state.device, &self.gpu_state.queue, settings);
+    }
+
+    pub fn update_camera(&mut self, camera: &Camera) {
+        self.scene_renderer.update_camera(&self.gpu_state.queue, camera);
     }
 
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
@@ -31,32 +89,25 @@ impl<'window> Renderer<'window> {
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             });
-
-        {
-            let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-        }
+        self.scene_renderer.render(&mut encoder, &view);
 
         self.gpu_state.queue.submit(std::iter::once(encoder.finish()));
         output.present();
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Draw the current view again into an image the size of the window, or
+    /// `None` if the surface format isn't 8-bit RGBA or BGRA.
+    pub fn capture(&self) -> Option<image::RgbaImage> {
+        let size = self.surface.size();
+        offscreen::render_to_image(
+            &self.gpu_state.device,
+            &self.gpu_state.queue,
+            &self.scene_renderer,
+            self.surface.format(),
+            size.width,
+            size.height,
+        )
+    }
+}
@@ -0,0 +1,173 @@
+use crate::camera::Camera;
+use crate::core::state::{GpuError, GpuState};
+use crate::renderer::scene_renderer::SceneRenderer;
+use crate::renderer::shadow::ShadowSettings;
+use crate::scene::Scene;
+use std::path::{Path, PathBuf};
+use wgpu::{Device, Queue, TextureFormat};
+
+/// Colour format of offscreen renders: the bytes read back are already RGBA.
+const OFFSCREEN_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+/// Renders scenes to images without a window or surface.
+///
+/// Prefers a hardware adapter and falls back to wgpu's software adapter, so
+/// it also runs on CI machines without a GPU.
+pub struct OffscreenRenderer {
+    gpu_state: GpuState,
+    asset_dir: PathBuf,
+    shadow_settings: ShadowSettings,
+}
+
+impl OffscreenRenderer {
+    pub async fn new() -> Result<Self, GpuError> {
+        Ok(Self {
+            gpu_state: GpuState::headless().await?,
+            asset_dir: PathBuf::from("."),
+            shadow_settings: ShadowSettings::default(),
+        })
+    }
+
+    /// Directory that texture paths in scene materials are relative to,
+    /// usually the scene file's directory. Defaults to the working directory.
+    pub fn with_asset_dir(mut self, asset_dir: impl Into<PathBuf>) -> Self {
+        self.asset_dir = asset_dir.into();
+        self
+    }
+
+    pub fn with_shadow_settings(mut self, shadow_settings: ShadowSettings) -> Self {
+        self.shadow_settings = shadow_settings;
+        self
+    }
+
+    pub fn adapter_info(&self) -> wgpu::AdapterInfo {
+        self.gpu_state.adapter.get_info()
+    }
+
+    /// Render `scene` from `camera` into a `width`×`height` image. The
+    /// camera's aspect ratio is taken from the resolution.
+    pub fn render(&self, scene: &Scene, camera: &Camera, width: u32, height: u32) -> image::RgbaImage {
+        let GpuState { device, queue, .. } = &self.gpu_state;
+        let mut scene_renderer = SceneRenderer::new(
+            device,
+            queue,
+            OFFSCREEN_FORMAT,
+            width,
+            height,
+            scene,
+            Path::new(&self.asset_dir),
+        );
+        if self.shadow_settings != scene_renderer.shadow_settings() {
+            scene_renderer.set_shadow_settings(device, queue, self.shadow_settings);
+        }
+        let camera = Camera {
+            aspect: width as f32 / height as f32,
+            ..*camera
+        };
+        scene_renderer.update_camera(queue, &camera);
+
+        render_to_image(device, queue, &scene_renderer, OFFSCREEN_FORMAT, width, height)
+            .expect("offscreen renders are RGBA")
+    }
+}
+
+/// Draw `scene_renderer` into a fresh `format` texture and read it back as
+/// RGBA, or `None` if `format` isn't 8-bit RGBA or BGRA. `width`×`height`
+/// must match the size `scene_renderer` was created or last resized with.
+pub(crate) fn render_to_image(
+    device: &Device,
+    queue: &Queue,
+    scene_renderer: &SceneRenderer,
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+) -> Option<image::RgbaImage> {
+    // Pixels are read back as stored; surfaces are commonly BGRA
+    let swap_red_blue = match format {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => false,
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => true,
+        _ => return None,
+    };
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Capture Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    // Rows of a texture copy must be padded to a multiple of 256 bytes
+    let unpadded_bytes_per_row = format.block_copy_size(None)? * width;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+    let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Capture Buffer"),
+        size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Capture Encoder"),
+    });
+    scene_renderer.render(&mut encoder, &view);
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &output_buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    let submission_index = queue.submit(std::iter::once(encoder.finish()));
+
+    let buffer_slice = output_buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+        sender.send(result).unwrap();
+    });
+    device.poll(wgpu::Maintain::WaitForSubmissionIndex(submission_index));
+    receiver
+        .recv()
+        .expect("map_async callback dropped")
+        .expect("failed to map capture buffer");
+
+    let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    {
+        let data = buffer_slice.get_mapped_range();
+        for row in data.chunks_exact(padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+    }
+    output_buffer.unmap();
+
+    if swap_red_blue {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    Some(image::RgbaImage::from_raw(width, height, pixels).expect("pixel buffer matches the image size"))
+}
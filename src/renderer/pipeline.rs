@@ -1,64 +1,147 @@
-use wgpu::{RenderPipeline, Device, ShaderModule};
+use wgpu::{
+    BindGroupLayout, BlendState, DepthBiasState, Device, Face, RenderPipeline, ShaderModule, TextureFormat,
+    VertexBufferLayout,
+};
 
-pub struct RenderPipelineBuilder {
-    vertex_shader: Option<ShaderModule>,
-    fragment_shader: Option<ShaderModule>,
+/// Builds a triangle-list pipeline running `vs_main` and `fs_main` from its
+/// shaders, culling back faces unless told otherwise.
+pub struct RenderPipelineBuilder<'a> {
+    label: &'a str,
+    vertex_shader: Option<&'a ShaderModule>,
+    fragment_shader: Option<&'a ShaderModule>,
+    bind_group_layouts: Vec<&'a BindGroupLayout>,
+    vertex_buffers: Vec<VertexBufferLayout<'a>>,
+    blend: BlendState,
+    cull_mode: Option<Face>,
+    depth_format: Option<TextureFormat>,
+    depth_bias: DepthBiasState,
 }
 
-impl RenderPipelineBuilder {
+impl<'a> RenderPipelineBuilder<'a> {
     pub fn new() -> Self {
         Self {
+            label: "Render Pipeline",
             vertex_shader: None,
             fragment_shader: None,
+            bind_group_layouts: Vec::new(),
+            vertex_buffers: Vec::new(),
+            blend: BlendState::REPLACE,
+            cull_mode: Some(Face::Back),
+            depth_format: None,
+            depth_bias: DepthBiasState::default(),
         }
     }
 
-    pub fn vertex_shader(mut self, shader: ShaderModule) -> Self {
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = label;
+        self
+    }
+
+    pub fn vertex_shader(mut self, shader: &'a ShaderModule) -> Self {
         self.vertex_shader = Some(shader);
         self
     }
 
-    pub fn fragment_shader(mut self, shader: ShaderModule) -> Self {
+    pub fn fragment_shader(mut self, shader: &'a ShaderModule) -> Self {
         self.fragment_shader = Some(shader);
         self
     }
 
-    pub fn build(self, device: &Device, format: wgpu::TextureFormat) -> RenderPipeline {
+    /// Use `shader` for both stages.
+    pub fn shader(self, shader: &'a ShaderModule) -> Self {
+        self.vertex_shader(shader).fragment_shader(shader)
+    }
+
+    /// Add the layout of the next bind group, in group order.
+    pub fn bind_group_layout(mut self, layout: &'a BindGroupLayout) -> Self {
+        self.bind_group_layouts.push(layout);
+        self
+    }
+
+    /// Add the layout of the next vertex buffer slot.
+    pub fn vertex_buffer(mut self, layout: VertexBufferLayout<'a>) -> Self {
+        self.vertex_buffers.push(layout);
+        self
+    }
+
+    pub fn blend(mut self, blend: BlendState) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn cull_mode(mut self, cull_mode: Option<Face>) -> Self {
+        self.cull_mode = cull_mode;
+        self
+    }
+
+    /// Depth-test with `Less` and write depth into a `format` attachment.
+    pub fn depth_format(mut self, format: TextureFormat) -> Self {
+        self.depth_format = Some(format);
+        self
+    }
+
+    /// Offset written depth away from the viewer, as shadow maps need.
+    pub fn depth_bias(mut self, depth_bias: DepthBiasState) -> Self {
+        self.depth_bias = depth_bias;
+        self
+    }
+
+    pub fn build(self, device: &Device, format: TextureFormat) -> RenderPipeline {
+        let fragment_shader = self.fragment_shader.expect("pipeline needs a fragment shader");
+        let targets = [Some(wgpu::ColorTargetState {
+            format,
+            blend: Some(self.blend),
+            write_mask: wgpu::ColorWrites::ALL,
+        })];
+        let fragment = wgpu::FragmentState {
+            module: fragment_shader,
+            entry_point: Some("fs_main"),
+            targets: &targets,
+            compilation_options: Default::default(),
+        };
+        self.build_with_fragment(device, Some(fragment))
+    }
+
+    /// Pipeline without a fragment stage or colour target, writing only depth.
+    pub fn build_depth_only(self, device: &Device) -> RenderPipeline {
+        self.build_with_fragment(device, None)
+    }
+
+    fn build_with_fragment(self, device: &Device, fragment: Option<wgpu::FragmentState>) -> RenderPipeline {
+        let vertex_shader = self.vertex_shader.expect("pipeline needs a vertex shader");
+
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Render Pipeline Layout"),
-            bind_group_layouts: &[],
+            label: Some(&format!("{} Layout", self.label)),
+            bind_group_layouts: &self.bind_group_layouts,
             push_constant_ranges: &[],
         });
 
         device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
+            label: Some(self.label),
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &self.vertex_shader.unwrap(),
+                module: vertex_shader,
                 entry_point: Some("vs_main"),
-                buffers: &[],
+                buffers: &self.vertex_buffers,
                 compilation_options: Default::default(),
             },
-            fragment: Some(wgpu::FragmentState {
-                module: &self.fragment_shader.unwrap(),
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: Default::default(),
-            }),
+            fragment,
             primitive: wgpu::PrimitiveState {
                 topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
+                cull_mode: self.cull_mode,
                 polygon_mode: wgpu::PolygonMode::Fill,
                 unclipped_depth: false,
                 conservative: false,
             },
-            depth_stencil: None,
+            depth_stencil: self.depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: self.depth_bias,
+            }),
             multisample: wgpu::MultisampleState {
                 count: 1,
                 mask: !0,
@@ -68,4 +151,10 @@ impl RenderPipelineBuilder {
             cache: None,
         })
     }
-}
\ No newline at end of file
+}
+
+impl Default for RenderPipelineBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,401 @@
+use crate::camera::{Camera, CameraUniform};
+use crate::renderer::buffer::{IndexBuffer, VertexBuffer};
+use crate::renderer::pipeline::RenderPipelineBuilder;
+use crate::renderer::shadow::{ShadowMap, ShadowSettings};
+use crate::renderer::texture::Texture;
+use crate::scene::light::{lights_to_gpu, GpuLight};
+use crate::scene::loader::scene_to_mesh;
+use crate::scene::material::{batch_by_texture, DrawBatch, MaterialLibrary};
+use crate::scene::primitives::Vertex;
+use crate::scene::Scene;
+use crate::shaders::{SCENE_SHADER, TEXTURED_SHADER};
+use crate::utils::create_shader_module;
+use std::path::Path;
+use wgpu::util::DeviceExt;
+use wgpu::{BindGroup, BindGroupLayout, Buffer, CommandEncoder, Device, Queue, RenderPipeline, TextureFormat, TextureView};
+
+pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+/// Sky colour behind the scene.
+pub const CLEAR_COLOR: wgpu::Color = wgpu::Color {
+    r: 0.5,
+    g: 0.7,
+    b: 0.9,
+    a: 1.0,
+};
+
+/// Draws a [`Scene`] into any colour target of a given format: a window
+/// surface or an offscreen texture.
+///
+/// Owns the scene and textured pipelines, the camera bind group, the bind
+/// group of the scene's materials, lights and shadow map, the texture bind
+/// groups, the scene's mesh buffers and a depth texture matching the target
+/// size.
+pub struct SceneRenderer {
+    render_pipeline: RenderPipeline,
+    textured_pipeline: RenderPipeline,
+    camera_uniform: CameraUniform,
+    camera_buffer: Buffer,
+    camera_bind_group: BindGroup,
+    scene_bind_group_layout: BindGroupLayout,
+    scene_bind_group: BindGroup,
+    material_buffer: Buffer,
+    light_buffer: Buffer,
+    shadow_map: ShadowMap,
+    texture_bind_group_layout: BindGroupLayout,
+    texture_bind_groups: Vec<Option<BindGroup>>,
+    vertex_buffer: VertexBuffer<Vertex>,
+    index_buffer: IndexBuffer,
+    draw_batches: Vec<DrawBatch>,
+    depth_texture: TextureView,
+}
+
+impl SceneRenderer {
+    /// Renderer for `scene`, drawing into `format` targets of `width`×`height`.
+    /// Texture paths in the scene's materials are relative to `asset_dir`.
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        format: TextureFormat,
+        width: u32,
+        height: u32,
+        scene: &Scene,
+        asset_dir: &Path,
+    ) -> Self {
+        let camera_uniform = CameraUniform::new();
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Buffer"),
+            contents: bytemuck::cast_slice(&[camera_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let camera_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("camera_bind_group_layout"),
+        });
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("camera_bind_group"),
+        });
+
+        // Material table, lights and the sun's shadow map
+        let fragment_entry = |binding, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty,
+            count: None,
+        };
+        let storage = wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only: true },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        };
+        let scene_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                fragment_entry(0, storage),
+                fragment_entry(1, storage),
+                fragment_entry(
+                    2,
+                    wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                ),
+                fragment_entry(
+                    3,
+                    wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Depth,
+                    },
+                ),
+                fragment_entry(4, wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison)),
+            ],
+            label: Some("scene_bind_group_layout"),
+        });
+        let texture_bind_group_layout = Texture::bind_group_layout(device);
+
+        let shader = create_shader_module(device, SCENE_SHADER, "Scene Shader");
+        let textured_shader = create_shader_module(device, TEXTURED_SHADER, "Textured Shader");
+
+        let render_pipeline = scene_pipeline(&shader, &camera_bind_group_layout, &scene_bind_group_layout)
+            .label("Render Pipeline")
+            .build(device, format);
+        let textured_pipeline = scene_pipeline(&textured_shader, &camera_bind_group_layout, &scene_bind_group_layout)
+            .label("Textured Pipeline")
+            .bind_group_layout(&texture_bind_group_layout)
+            .build(device, format);
+
+        let materials = MaterialLibrary::from_scene(scene);
+        let mut mesh = scene_to_mesh(scene);
+        let draw_batches = batch_by_texture(&mut mesh, &materials);
+
+        let material_buffer = create_material_buffer(device, &materials);
+        let light_buffer = create_light_buffer(device, &lights_to_gpu(scene));
+        let mut shadow_map = ShadowMap::new(device, ShadowSettings::default());
+        shadow_map.update(queue, scene, &mesh);
+        let scene_bind_group = create_scene_bind_group(
+            device,
+            &scene_bind_group_layout,
+            &material_buffer,
+            &light_buffer,
+            &shadow_map,
+        );
+        let texture_bind_groups =
+            load_texture_bind_groups(device, queue, &texture_bind_group_layout, &materials, asset_dir);
+
+        let vertex_buffer = VertexBuffer::new(device, &mesh.vertices);
+        let index_buffer = IndexBuffer::new(device, &mesh.indices);
+
+        let depth_texture = create_depth_texture(device, width, height);
+
+        Self {
+            render_pipeline,
+            textured_pipeline,
+            camera_uniform,
+            camera_buffer,
+            camera_bind_group,
+            scene_bind_group_layout,
+            scene_bind_group,
+            material_buffer,
+            light_buffer,
+            shadow_map,
+            texture_bind_group_layout,
+            texture_bind_groups,
+            vertex_buffer,
+            index_buffer,
+            draw_batches,
+            depth_texture,
+        }
+    }
+
+    /// Replace the scene being drawn, keeping the pipelines.
+    pub fn load_scene(&mut self, device: &Device, queue: &Queue, scene: &Scene, asset_dir: &Path) {
+        let materials = MaterialLibrary::from_scene(scene);
+        let mut mesh = scene_to_mesh(scene);
+        self.draw_batches = batch_by_texture(&mut mesh, &materials);
+
+        self.material_buffer = create_material_buffer(device, &materials);
+        self.light_buffer = create_light_buffer(device, &lights_to_gpu(scene));
+        self.shadow_map.update(queue, scene, &mesh);
+        self.update_scene_bind_group(device);
+        self.texture_bind_groups =
+            load_texture_bind_groups(device, queue, &self.texture_bind_group_layout, &materials, asset_dir);
+
+        self.vertex_buffer = VertexBuffer::new(device, &mesh.vertices);
+        self.index_buffer = IndexBuffer::new(device, &mesh.indices);
+    }
+
+    pub fn shadow_settings(&self) -> ShadowSettings {
+        self.shadow_map.settings()
+    }
+
+    pub fn set_shadow_settings(&mut self, device: &Device, queue: &Queue, settings: ShadowSettings) {
+        self.shadow_map.set_settings(device, queue, settings);
+        self.update_scene_bind_group(device);
+    }
+
+    fn update_scene_bind_group(&mut self, device: &Device) {
+        self.scene_bind_group = create_scene_bind_group(
+            device,
+            &self.scene_bind_group_layout,
+            &self.material_buffer,
+            &self.light_buffer,
+            &self.shadow_map,
+        );
+    }
+
+    pub fn update_camera(&mut self, queue: &Queue, camera: &Camera) {
+        self.camera_uniform.update_view_proj(camera);
+        queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[self.camera_uniform]));
+    }
+
+    /// Match the depth texture to a new target size.
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+        self.depth_texture = create_depth_texture(device, width, height);
+    }
+
+    /// Record the shadow pass, then a pass clearing `target` and drawing the
+    /// scene into it.
+    pub fn render(&self, encoder: &mut CommandEncoder, target: &TextureView) {
+        self.shadow_map.render(
+            encoder,
+            &self.vertex_buffer.buffer,
+            &self.index_buffer.buffer,
+            self.index_buffer.count,
+        );
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(CLEAR_COLOR),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.scene_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.buffer.slice(..), wgpu::IndexFormat::Uint32);
+        for batch in &self.draw_batches {
+            // Textures that failed to load draw untextured
+            match batch.texture.and_then(|texture| self.texture_bind_groups[texture].as_ref()) {
+                Some(texture_bind_group) => {
+                    render_pass.set_pipeline(&self.textured_pipeline);
+                    render_pass.set_bind_group(2, texture_bind_group, &[]);
+                }
+                None => render_pass.set_pipeline(&self.render_pipeline),
+            }
+            render_pass.draw_indexed(batch.indices.clone(), 0, 0..1);
+        }
+    }
+}
+
+pub fn create_depth_texture(device: &Device, width: u32, height: u32) -> TextureView {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Depth Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+pub fn create_material_buffer(device: &Device, materials: &MaterialLibrary) -> Buffer {
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Material Buffer"),
+        contents: bytemuck::cast_slice(&materials.to_gpu()),
+        usage: wgpu::BufferUsages::STORAGE,
+    })
+}
+
+/// Storage buffer of `lights`, which must not be empty; scenes without lights
+/// use [`crate::scene::light::default_lights`].
+pub fn create_light_buffer(device: &Device, lights: &[GpuLight]) -> Buffer {
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Light Buffer"),
+        contents: bytemuck::cast_slice(lights),
+        usage: wgpu::BufferUsages::STORAGE,
+    })
+}
+
+/// Bind group of the scene's material table, lights and shadow map.
+pub fn create_scene_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    material_buffer: &Buffer,
+    light_buffer: &Buffer,
+    shadow_map: &ShadowMap,
+) -> BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: material_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: light_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: shadow_map.uniform_buffer().as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(shadow_map.view()),
+            },
+            wgpu::BindGroupEntry {
+                binding: 4,
+                resource: wgpu::BindingResource::Sampler(shadow_map.sampler()),
+            },
+        ],
+        label: Some("scene_bind_group"),
+    })
+}
+
+/// Texture bind group for every texture the materials use, or `None` where the
+/// image couldn't be loaded so those surfaces fall back to their plain colour.
+pub fn load_texture_bind_groups(
+    device: &Device,
+    queue: &Queue,
+    layout: &BindGroupLayout,
+    materials: &MaterialLibrary,
+    asset_dir: &Path,
+) -> Vec<Option<BindGroup>> {
+    materials
+        .textures()
+        .into_iter()
+        .map(|texture| {
+            let path = asset_dir.join(texture);
+            match Texture::load(device, queue, &path) {
+                Ok(texture) => Some(texture.bind_group(device, layout)),
+                Err(e) => {
+                    log::warn!("Failed to load texture {}: {}", path.display(), e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Layout of [`Vertex`] in the vertex buffer, matching `VertexInput` in the scene shaders.
+pub const VERTEX_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+    array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode::Vertex,
+    attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32x2, 3 => Uint32],
+};
+
+/// Pipeline settings shared by the scene shaders: the camera and scene bind
+/// groups, [`VERTEX_LAYOUT`], alpha blending and depth testing.
+fn scene_pipeline<'a>(
+    shader: &'a wgpu::ShaderModule,
+    camera_bind_group_layout: &'a BindGroupLayout,
+    scene_bind_group_layout: &'a BindGroupLayout,
+) -> RenderPipelineBuilder<'a> {
+    RenderPipelineBuilder::new()
+        .shader(shader)
+        .bind_group_layout(camera_bind_group_layout)
+        .bind_group_layout(scene_bind_group_layout)
+        .vertex_buffer(VERTEX_LAYOUT)
+        .blend(wgpu::BlendState::ALPHA_BLENDING)
+        .depth_format(DEPTH_FORMAT)
+}
@@ -0,0 +1,284 @@
+use crate::renderer::pipeline::RenderPipelineBuilder;
+use crate::renderer::scene_renderer::{create_depth_texture, DEPTH_FORMAT, VERTEX_LAYOUT};
+use crate::scene::light::scene_lights;
+use crate::scene::material::GROUND_MATERIAL;
+use crate::scene::primitives::Mesh;
+use crate::scene::{Light, Scene};
+use crate::shaders::SHADOW_SHADER;
+use crate::utils::create_shader_module;
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec3};
+use wgpu::util::DeviceExt;
+use wgpu::{BindGroup, Buffer, CommandEncoder, Device, Queue, RenderPipeline, Sampler, TextureView};
+
+/// `ShadowUniform::light_index` when no light casts shadows.
+const NO_SHADOW_LIGHT: u32 = u32::MAX;
+
+/// Quality settings of the sun's shadow map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowSettings {
+    /// Width and height of the shadow map in texels.
+    pub resolution: u32,
+    /// Metres a surface is moved towards the light before testing whether it
+    /// is in shadow. Too little gives shadow acne, too much detaches shadows
+    /// from their casters.
+    pub bias: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            resolution: 2048,
+            bias: 0.03,
+        }
+    }
+}
+
+impl ShadowSettings {
+    /// These settings within what `device` supports: the resolution between 1
+    /// and the device's largest 2D texture, and the bias not negative.
+    pub fn clamped(self, limits: &wgpu::Limits) -> Self {
+        let resolution = self.resolution.clamp(1, limits.max_texture_dimension_2d);
+        if resolution != self.resolution {
+            log::warn!("Shadow map resolution {} is not supported, using {}", self.resolution, resolution);
+        }
+        Self {
+            resolution,
+            bias: self.bias.max(0.0),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+pub struct ShadowUniform {
+    light_view_proj: [[f32; 4]; 4],
+    /// Index into the scene's lights of the light casting shadows.
+    light_index: u32,
+    /// `ShadowSettings::bias` in shadow map depth units.
+    depth_bias: f32,
+    texel_size: f32,
+    _padding: f32,
+}
+
+/// Frustum of the shadow-casting light.
+#[derive(Debug, Clone, Copy)]
+struct LightAim {
+    light_index: u32,
+    view_proj: Mat4,
+    /// Metres covered by the frustum's depth range.
+    depth_range: f32,
+}
+
+/// Depth map of the scene seen from its primary sun, the first `sun` among
+/// the scene's lights, which the scene shaders sample with PCF.
+///
+/// The light's orthographic frustum is fitted around everything but the
+/// ground plane, so the shadow map's resolution is spent on the building.
+pub struct ShadowMap {
+    settings: ShadowSettings,
+    pipeline: RenderPipeline,
+    aim: Option<LightAim>,
+    uniform_buffer: Buffer,
+    bind_group: BindGroup,
+    view: TextureView,
+    sampler: Sampler,
+}
+
+impl ShadowMap {
+    pub fn new(device: &Device, settings: ShadowSettings) -> Self {
+        let settings = settings.clamped(&device.limits());
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Shadow Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[shadow_uniform(None, settings)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("shadow_bind_group_layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("shadow_bind_group"),
+        });
+
+        let shader = create_shader_module(device, SHADOW_SHADER, "Shadow Shader");
+        let pipeline = RenderPipelineBuilder::new()
+            .label("Shadow Pipeline")
+            .vertex_shader(&shader)
+            .bind_group_layout(&bind_group_layout)
+            .vertex_buffer(VERTEX_LAYOUT)
+            .depth_format(DEPTH_FORMAT)
+            // Single-sided planes such as upper floors cast shadows too
+            .cull_mode(None)
+            .depth_bias(wgpu::DepthBiasState {
+                constant: 2,
+                slope_scale: 2.0,
+                clamp: 0.0,
+            })
+            .build_depth_only(device);
+
+        let view = create_depth_texture(device, settings.resolution, settings.resolution);
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        Self {
+            settings,
+            pipeline,
+            aim: None,
+            uniform_buffer,
+            bind_group,
+            view,
+            sampler,
+        }
+    }
+
+    pub fn settings(&self) -> ShadowSettings {
+        self.settings
+    }
+
+    /// Aim the light's frustum at `mesh`, the mesh of `scene`, from the
+    /// scene's primary sun.
+    pub fn update(&mut self, queue: &Queue, scene: &Scene, mesh: &Mesh) {
+        let sun = scene_lights(scene).iter().enumerate().find_map(|(index, light)| match light {
+            Light::Sun(sun) => Some((index, sun.direction)),
+            _ => None,
+        });
+
+        self.aim = match (sun, caster_bounds(mesh)) {
+            (Some((index, direction)), Some((min, max))) => {
+                let (view_proj, depth_range) = light_view_proj(direction, min, max);
+                Some(LightAim {
+                    light_index: index as u32,
+                    view_proj,
+                    depth_range,
+                })
+            }
+            _ => None,
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[shadow_uniform(self.aim, self.settings)]));
+    }
+
+    /// Change the resolution and bias, clamped to what `device` supports. The
+    /// shadow map texture is replaced, so bind groups using [`Self::view`]
+    /// must be recreated.
+    pub fn set_settings(&mut self, device: &Device, queue: &Queue, settings: ShadowSettings) {
+        let settings = settings.clamped(&device.limits());
+        if settings.resolution != self.settings.resolution {
+            self.view = create_depth_texture(device, settings.resolution, settings.resolution);
+        }
+        self.settings = settings;
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[shadow_uniform(self.aim, self.settings)]));
+    }
+
+    pub fn uniform_buffer(&self) -> &Buffer {
+        &self.uniform_buffer
+    }
+
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+
+    pub fn sampler(&self) -> &Sampler {
+        &self.sampler
+    }
+
+    /// Record the pass rendering the mesh in `vertex_buffer`/`index_buffer`
+    /// into the shadow map. Does nothing when no light casts shadows.
+    pub fn render(&self, encoder: &mut CommandEncoder, vertex_buffer: &Buffer, index_buffer: &Buffer, index_count: u32) {
+        if self.aim.is_none() {
+            return;
+        }
+
+        let mut shadow_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Shadow Pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        shadow_pass.set_pipeline(&self.pipeline);
+        shadow_pass.set_bind_group(0, &self.bind_group, &[]);
+        shadow_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        shadow_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        shadow_pass.draw_indexed(0..index_count, 0, 0..1);
+    }
+}
+
+fn shadow_uniform(aim: Option<LightAim>, settings: ShadowSettings) -> ShadowUniform {
+    let texel_size = 1.0 / settings.resolution as f32;
+    match aim {
+        Some(aim) => ShadowUniform {
+            light_view_proj: aim.view_proj.to_cols_array_2d(),
+            light_index: aim.light_index,
+            depth_bias: settings.bias / aim.depth_range,
+            texel_size,
+            _padding: 0.0,
+        },
+        None => ShadowUniform {
+            light_view_proj: Mat4::IDENTITY.to_cols_array_2d(),
+            light_index: NO_SHADOW_LIGHT,
+            depth_bias: 0.0,
+            texel_size,
+            _padding: 0.0,
+        },
+    }
+}
+
+/// Bounding box of the vertices that can cast shadows: all but the ground plane.
+fn caster_bounds(mesh: &Mesh) -> Option<(Vec3, Vec3)> {
+    mesh.vertices
+        .iter()
+        .filter(|vertex| vertex.material != GROUND_MATERIAL)
+        .map(|vertex| Vec3::from(vertex.position))
+        .fold(None, |bounds, position| match bounds {
+            None => Some((position, position)),
+            Some((min, max)) => Some((min.min(position), max.max(position))),
+        })
+}
+
+/// Orthographic view-projection looking along `direction` that contains the
+/// box `min`..`max`, and the depth in metres its depth range covers.
+fn light_view_proj(direction: Vec3, min: Vec3, max: Vec3) -> (Mat4, f32) {
+    let direction = direction.normalize_or(Vec3::NEG_Y);
+    let center = (min + max) * 0.5;
+    let radius = ((max - min).length() * 0.5).max(0.5);
+
+    let up = if direction.y.abs() > 0.99 { Vec3::Z } else { Vec3::Y };
+    let eye = center - direction * radius * 2.0;
+    let view = Mat4::look_at_rh(eye, center, up);
+    let depth_range = radius * 4.0;
+    let proj = Mat4::orthographic_rh(-radius, radius, -radius, radius, 0.0, depth_range);
+    (proj * view, depth_range)
+}
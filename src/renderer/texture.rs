@@ -0,0 +1,127 @@
+use std::path::Path;
+use wgpu::{BindGroup, BindGroupLayout, Device, Queue, Sampler, TextureView};
+
+/// Colour texture with a full mip chain and a repeating sampler, ready to tile
+/// over surfaces whose texture coordinates are in metres.
+pub struct Texture {
+    pub texture: wgpu::Texture,
+    pub view: TextureView,
+    pub sampler: Sampler,
+}
+
+impl Texture {
+    pub fn load(device: &Device, queue: &Queue, path: &Path) -> Result<Self, image::ImageError> {
+        let image = image::open(path)?.to_rgba8();
+        Ok(Self::from_image(device, queue, &image, &path.display().to_string()))
+    }
+
+    pub fn from_image(device: &Device, queue: &Queue, image: &image::RgbaImage, label: &str) -> Self {
+        let (width, height) = image.dimensions();
+        let mip_level_count = width.max(height).max(1).ilog2() + 1;
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        // Mip levels are downsampled on the CPU; scenes only load a handful of textures
+        let mut level = image.clone();
+        for mip_level in 0..mip_level_count {
+            if mip_level > 0 {
+                let (w, h) = level.dimensions();
+                level = image::imageops::resize(
+                    &level,
+                    (w / 2).max(1),
+                    (h / 2).max(1),
+                    image::imageops::FilterType::Triangle,
+                );
+            }
+            let (w, h) = level.dimensions();
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &level,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * w),
+                    rows_per_image: Some(h),
+                },
+                wgpu::Extent3d {
+                    width: w,
+                    height: h,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some(label),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            address_mode_w: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self { texture, view, sampler }
+    }
+
+    /// Layout of the `t_diffuse`/`s_diffuse` bind group in `textured.wgsl`.
+    pub fn bind_group_layout(device: &Device) -> BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("texture_bind_group_layout"),
+        })
+    }
+
+    pub fn bind_group(&self, device: &Device, layout: &BindGroupLayout) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&self.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+            label: Some("texture_bind_group"),
+        })
+    }
+}
@@ -0,0 +1,121 @@
+use super::viewpoints::is_viewpoints_file;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory the example scenes are in, relative to the working directory.
+pub const EXAMPLES_DIR: &str = "examples";
+
+/// Number a scene file's name starts with, such as 7 for `7_multi_level.json`.
+pub fn scene_number(path: &Path) -> Option<u32> {
+    let stem = path.file_stem()?.to_str()?;
+    let digits = stem.find(|c: char| !c.is_ascii_digit()).unwrap_or(stem.len());
+    stem[..digits].parse().ok()
+}
+
+/// Name of a scene file without its number, such as `multi_level` for
+/// `7_multi_level.json`.
+pub fn scene_name(path: &Path) -> &str {
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("");
+    match scene_number(path) {
+        Some(_) => stem.trim_start_matches(|c: char| c.is_ascii_digit()).trim_start_matches('_'),
+        None => stem,
+    }
+}
+
+/// The `.json` scene files in `dir`, in order of the numbers their names
+/// start with, then by name. Files without a number come last, and files of
+/// saved viewpoints are left out.
+pub fn scene_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|extension| extension == "json"))
+        .filter(|path| !is_viewpoints_file(path))
+        .collect();
+    files.sort_by(|a, b| {
+        let key = |path: &Path| (scene_number(path).unwrap_or(u32::MAX), path.file_name().map(|name| name.to_os_string()));
+        key(a).cmp(&key(b))
+    });
+    Ok(files)
+}
+
+/// The file in `files` that `name` refers to: by its number (`7`), its file
+/// stem (`7_multi_level`) or its name without the number (`multi_level`).
+pub fn find_scene<'a>(files: &'a [PathBuf], name: &str) -> Option<&'a PathBuf> {
+    if let Ok(number) = name.parse::<u32>() {
+        return files.iter().find(|file| scene_number(file) == Some(number));
+    }
+    files
+        .iter()
+        .find(|file| file.file_stem().is_some_and(|stem| stem == name))
+        .or_else(|| files.iter().find(|file| scene_name(file) == name))
+}
+
+/// The scene files of a directory, in the order of [`scene_files`], for
+/// finding scenes by name and paging through them.
+#[derive(Debug, Clone)]
+pub struct SceneCatalog {
+    dir: PathBuf,
+    files: Vec<PathBuf>,
+}
+
+impl SceneCatalog {
+    /// Catalog of the scene files in `dir`.
+    pub fn scan(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        let files = scene_files(&dir)?;
+        Ok(Self { dir, files })
+    }
+
+    /// Catalog of `dir` without any scenes, for when it can't be read.
+    pub fn empty(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            files: Vec::new(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The scene `name` refers to, as in [`find_scene`].
+    pub fn find(&self, name: &str) -> Option<&PathBuf> {
+        find_scene(&self.files, name)
+    }
+
+    /// Index of the scene file at `path`, however the path is spelled.
+    pub fn position(&self, path: &Path) -> Option<usize> {
+        let canonical = path.canonicalize().ok();
+        self.files.iter().position(|file| {
+            file == path || canonical.is_some() && file.canonicalize().ok() == canonical
+        })
+    }
+
+    /// The scene `offset` places after the one at `current`, wrapping around
+    /// at either end. From a scene outside the catalog, stepping forward
+    /// starts at the first scene and stepping back at the last.
+    pub fn step(&self, current: &Path, offset: isize) -> Option<&PathBuf> {
+        if self.files.is_empty() {
+            return None;
+        }
+        let len = self.files.len() as isize;
+        let index = match self.position(current) {
+            Some(index) => (index as isize + offset).rem_euclid(len),
+            None if offset > 0 => (offset - 1).rem_euclid(len),
+            None => offset.rem_euclid(len),
+        };
+        self.files.get(index as usize)
+    }
+}
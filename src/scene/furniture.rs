@@ -0,0 +1,240 @@
+use super::{Furniture, FurnitureType};
+use super::primitives::{Mesh, create_box, create_cylinder};
+use glam::{Mat4, Quat, Vec3};
+
+/// Build a piece of furniture at its position, turned by its `rotation`.
+///
+/// Every piece is modelled in a local frame standing on `y = 0`, centred on
+/// the origin, with its width along X and its front facing +Z (backrests,
+/// headboards and the like sit on the -Z side). `rotation` is a yaw in
+/// degrees, counter-clockwise when seen from above.
+pub fn furniture_to_mesh(furniture: &Furniture) -> Mesh {
+    let mut mesh = match &furniture.furniture_type {
+        FurnitureType::Table { width, depth, height } => table(*width, *depth, *height),
+        FurnitureType::Chair { seat_height } => chair(*seat_height),
+        FurnitureType::Sofa { width, depth } => sofa(*width, *depth),
+        FurnitureType::Bed { width, length } => bed(*width, *length),
+        FurnitureType::Desk { width, depth } => desk(*width, *depth),
+        FurnitureType::Cabinet { width, depth, height } => cabinet(*width, *depth, *height),
+        FurnitureType::Shelf { width, depth, height, shelves } => shelf(*width, *depth, *height, *shelves),
+        FurnitureType::Wardrobe { width, depth, height } => wardrobe(*width, *depth, *height),
+        FurnitureType::Toilet => toilet(),
+        FurnitureType::Sink { width, depth } => sink(*width, *depth),
+        FurnitureType::Bathtub { width, length } => bathtub(*width, *length),
+        FurnitureType::KitchenCounter { width, depth, height } => kitchen_counter(*width, *depth, *height),
+        FurnitureType::Fridge { width, depth, height } => fridge(*width, *depth, *height),
+    };
+    let rotation = Quat::from_rotation_y(furniture.rotation.to_radians());
+    mesh.transform(Mat4::from_rotation_translation(rotation, furniture.position));
+    mesh
+}
+
+/// Height of a standard desk or dining table top.
+const DESK_HEIGHT: f32 = 0.75;
+const LEG_SIZE: f32 = 0.05;
+
+/// Add an axis-aligned box spanning `min` to `max`.
+fn add_block(mesh: &mut Mesh, min: Vec3, max: Vec3) {
+    mesh.append(&create_box((min + max) * 0.5, max - min));
+}
+
+/// Four square legs of `height` centred at `(±x, ±z)`.
+fn add_legs(mesh: &mut Mesh, x: f32, z: f32, height: f32) {
+    for (sx, sz) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+        let centre = Vec3::new(sx * x, height * 0.5, sz * z);
+        mesh.append(&create_box(centre, Vec3::new(LEG_SIZE, height, LEG_SIZE)));
+    }
+}
+
+fn table(width: f32, depth: f32, height: f32) -> Mesh {
+    let mut mesh = Mesh::new();
+    // Table top
+    add_block(&mut mesh, Vec3::new(-width * 0.5, height - 0.1, -depth * 0.5), Vec3::new(width * 0.5, height, depth * 0.5));
+    add_legs(&mut mesh, width * 0.4, depth * 0.4, height - 0.1);
+    mesh
+}
+
+fn chair(seat_height: f32) -> Mesh {
+    let (size, seat) = (0.45, 0.05);
+    let half = size * 0.5;
+    let mut mesh = Mesh::new();
+    add_block(&mut mesh, Vec3::new(-half, seat_height - seat, -half), Vec3::new(half, seat_height, half));
+    add_legs(&mut mesh, half - LEG_SIZE * 0.5, half - LEG_SIZE * 0.5, seat_height - seat);
+    // Backrest
+    add_block(&mut mesh, Vec3::new(-half, seat_height, -half), Vec3::new(half, seat_height + 0.45, -half + 0.04));
+    mesh
+}
+
+fn sofa(width: f32, depth: f32) -> Mesh {
+    let (seat_height, back_height, arm_height) = (0.42, 0.85, 0.6);
+    let (arm, back) = (0.15, 0.2);
+    let (hw, hd) = (width * 0.5, depth * 0.5);
+    let mut mesh = Mesh::new();
+    // Base with seat cushions on top
+    add_block(&mut mesh, Vec3::new(-hw + arm, 0.05, -hd + back), Vec3::new(hw - arm, seat_height - 0.12, hd));
+    let cushions = ((width - 2.0 * arm) / 0.7).round().max(1.0) as u32;
+    let cushion_width = (width - 2.0 * arm) / cushions as f32;
+    for i in 0..cushions {
+        let x0 = -hw + arm + i as f32 * cushion_width;
+        add_block(
+            &mut mesh,
+            Vec3::new(x0 + 0.01, seat_height - 0.12, -hd + back),
+            Vec3::new(x0 + cushion_width - 0.01, seat_height, hd - 0.02),
+        );
+    }
+    // Backrest and armrests
+    add_block(&mut mesh, Vec3::new(-hw, 0.05, -hd), Vec3::new(hw, back_height, -hd + back));
+    add_block(&mut mesh, Vec3::new(-hw, 0.05, -hd + back), Vec3::new(-hw + arm, arm_height, hd));
+    add_block(&mut mesh, Vec3::new(hw - arm, 0.05, -hd + back), Vec3::new(hw, arm_height, hd));
+    add_legs(&mut mesh, hw - 0.05, hd - 0.05, 0.05);
+    mesh
+}
+
+fn bed(width: f32, length: f32) -> Mesh {
+    let (frame_height, mattress) = (0.3, 0.22);
+    let (hw, hl) = (width * 0.5, length * 0.5);
+    let mut mesh = Mesh::new();
+    add_block(&mut mesh, Vec3::new(-hw, 0.05, -hl), Vec3::new(hw, frame_height, hl));
+    add_block(&mut mesh, Vec3::new(-hw + 0.03, frame_height, -hl + 0.05), Vec3::new(hw - 0.03, frame_height + mattress, hl - 0.03));
+    add_legs(&mut mesh, hw - 0.05, hl - 0.05, 0.05);
+    // Headboard at the -Z end
+    add_block(&mut mesh, Vec3::new(-hw, 0.05, -hl - 0.06), Vec3::new(hw, 1.0, -hl));
+    // One pillow per 0.8 m of width
+    let pillows = (width / 0.8).round().max(1.0) as u32;
+    let pillow_width = (width - 0.1) / pillows as f32;
+    let top = frame_height + mattress;
+    for i in 0..pillows {
+        let x0 = -hw + 0.05 + i as f32 * pillow_width;
+        add_block(&mut mesh, Vec3::new(x0 + 0.03, top, -hl + 0.1), Vec3::new(x0 + pillow_width - 0.03, top + 0.12, -hl + 0.5));
+    }
+    mesh
+}
+
+fn desk(width: f32, depth: f32) -> Mesh {
+    let (hw, hd) = (width * 0.5, depth * 0.5);
+    let top = 0.03;
+    let mut mesh = Mesh::new();
+    add_block(&mut mesh, Vec3::new(-hw, DESK_HEIGHT - top, -hd), Vec3::new(hw, DESK_HEIGHT, hd));
+    // Panel leg on the left, drawer pedestal on the right
+    add_block(&mut mesh, Vec3::new(-hw, 0.0, -hd), Vec3::new(-hw + 0.03, DESK_HEIGHT - top, hd));
+    let pedestal = 0.4_f32.min(width * 0.4);
+    add_block(&mut mesh, Vec3::new(hw - pedestal, 0.0, -hd), Vec3::new(hw, DESK_HEIGHT - top, hd - 0.02));
+    // Modesty panel along the back
+    add_block(&mut mesh, Vec3::new(-hw + 0.03, DESK_HEIGHT - 0.4, -hd), Vec3::new(hw - pedestal, DESK_HEIGHT - top, -hd + 0.02));
+    let drawer = (DESK_HEIGHT - top) / 3.0;
+    for i in 0..3 {
+        let y = i as f32 * drawer + drawer * 0.5;
+        add_block(&mut mesh, Vec3::new(hw - pedestal * 0.5 - 0.06, y - 0.01, hd - 0.02), Vec3::new(hw - pedestal * 0.5 + 0.06, y + 0.01, hd));
+    }
+    mesh
+}
+
+/// Box carcass on a recessed plinth with a handle per door.
+fn carcass(mesh: &mut Mesh, width: f32, depth: f32, height: f32, doors: u32) {
+    let (hw, hd) = (width * 0.5, depth * 0.5);
+    let plinth = 0.08;
+    add_block(mesh, Vec3::new(-hw + 0.03, 0.0, -hd), Vec3::new(hw - 0.03, plinth, hd - 0.05));
+    add_block(mesh, Vec3::new(-hw, plinth, -hd), Vec3::new(hw, height, hd));
+    let door_width = width / doors as f32;
+    let handle_y = plinth + (height - plinth) * 0.5;
+    for i in 0..doors {
+        // Handles sit next to the meeting edge of each pair of doors
+        let left_edge = -hw + i as f32 * door_width;
+        let x = if i % 2 == 0 { left_edge + door_width - 0.06 } else { left_edge + 0.06 };
+        add_block(mesh, Vec3::new(x - 0.01, handle_y - 0.08, hd), Vec3::new(x + 0.01, handle_y + 0.08, hd + 0.025));
+    }
+}
+
+fn cabinet(width: f32, depth: f32, height: f32) -> Mesh {
+    let mut mesh = Mesh::new();
+    carcass(&mut mesh, width, depth, height, if width > 0.6 { 2 } else { 1 });
+    mesh
+}
+
+fn wardrobe(width: f32, depth: f32, height: f32) -> Mesh {
+    let mut mesh = Mesh::new();
+    let doors = ((width / 0.5).round() as u32).max(2);
+    carcass(&mut mesh, width, depth, height, doors);
+    // Cornice
+    add_block(&mut mesh, Vec3::new(-width * 0.5 - 0.02, height, -depth * 0.5), Vec3::new(width * 0.5 + 0.02, height + 0.04, depth * 0.5 + 0.02));
+    mesh
+}
+
+fn shelf(width: f32, depth: f32, height: f32, shelves: u32) -> Mesh {
+    let (hw, hd) = (width * 0.5, depth * 0.5);
+    let board = 0.02;
+    let mut mesh = Mesh::new();
+    // Sides and back panel
+    add_block(&mut mesh, Vec3::new(-hw, 0.0, -hd), Vec3::new(-hw + board, height, hd));
+    add_block(&mut mesh, Vec3::new(hw - board, 0.0, -hd), Vec3::new(hw, height, hd));
+    add_block(&mut mesh, Vec3::new(-hw + board, 0.0, -hd), Vec3::new(hw - board, height, -hd + 0.01));
+    // Bottom, top and the evenly spaced boards in between
+    let spacing = (height - board) / (shelves + 1) as f32;
+    for i in 0..=shelves + 1 {
+        let y = (i as f32 * spacing).min(height - board);
+        add_block(&mut mesh, Vec3::new(-hw + board, y, -hd + 0.01), Vec3::new(hw - board, y + board, hd));
+    }
+    mesh
+}
+
+fn toilet() -> Mesh {
+    let mut mesh = Mesh::new();
+    // Pedestal and round bowl, cistern against the wall at -Z
+    add_block(&mut mesh, Vec3::new(-0.12, 0.0, -0.2), Vec3::new(0.12, 0.3, 0.12));
+    mesh.append(&create_cylinder(Vec3::new(0.0, 0.35, 0.05), 0.19, 0.1, 20));
+    add_block(&mut mesh, Vec3::new(-0.19, 0.4, -0.12), Vec3::new(0.19, 0.42, 0.24));
+    add_block(&mut mesh, Vec3::new(-0.2, 0.3, -0.35), Vec3::new(0.2, 0.8, -0.17));
+    mesh
+}
+
+fn sink(width: f32, depth: f32) -> Mesh {
+    let (hw, hd) = (width * 0.5, depth * 0.5);
+    let rim = 0.85;
+    let mut mesh = Mesh::new();
+    // Vanity unit with a basin rim and a tap at the back
+    carcass(&mut mesh, width, depth, rim - 0.12, if width > 0.8 { 2 } else { 1 });
+    add_block(&mut mesh, Vec3::new(-hw, rim - 0.12, -hd), Vec3::new(hw, rim, -hd + 0.1));
+    add_block(&mut mesh, Vec3::new(-hw, rim - 0.12, hd - 0.06), Vec3::new(hw, rim, hd));
+    add_block(&mut mesh, Vec3::new(-hw, rim - 0.12, -hd + 0.1), Vec3::new(-hw + 0.08, rim, hd - 0.06));
+    add_block(&mut mesh, Vec3::new(hw - 0.08, rim - 0.12, -hd + 0.1), Vec3::new(hw, rim, hd - 0.06));
+    mesh.append(&create_cylinder(Vec3::new(0.0, rim + 0.1, -hd + 0.05), 0.015, 0.2, 8));
+    mesh
+}
+
+fn bathtub(width: f32, length: f32) -> Mesh {
+    let (hw, hl) = (width * 0.5, length * 0.5);
+    let (height, wall) = (0.55, 0.08);
+    let mut mesh = Mesh::new();
+    // Floor of the tub and the four rims around the hollow
+    add_block(&mut mesh, Vec3::new(-hw, 0.0, -hl), Vec3::new(hw, 0.15, hl));
+    add_block(&mut mesh, Vec3::new(-hw, 0.15, -hl), Vec3::new(-hw + wall, height, hl));
+    add_block(&mut mesh, Vec3::new(hw - wall, 0.15, -hl), Vec3::new(hw, height, hl));
+    add_block(&mut mesh, Vec3::new(-hw + wall, 0.15, -hl), Vec3::new(hw - wall, height, -hl + wall));
+    add_block(&mut mesh, Vec3::new(-hw + wall, 0.15, hl - wall), Vec3::new(hw - wall, height, hl));
+    mesh
+}
+
+fn kitchen_counter(width: f32, depth: f32, height: f32) -> Mesh {
+    let (hw, hd) = (width * 0.5, depth * 0.5);
+    let worktop = 0.04;
+    let mut mesh = Mesh::new();
+    let doors = ((width / 0.6).round() as u32).max(1);
+    carcass(&mut mesh, width - 0.02, depth - 0.03, height - worktop, doors);
+    add_block(&mut mesh, Vec3::new(-hw, height - worktop, -hd), Vec3::new(hw, height, hd + 0.02));
+    mesh
+}
+
+fn fridge(width: f32, depth: f32, height: f32) -> Mesh {
+    let (hw, hd) = (width * 0.5, depth * 0.5);
+    let mut mesh = Mesh::new();
+    add_block(&mut mesh, Vec3::new(-hw, 0.0, -hd), Vec3::new(hw, height, hd));
+    // Freezer door split and long handles on the right, kept clear of the
+    // floor and the top; short fridges get shorter handles or none
+    let split = height * 0.65;
+    add_block(&mut mesh, Vec3::new(-hw, split - 0.005, hd), Vec3::new(hw, split + 0.005, hd + 0.005));
+    let handles = [((split - 0.6).max(0.05), split - 0.1), (split + 0.1, (split + 0.5).min(height - 0.05))];
+    for (y0, y1) in handles.into_iter().filter(|(y0, y1)| y1 > y0) {
+        add_block(&mut mesh, Vec3::new(hw - 0.08, y0, hd), Vec3::new(hw - 0.05, y1, hd + 0.04));
+    }
+    mesh
+}
@@ -0,0 +1,30 @@
+use super::{Element, Scene};
+
+/// Elements whose bases are this close belong to the same level.
+const LEVEL_TOLERANCE: f32 = 0.1;
+
+/// Base elevations of the levels of `scene`, lowest first: one for every
+/// height rooms and walls stand at.
+pub fn levels(scene: &Scene) -> Vec<f32> {
+    let mut levels: Vec<f32> = scene
+        .elements
+        .iter()
+        .filter(|element| matches!(element, Element::Room(_) | Element::Wall(_)))
+        .map(Element::base_elevation)
+        .collect();
+    levels.sort_by(f32::total_cmp);
+    levels.dedup_by(|higher, lower| *higher - *lower < LEVEL_TOLERANCE);
+    if levels.is_empty() {
+        levels.push(0.0);
+    }
+    levels
+}
+
+/// The highest of `levels` at or below `elevation`, or the lowest level.
+pub fn level_of(levels: &[f32], elevation: f32) -> f32 {
+    levels
+        .iter()
+        .copied()
+        .rfind(|&level| level <= elevation + LEVEL_TOLERANCE)
+        .unwrap_or(levels[0])
+}
@@ -0,0 +1,110 @@
+use super::{AmbientLight, Light, Scene, SunLight};
+use bytemuck::{Pod, Zeroable};
+use glam::Vec3;
+
+/// `GpuLight::kind` values, matching the `LIGHT_*` constants of the scene shaders.
+pub const LIGHT_AMBIENT: u32 = 0;
+pub const LIGHT_SUN: u32 = 1;
+pub const LIGHT_POINT: u32 = 2;
+pub const LIGHT_SPOT: u32 = 3;
+
+/// Lighting of scenes that don't define any lights: soft ambient light and a
+/// sun from behind the typical camera position, as the shader used before
+/// scenes could set their own.
+pub fn default_lights() -> Vec<Light> {
+    vec![
+        Light::Ambient(AmbientLight {
+            color: Vec3::ONE,
+            intensity: 0.3,
+        }),
+        Light::Sun(SunLight {
+            direction: Vec3::new(-0.5, -1.0, 0.5),
+            color: Vec3::ONE,
+            intensity: 0.7,
+        }),
+    ]
+}
+
+/// The lights `scene` is drawn with: its own, or the defaults if it has none.
+///
+/// When the scene's site has a `date_time`, the first sun is moved to where
+/// the real sun is then, and turned off while it is below the horizon.
+pub fn scene_lights(scene: &Scene) -> Vec<Light> {
+    let mut lights = if scene.lights.is_empty() {
+        default_lights()
+    } else {
+        scene.lights.clone()
+    };
+
+    if let Some((site, date_time)) = scene.site.as_ref().and_then(|site| Some((site, site.date_time?))) {
+        let sun = lights.iter_mut().find_map(|light| match light {
+            Light::Sun(sun) => Some(sun),
+            _ => None,
+        });
+        if let Some(sun) = sun {
+            sun.direction = site.sun_direction(date_time);
+            if !site.solar_position(date_time).is_above_horizon() {
+                sun.intensity = 0.0;
+            }
+        }
+    }
+    lights
+}
+
+/// The scene's lights in the layout of the shader's light storage buffer.
+pub fn lights_to_gpu(scene: &Scene) -> Vec<GpuLight> {
+    scene_lights(scene).iter().map(GpuLight::new).collect()
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+pub struct GpuLight {
+    pub position: [f32; 3],
+    pub kind: u32,
+    /// Normalised direction the light travels in.
+    pub direction: [f32; 3],
+    pub range: f32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    /// Cosines of the spot cone's inner and outer half-angles.
+    pub cos_inner: f32,
+    pub cos_outer: f32,
+    pub _padding: [f32; 2],
+}
+
+impl GpuLight {
+    pub fn new(light: &Light) -> Self {
+        let light_of_kind = |kind: u32, color: Vec3, intensity: f32| Self {
+            position: [0.0; 3],
+            kind,
+            direction: [0.0, -1.0, 0.0],
+            range: 0.0,
+            color: color.to_array(),
+            intensity,
+            cos_inner: 1.0,
+            cos_outer: 1.0,
+            _padding: [0.0; 2],
+        };
+
+        match light {
+            Light::Ambient(ambient) => light_of_kind(LIGHT_AMBIENT, ambient.color, ambient.intensity),
+            Light::Sun(sun) => Self {
+                direction: sun.direction.normalize_or(Vec3::NEG_Y).to_array(),
+                ..light_of_kind(LIGHT_SUN, sun.color, sun.intensity)
+            },
+            Light::Point(point) => Self {
+                position: point.position.to_array(),
+                range: point.range,
+                ..light_of_kind(LIGHT_POINT, point.color, point.intensity)
+            },
+            Light::Spot(spot) => Self {
+                position: spot.position.to_array(),
+                direction: spot.direction.normalize_or(Vec3::NEG_Y).to_array(),
+                range: spot.range,
+                cos_inner: spot.inner_angle.to_radians().cos(),
+                cos_outer: spot.outer_angle.to_radians().cos(),
+                ..light_of_kind(LIGHT_SPOT, spot.color, spot.intensity)
+            },
+        }
+    }
+}
@@ -1,73 +1,224 @@
-use super::{Scene, Element, Room, WallSide, WallFeature};
+use super::{Scene, Element, Room};
 use super::primitives::{Mesh, create_box, create_plane};
+use super::furniture::furniture_to_mesh;
+use super::material::{MaterialLibrary, GROUND_MATERIAL};
+use super::wall_graph::{WallGraph, element_walls, joins_scene_graph};
+use super::migration::{migrate, MigrationError, CURRENT_FORMAT_VERSION};
+use super::validation::{validate_scene, ValidationError};
 use glam::Vec3;
+use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-pub fn load_scene_from_file(path: &Path) -> Result<Scene, Box<dyn std::error::Error>> {
-    let contents = fs::read_to_string(path)?;
-    let scene: Scene = serde_json::from_str(&contents)?;
-    Ok(scene)
+#[derive(Debug)]
+pub enum SceneError {
+    Io { path: PathBuf, source: std::io::Error },
+    /// The file isn't valid JSON or doesn't match the scene format.
+    Parse { path: PathBuf, source: serde_json::Error },
+    /// The file's `format_version` can't be upgraded to the current one.
+    Migration { path: PathBuf, source: MigrationError },
+    /// The scene parsed but describes something that can't be built.
+    Invalid { path: PathBuf, errors: Vec<ValidationError> },
 }
 
-pub fn scene_to_mesh(scene: &Scene) -> Mesh {
-    let mut combined_mesh = Mesh::new();
-    let mut vertex_offset = 0u16;
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io { path, source } => write!(f, "failed to read {}: {}", path.display(), source),
+            SceneError::Parse { path, source } => write!(f, "failed to parse {}: {}", path.display(), source),
+            SceneError::Migration { path, source } => write!(f, "failed to load {}: {}", path.display(), source),
+            SceneError::Invalid { path, errors } => {
+                write!(f, "{} has {} problem(s):", path.display(), errors.len())?;
+                for error in errors {
+                    write!(f, "\n  {}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
 
-    // First, add a large base floor plane
-    let base_floor_size = 50.0; // 50x50 meter floor
-    let base_floor = create_plane(
-        Vec3::new(0.0, -0.01, 0.0), // Slightly below ground level to avoid z-fighting
-        glam::Vec2::new(base_floor_size, base_floor_size),
-        Vec3::Y,
-    );
-    
-    // Add base floor vertices
-    for vertex in &base_floor.vertices {
-        combined_mesh.vertices.push(*vertex);
+impl std::error::Error for SceneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneError::Io { source, .. } => Some(source),
+            SceneError::Parse { source, .. } => Some(source),
+            SceneError::Migration { source, .. } => Some(source),
+            SceneError::Invalid { .. } => None,
+        }
     }
-    for &index in &base_floor.indices {
-        combined_mesh.indices.push(index);
+}
+
+/// Read and parse the scene at `path`, upgrading it from older format
+/// versions, and reject scenes that fail [`validate_scene`].
+pub fn load_scene_from_file(path: &Path) -> Result<Scene, SceneError> {
+    let (scene, _) = read_scene_file(path)?;
+
+    let errors = validate_scene(&scene);
+    if !errors.is_empty() {
+        return Err(SceneError::Invalid {
+            path: path.to_path_buf(),
+            errors,
+        });
     }
-    vertex_offset += base_floor.vertices.len() as u16;
+    Ok(scene)
+}
 
-    // Then add all scene elements
-    for element in &scene.elements {
-        let element_mesh = element_to_mesh(element);
-        
-        // Add vertices with offset
-        for vertex in &element_mesh.vertices {
-            combined_mesh.vertices.push(*vertex);
+/// Rewrite the scene at `path` in the current format, returning the version
+/// it was upgraded from, or `None` if it already was current.
+///
+/// Scenes are upgraded even if they fail validation, so old files can be
+/// fixed up in the current format.
+pub fn upgrade_scene_file(path: &Path) -> Result<Option<u32>, SceneError> {
+    let (scene, version) = read_scene_file(path)?;
+    if version == CURRENT_FORMAT_VERSION {
+        return Ok(None);
+    }
+    save_scene_to_file(path, &scene)?;
+    Ok(Some(version))
+}
+
+pub fn save_scene_to_file(path: &Path, scene: &Scene) -> Result<(), SceneError> {
+    fs::write(path, scene_to_json(scene)).map_err(|source| SceneError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// `scene` as pretty-printed JSON in the current format, with vectors and
+/// other arrays of numbers kept on one line as in hand-written scenes.
+pub fn scene_to_json(scene: &Scene) -> String {
+    let scene = Scene {
+        format_version: CURRENT_FORMAT_VERSION,
+        ..scene.clone()
+    };
+    let json = serde_json::to_string_pretty(&scene).expect("scenes serialize to JSON");
+    let mut output = inline_scalar_arrays(&json);
+    output.push('\n');
+    output
+}
+
+/// Read the scene at `path` and the format version it was written in.
+fn read_scene_file(path: &Path) -> Result<(Scene, u32), SceneError> {
+    let contents = fs::read_to_string(path).map_err(|source| SceneError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let parse_error = |source| SceneError::Parse {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let mut document: serde_json::Value = serde_json::from_str(&contents).map_err(parse_error)?;
+    let version = migrate(&mut document).map_err(|source| SceneError::Migration {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    // Current documents are parsed from the text again so errors keep their line and column
+    let scene = if version == CURRENT_FORMAT_VERSION {
+        serde_json::from_str(&contents)
+    } else {
+        serde_json::from_value(document)
+    };
+    Ok((scene.map_err(parse_error)?, version))
+}
+
+/// Put arrays holding no strings, objects or arrays of `json` on one line.
+pub(crate) fn inline_scalar_arrays(json: &str) -> String {
+    let mut output = String::with_capacity(json.len());
+    let mut chars = json.char_indices();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    while let Some((index, c)) = chars.next() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            output.push(c);
+            continue;
         }
 
-        // Add indices with offset
-        for &index in &element_mesh.indices {
-            combined_mesh.indices.push(index + vertex_offset);
+        match c {
+            '"' => in_string = true,
+            '[' => {
+                let rest = &json[index + 1..];
+                if let Some(end) = rest.find(']') {
+                    let items = &rest[..end];
+                    if !items.contains(['[', '{', '"']) {
+                        let items: Vec<&str> = items.split(',').map(str::trim).collect();
+                        output.push('[');
+                        output.push_str(&items.join(", "));
+                        output.push(']');
+                        // Skip past the closing bracket
+                        chars.nth(rest[..=end].chars().count() - 1);
+                        continue;
+                    }
+                }
+            }
+            _ => {}
         }
+        output.push(c);
+    }
+    output
+}
+
+pub fn scene_to_mesh(scene: &Scene) -> Mesh {
+    let materials = MaterialLibrary::from_scene(scene);
+    let mut combined_mesh = ground_mesh();
+
+    // Walls of every room and wall element are joined as one graph
+    combined_mesh.append(&WallGraph::from_scene(scene).to_mesh(&materials));
 
-        vertex_offset += element_mesh.vertices.len() as u16;
+    // Then add all scene elements
+    for element in &scene.elements {
+        combined_mesh.append(&element_to_mesh(element, &materials));
     }
 
     combined_mesh
 }
 
-fn element_to_mesh(element: &Element) -> Mesh {
-    match element {
+/// The mesh of each of the scene's elements, in the order of `elements`.
+///
+/// Together with [`ground_mesh`] they hold the same geometry as
+/// [`scene_to_mesh`]. Walls joined across elements go to the element that
+/// declared them first.
+pub fn element_meshes(scene: &Scene) -> Vec<Mesh> {
+    let materials = MaterialLibrary::from_scene(scene);
+    let wall_graph = WallGraph::from_scene(scene);
+
+    scene
+        .elements
+        .iter()
+        .enumerate()
+        .map(|(index, element)| {
+            let mut mesh = wall_graph.element_mesh(&materials, index);
+            mesh.append(&element_to_mesh(element, &materials));
+            mesh
+        })
+        .collect()
+}
+
+/// The large base floor plane every scene stands on.
+pub fn ground_mesh() -> Mesh {
+    let base_floor_size = 50.0; // 50x50 meter floor
+    let mut base_floor = create_plane(
+        Vec3::new(0.0, -0.01, 0.0), // Slightly below ground level to avoid z-fighting
+        glam::Vec2::new(base_floor_size, base_floor_size),
+        Vec3::Y,
+    );
+    base_floor.set_material(GROUND_MATERIAL);
+    base_floor
+}
+
+fn element_to_mesh(element: &Element, materials: &MaterialLibrary) -> Mesh {
+    let mut mesh = match element {
         Element::Room(room) => room_to_mesh(room),
-        Element::Wall(wall) => {
-            create_box(
-                Vec3::new(
-                    (wall.start.x + wall.end.x) * 0.5,
-                    wall.height * 0.5,
-                    (wall.start.z + wall.end.z) * 0.5,
-                ),
-                Vec3::new(
-                    (wall.end - wall.start).length(),
-                    wall.height,
-                    wall.thickness,
-                ),
-            )
-        }
+        // Walls are built by the wall graph in `scene_to_mesh`
+        Element::Wall(_) => Mesh::new(),
         Element::Floor(floor) => {
             create_plane(
                 floor.position,
@@ -111,55 +262,23 @@ fn element_to_mesh(element: &Element) -> Mesh {
                     ),
                     Vec3::new(stairs.width, step_rise, step_run),
                 );
-                
-                let offset = mesh.vertices.len() as u16;
-                mesh.vertices.extend_from_slice(&step_mesh.vertices);
-                for &index in &step_mesh.indices {
-                    mesh.indices.push(index + offset);
-                }
+                mesh.append(&step_mesh);
             }
             mesh
         }
-        Element::Furniture(furniture) => {
-            use super::FurnitureType;
-            match &furniture.furniture_type {
-                FurnitureType::Table { width, depth, height } => {
-                    let mut mesh = Mesh::new();
-                    
-                    // Table top
-                    let top_mesh = create_box(
-                        furniture.position + Vec3::new(0.0, *height - 0.05, 0.0),
-                        Vec3::new(*width, 0.1, *depth),
-                    );
-                    mesh.vertices.extend_from_slice(&top_mesh.vertices);
-                    mesh.indices.extend_from_slice(&top_mesh.indices);
-                    
-                    // Table legs
-                    let leg_positions = [
-                        Vec3::new(-width * 0.4, *height * 0.5, -depth * 0.4),
-                        Vec3::new(width * 0.4, *height * 0.5, -depth * 0.4),
-                        Vec3::new(-width * 0.4, *height * 0.5, depth * 0.4),
-                        Vec3::new(width * 0.4, *height * 0.5, depth * 0.4),
-                    ];
-                    
-                    for leg_offset in &leg_positions {
-                        let offset = mesh.vertices.len() as u16;
-                        let leg_mesh = create_box(
-                            furniture.position + leg_offset,
-                            Vec3::new(0.05, *height - 0.1, 0.05),
-                        );
-                        mesh.vertices.extend_from_slice(&leg_mesh.vertices);
-                        for &index in &leg_mesh.indices {
-                            mesh.indices.push(index + offset);
-                        }
-                    }
-                    
-                    mesh
-                }
-                _ => create_box(furniture.position, Vec3::new(1.0, 1.0, 1.0)),
-            }
-        }
+        Element::Furniture(furniture) => furniture_to_mesh(furniture),
+    };
+    mesh.set_material(materials.id(element.material()));
+
+    // Walls left out of the scene graph are joined on their own
+    if !joins_scene_graph(element) {
+        mesh.append(&WallGraph::from_walls(element_walls(element)).to_mesh(materials));
     }
+
+    if let Some(matrix) = element.transform_matrix() {
+        mesh.transform(matrix);
+    }
+    mesh
 }
 
 fn room_to_mesh(room: &Room) -> Mesh {
@@ -175,60 +294,8 @@ fn room_to_mesh(room: &Room) -> Mesh {
     );
     mesh.vertices.extend_from_slice(&ceiling.vertices);
     mesh.indices.extend_from_slice(&ceiling.indices);
-    
-    // Create walls
-    for wall_spec in &room.walls {
-        let wall_mesh = create_room_wall(room, &wall_spec.side, &wall_spec.features);
-        let offset = mesh.vertices.len() as u16;
-        mesh.vertices.extend_from_slice(&wall_mesh.vertices);
-        for &index in &wall_mesh.indices {
-            mesh.indices.push(index + offset);
-        }
-    }
-    
+
+    // Walls are joined across rooms by the wall graph, not built here
+
     mesh
 }
-
-fn create_room_wall(room: &Room, side: &WallSide, _features: &[WallFeature]) -> Mesh {
-    let wall_thickness = 0.2;
-    let (start, end) = match side {
-        WallSide::North => (
-            room.position + Vec3::new(-room.dimensions.width * 0.5, 0.0, room.dimensions.depth * 0.5),
-            room.position + Vec3::new(room.dimensions.width * 0.5, 0.0, room.dimensions.depth * 0.5),
-        ),
-        WallSide::South => (
-            room.position + Vec3::new(room.dimensions.width * 0.5, 0.0, -room.dimensions.depth * 0.5),
-            room.position + Vec3::new(-room.dimensions.width * 0.5, 0.0, -room.dimensions.depth * 0.5),
-        ),
-        WallSide::East => (
-            room.position + Vec3::new(room.dimensions.width * 0.5, 0.0, room.dimensions.depth * 0.5),
-            room.position + Vec3::new(room.dimensions.width * 0.5, 0.0, -room.dimensions.depth * 0.5),
-        ),
-        WallSide::West => (
-            room.position + Vec3::new(-room.dimensions.width * 0.5, 0.0, -room.dimensions.depth * 0.5),
-            room.position + Vec3::new(-room.dimensions.width * 0.5, 0.0, room.dimensions.depth * 0.5),
-        ),
-    };
-    
-    // Calculate wall center and dimensions
-    let wall_center = (start + end) * 0.5 + Vec3::new(0.0, room.dimensions.height * 0.5, 0.0);
-    let wall_length = (end - start).length();
-    
-    // Create wall with proper orientation
-    match side {
-        WallSide::North | WallSide::South => {
-            // Walls running east-west
-            create_box(
-                wall_center,
-                Vec3::new(wall_length, room.dimensions.height, wall_thickness),
-            )
-        }
-        WallSide::East | WallSide::West => {
-            // Walls running north-south (need different orientation)
-            create_box(
-                wall_center,
-                Vec3::new(wall_thickness, room.dimensions.height, wall_length),
-            )
-        }
-    }
-}
\ No newline at end of file
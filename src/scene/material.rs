@@ -0,0 +1,176 @@
+use super::primitives::Mesh;
+use super::{Material, Scene};
+use bytemuck::{Pod, Zeroable};
+use glam::Vec3;
+use std::ops::Range;
+
+/// Material used by elements that don't name one, or name an unknown one.
+pub const DEFAULT_MATERIAL: u32 = 0;
+
+/// Material of the base ground plane, drawn with the measurement grid.
+pub const GROUND_MATERIAL: u32 = 1;
+
+/// `GpuMaterial::flags` bit: draw the 1m/5m measurement grid over the surface.
+pub const MATERIAL_FLAG_GRID: u32 = 1;
+
+/// Every material a scene can use, indexed by the material ID stored in
+/// each vertex.
+///
+/// The table starts with the default and ground materials, followed by a
+/// small built-in library (so the `material` strings of older scenes keep
+/// meaning something) and then the scene's own `materials`. A scene material
+/// with the name of a built-in one replaces it.
+#[derive(Debug, Clone)]
+pub struct MaterialLibrary {
+    materials: Vec<Material>,
+}
+
+impl MaterialLibrary {
+    pub fn from_scene(scene: &Scene) -> Self {
+        let mut library = Self { materials: builtin_materials() };
+        for material in &scene.materials {
+            match library.get(&material.name) {
+                Some(id) => library.materials[id as usize] = material.clone(),
+                None => library.materials.push(material.clone()),
+            }
+        }
+        library
+    }
+
+    /// ID of the material called `name`, if there is one.
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.materials
+            .iter()
+            .position(|material| material.name == name)
+            .map(|index| index as u32)
+    }
+
+    /// ID to draw with for an element's material name, falling back to the
+    /// default material.
+    pub fn id(&self, name: Option<&str>) -> u32 {
+        name.and_then(|name| self.get(name)).unwrap_or(DEFAULT_MATERIAL)
+    }
+
+    pub fn materials(&self) -> &[Material] {
+        &self.materials
+    }
+
+    /// Distinct texture paths used by the materials, in order of first use.
+    pub fn textures(&self) -> Vec<&str> {
+        let mut textures: Vec<&str> = Vec::new();
+        for texture in self.materials.iter().filter_map(|material| material.texture.as_deref()) {
+            if !textures.contains(&texture) {
+                textures.push(texture);
+            }
+        }
+        textures
+    }
+
+    /// Index into [`Self::textures`] of the texture a material is drawn with.
+    pub fn texture_index(&self, id: u32) -> Option<usize> {
+        let texture = self.materials.get(id as usize)?.texture.as_deref()?;
+        self.textures().iter().position(|&t| t == texture)
+    }
+
+    /// The table in the layout of the shader's material storage buffer.
+    pub fn to_gpu(&self) -> Vec<GpuMaterial> {
+        self.materials
+            .iter()
+            .enumerate()
+            .map(|(id, material)| {
+                let flags = if id as u32 == GROUND_MATERIAL { MATERIAL_FLAG_GRID } else { 0 };
+                GpuMaterial::new(material, flags)
+            })
+            .collect()
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+pub struct GpuMaterial {
+    /// RGB base colour with the opacity in alpha.
+    pub base_color: [f32; 4],
+    pub roughness: f32,
+    pub metallic: f32,
+    pub flags: u32,
+    pub tile_size: f32,
+}
+
+impl GpuMaterial {
+    pub fn new(material: &Material, flags: u32) -> Self {
+        Self {
+            base_color: material.base_color.extend(material.opacity).to_array(),
+            roughness: material.roughness,
+            metallic: material.metallic,
+            flags,
+            tile_size: material.tile_size,
+        }
+    }
+}
+
+fn material(name: &str, base_color: Vec3, roughness: f32, metallic: f32, opacity: f32) -> Material {
+    Material {
+        name: name.to_string(),
+        base_color,
+        roughness,
+        metallic,
+        opacity,
+        texture: None,
+        tile_size: 1.0,
+    }
+}
+
+fn builtin_materials() -> Vec<Material> {
+    vec![
+        material("default", Vec3::splat(0.9), 0.8, 0.0, 1.0),
+        material("ground", Vec3::new(0.85, 0.82, 0.78), 0.9, 0.0, 1.0),
+        material("plaster", Vec3::new(0.92, 0.91, 0.88), 0.9, 0.0, 1.0),
+        material("concrete", Vec3::new(0.62, 0.62, 0.6), 0.95, 0.0, 1.0),
+        material("wood", Vec3::new(0.55, 0.38, 0.22), 0.6, 0.0, 1.0),
+        material("hardwood", Vec3::new(0.45, 0.28, 0.15), 0.4, 0.0, 1.0),
+        material("marble", Vec3::new(0.93, 0.92, 0.9), 0.2, 0.0, 1.0),
+        material("tile", Vec3::new(0.85, 0.87, 0.88), 0.3, 0.0, 1.0),
+        material("carpet", Vec3::new(0.45, 0.42, 0.5), 1.0, 0.0, 1.0),
+        material("fabric", Vec3::new(0.35, 0.4, 0.5), 0.9, 0.0, 1.0),
+        material("metal", Vec3::new(0.7, 0.7, 0.72), 0.3, 1.0, 1.0),
+        material("glass", Vec3::new(0.8, 0.9, 0.95), 0.05, 0.0, 0.3),
+    ]
+}
+
+/// Run of triangles in a mesh's index buffer sharing one texture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawBatch {
+    /// Index into [`MaterialLibrary::textures`], or `None` for untextured triangles.
+    pub texture: Option<usize>,
+    pub indices: Range<u32>,
+}
+
+/// Reorder the triangles of `mesh` so those sharing a texture are contiguous,
+/// and return one batch per texture. Untextured triangles come first.
+pub fn batch_by_texture(mesh: &mut Mesh, materials: &MaterialLibrary) -> Vec<DrawBatch> {
+    let material_textures: Vec<Option<usize>> =
+        (0..materials.materials().len() as u32).map(|id| materials.texture_index(id)).collect();
+
+    let mut triangles: Vec<(Option<usize>, [u32; 3])> = mesh
+        .indices
+        .chunks_exact(3)
+        .map(|triangle| {
+            let material = mesh.vertices[triangle[0] as usize].material as usize;
+            let texture = material_textures.get(material).copied().flatten();
+            (texture, [triangle[0], triangle[1], triangle[2]])
+        })
+        .collect();
+    triangles.sort_by_key(|(texture, _)| *texture);
+
+    let mut batches: Vec<DrawBatch> = Vec::new();
+    mesh.indices.clear();
+    for (texture, triangle) in triangles {
+        let start = mesh.indices.len() as u32;
+        mesh.indices.extend_from_slice(&triangle);
+        match batches.last_mut() {
+            Some(batch) if batch.texture == texture => batch.indices.end = start + 3,
+            _ => batches.push(DrawBatch { texture, indices: start..start + 3 }),
+        }
+    }
+    batches
+}
@@ -0,0 +1,83 @@
+use serde_json::Value;
+use std::fmt;
+
+/// `format_version` written by this version of the crate.
+pub const CURRENT_FORMAT_VERSION: u32 = 1;
+
+/// Version of documents written before scenes carried a `format_version`.
+pub const UNVERSIONED: u32 = 0;
+
+/// Upgrades a document from the version it is indexed by to the next one.
+type Migration = fn(&mut serde_json::Map<String, Value>) -> Result<(), String>;
+
+/// `MIGRATIONS[n]` turns a version `n` document into a version `n + 1` one.
+/// Changes to the scene format add a step here and bump
+/// [`CURRENT_FORMAT_VERSION`].
+const MIGRATIONS: [Migration; CURRENT_FORMAT_VERSION as usize] = [from_unversioned];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MigrationError {
+    /// The document isn't a JSON object.
+    NotAScene,
+    /// `format_version` isn't a non-negative integer.
+    InvalidVersion(Value),
+    /// Written by a newer version of the crate.
+    UnsupportedVersion(u64),
+    /// A migration step failed on a document of version `from`.
+    Failed { from: u32, message: String },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::NotAScene => write!(f, "scene must be a JSON object"),
+            MigrationError::InvalidVersion(version) => write!(f, "invalid format_version {}", version),
+            MigrationError::UnsupportedVersion(version) => write!(
+                f,
+                "format_version {} is newer than the supported version {}",
+                version, CURRENT_FORMAT_VERSION
+            ),
+            MigrationError::Failed { from, message } => {
+                write!(f, "failed to upgrade from format_version {}: {}", from, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// `format_version` of a scene document; [`UNVERSIONED`] if it has none.
+pub fn format_version(document: &Value) -> Result<u32, MigrationError> {
+    let object = document.as_object().ok_or(MigrationError::NotAScene)?;
+    match object.get("format_version") {
+        None => Ok(UNVERSIONED),
+        Some(version) => {
+            let number = version
+                .as_u64()
+                .ok_or_else(|| MigrationError::InvalidVersion(version.clone()))?;
+            match u32::try_from(number) {
+                Ok(number) if number <= CURRENT_FORMAT_VERSION => Ok(number),
+                _ => Err(MigrationError::UnsupportedVersion(number)),
+            }
+        }
+    }
+}
+
+/// Upgrade `document` in place to [`CURRENT_FORMAT_VERSION`], one step at a
+/// time, returning the version it had before.
+pub fn migrate(document: &mut Value) -> Result<u32, MigrationError> {
+    let original = format_version(document)?;
+    let object = document.as_object_mut().ok_or(MigrationError::NotAScene)?;
+
+    for from in original..CURRENT_FORMAT_VERSION {
+        MIGRATIONS[from as usize](object).map_err(|message| MigrationError::Failed { from, message })?;
+        object.insert("format_version".to_string(), Value::from(from + 1));
+    }
+    Ok(original)
+}
+
+/// Version 1 is the unversioned format with a version number: every scene
+/// written so far reads the same, so there is nothing to change.
+fn from_unversioned(_scene: &mut serde_json::Map<String, Value>) -> Result<(), String> {
+    Ok(())
+}
@@ -1,16 +1,181 @@
 pub mod primitives;
 pub mod loader;
+pub mod wall;
+pub mod wall_graph;
+pub mod furniture;
+pub mod material;
+pub mod light;
+pub mod solar;
+pub mod validation;
+pub mod migration;
+pub mod levels;
+pub mod catalog;
+pub mod watch;
+pub mod viewpoints;
 
-use glam::{Vec2, Vec3};
+use chrono::NaiveDateTime;
+use glam::{EulerRot, Mat4, Quat, Vec2, Vec3};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Scene {
+    /// Version of the scene format the document was written in; see
+    /// [`migration`]. Documents without one are [`migration::UNVERSIONED`].
+    #[serde(default)]
+    pub format_version: u32,
     pub name: String,
     pub camera: Camera,
+    /// Named places to look at the scene from besides `camera`. Viewpoints
+    /// saved from the viewer go to a file of their own; see [`viewpoints`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub viewpoints: Vec<Viewpoint>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub materials: Vec<Material>,
+    /// Lights of the scene. Scenes without any are lit by
+    /// [`light::default_lights`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lights: Vec<Light>,
+    /// Where the building stands, for placing the sun.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub site: Option<Site>,
     pub elements: Vec<Element>,
 }
 
+/// Surface appearance, referenced by name from elements.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Material {
+    pub name: String,
+    /// Linear RGB, each channel in `0..=1`.
+    pub base_color: Vec3,
+    #[serde(default = "default_roughness")]
+    pub roughness: f32,
+    #[serde(default)]
+    pub metallic: f32,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    /// Image file, relative to the scene file, multiplied over `base_color`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texture: Option<String>,
+    /// Metres of surface covered by one repeat of the texture.
+    #[serde(default = "default_tile_size")]
+    pub tile_size: f32,
+}
+
+fn default_roughness() -> f32 {
+    0.5
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+fn default_tile_size() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Light {
+    Ambient(AmbientLight),
+    Sun(SunLight),
+    Point(PointLight),
+    Spot(SpotLight),
+}
+
+/// Light reaching every surface equally, standing in for bounced light.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmbientLight {
+    #[serde(default = "default_light_color")]
+    pub color: Vec3,
+    #[serde(default = "default_light_intensity")]
+    pub intensity: f32,
+}
+
+/// Directional light from infinitely far away.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SunLight {
+    /// Direction the light travels in, so pointing down for a sun overhead.
+    pub direction: Vec3,
+    #[serde(default = "default_light_color")]
+    pub color: Vec3,
+    #[serde(default = "default_light_intensity")]
+    pub intensity: f32,
+}
+
+/// Lamp shining equally in all directions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointLight {
+    pub position: Vec3,
+    #[serde(default = "default_light_color")]
+    pub color: Vec3,
+    /// Brightness at 1 m; it falls off with the square of the distance.
+    #[serde(default = "default_light_intensity")]
+    pub intensity: f32,
+    /// Distance in metres beyond which the light has no effect.
+    #[serde(default = "default_light_range")]
+    pub range: f32,
+}
+
+/// Lamp shining in a cone around `direction`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpotLight {
+    pub position: Vec3,
+    pub direction: Vec3,
+    #[serde(default = "default_light_color")]
+    pub color: Vec3,
+    /// Brightness at 1 m; it falls off with the square of the distance.
+    #[serde(default = "default_light_intensity")]
+    pub intensity: f32,
+    /// Distance in metres beyond which the light has no effect.
+    #[serde(default = "default_light_range")]
+    pub range: f32,
+    /// Half-angle in degrees of the fully lit centre of the cone.
+    #[serde(default = "default_spot_inner_angle")]
+    pub inner_angle: f32,
+    /// Half-angle in degrees where the light has faded out.
+    #[serde(default = "default_spot_outer_angle")]
+    pub outer_angle: f32,
+}
+
+fn default_light_color() -> Vec3 {
+    Vec3::ONE
+}
+
+fn default_light_intensity() -> f32 {
+    1.0
+}
+
+fn default_light_range() -> f32 {
+    10.0
+}
+
+fn default_spot_inner_angle() -> f32 {
+    20.0
+}
+
+fn default_spot_outer_angle() -> f32 {
+    30.0
+}
+
+/// Geographic location and orientation of a scene. With a `date_time`, the
+/// scene's sun is placed where the real sun is at that moment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Site {
+    /// Degrees, positive north of the equator.
+    pub latitude: f64,
+    /// Degrees, positive east of Greenwich.
+    pub longitude: f64,
+    /// Degrees clockwise, seen from above, from the scene's -Z axis to true north.
+    #[serde(default)]
+    pub north_angle: f32,
+    /// Offset of local time from UTC in hours, e.g. `1.0` for CET.
+    #[serde(default)]
+    pub timezone: f64,
+    /// Local time at the site, e.g. `"2024-06-21T15:00:00"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_time: Option<NaiveDateTime>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Camera {
     pub position: Vec3,
@@ -18,6 +183,14 @@ pub struct Camera {
     pub fov: f32,
 }
 
+/// A camera with a name, such as "Kitchen" or "Street view".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Viewpoint {
+    pub name: String,
+    #[serde(flatten)]
+    pub camera: Camera,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Element {
@@ -30,11 +203,174 @@ pub enum Element {
     Furniture(Furniture),
 }
 
+impl Element {
+    /// The element's `type` in scene files.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Element::Room(_) => "room",
+            Element::Wall(_) => "wall",
+            Element::Floor(_) => "floor",
+            Element::Ceiling(_) => "ceiling",
+            Element::Stairs(_) => "stairs",
+            Element::Column(_) => "column",
+            Element::Furniture(_) => "furniture",
+        }
+    }
+
+    pub fn transform(&self) -> Option<&Transform> {
+        match self {
+            Element::Room(room) => room.transform.as_ref(),
+            Element::Wall(wall) => wall.transform.as_ref(),
+            Element::Floor(floor) => floor.transform.as_ref(),
+            Element::Ceiling(ceiling) => ceiling.transform.as_ref(),
+            Element::Stairs(stairs) => stairs.transform.as_ref(),
+            Element::Column(column) => column.transform.as_ref(),
+            Element::Furniture(furniture) => furniture.transform.as_ref(),
+        }
+    }
+
+    /// Name of the material the element is drawn with, if it names one.
+    pub fn material(&self) -> Option<&str> {
+        match self {
+            Element::Room(room) => room.material.as_deref(),
+            Element::Wall(wall) => wall.material.as_deref(),
+            Element::Floor(floor) => Some(&floor.material),
+            Element::Ceiling(ceiling) => Some(&ceiling.material),
+            Element::Stairs(stairs) => stairs.material.as_deref(),
+            Element::Column(column) => column.material.as_deref(),
+            Element::Furniture(furniture) => furniture.material.as_deref(),
+        }
+    }
+
+    /// Point the element's transform rotates and scales around.
+    pub fn anchor(&self) -> Vec3 {
+        match self {
+            Element::Room(room) => room.position,
+            Element::Wall(wall) => (wall.start + wall.end) * 0.5,
+            Element::Floor(floor) => floor.position,
+            Element::Ceiling(ceiling) => ceiling.position,
+            Element::Stairs(stairs) => stairs.position,
+            Element::Column(column) => column.position,
+            Element::Furniture(furniture) => furniture.position,
+        }
+    }
+
+    /// Elevation the element stands at, after its transform.
+    pub fn base_elevation(&self) -> f32 {
+        let base = match self {
+            Element::Wall(wall) => wall.start.y.min(wall.end.y),
+            _ => self.anchor().y,
+        };
+        base + self.transform().map_or(0.0, |transform| transform.translation.y)
+    }
+
+    /// World matrix of the element's transform, if it has one.
+    pub fn transform_matrix(&self) -> Option<Mat4> {
+        self.transform().map(|transform| transform.matrix_about(self.anchor()))
+    }
+}
+
+/// Optional placement of an element, applied around its anchor point
+/// (its `position`, or the midpoint of a wall) after it has been built.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Transform {
+    #[serde(default)]
+    pub translation: Vec3,
+    #[serde(default)]
+    pub rotation: Rotation,
+    #[serde(default)]
+    pub scale: Scale,
+}
+
+/// Rotation in degrees: a single yaw around Y, or `[x, y, z]` Euler angles
+/// applied as yaw, then pitch, then roll.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Rotation {
+    Yaw(f32),
+    Euler(Vec3),
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Rotation::Yaw(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Scale {
+    Uniform(f32),
+    PerAxis(Vec3),
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Scale::Uniform(1.0)
+    }
+}
+
+impl Transform {
+    pub fn rotation_quat(&self) -> Quat {
+        match self.rotation {
+            Rotation::Yaw(yaw) => Quat::from_rotation_y(yaw.to_radians()),
+            Rotation::Euler(angles) => Quat::from_euler(
+                EulerRot::YXZ,
+                angles.y.to_radians(),
+                angles.x.to_radians(),
+                angles.z.to_radians(),
+            ),
+        }
+    }
+
+    pub fn scale_vec(&self) -> Vec3 {
+        match self.scale {
+            Scale::Uniform(scale) => Vec3::splat(scale),
+            Scale::PerAxis(scale) => scale,
+        }
+    }
+
+    /// Scale and rotate around `pivot`, then translate.
+    pub fn matrix_about(&self, pivot: Vec3) -> Mat4 {
+        Mat4::from_translation(pivot + self.translation)
+            * Mat4::from_scale_rotation_translation(self.scale_vec(), self.rotation_quat(), Vec3::ZERO)
+            * Mat4::from_translation(-pivot)
+    }
+
+    /// True when the transform only moves and turns the element around the
+    /// vertical axis, so walls stay upright and keep their dimensions.
+    pub fn is_planar_rigid(&self) -> bool {
+        let tilted = match self.rotation {
+            Rotation::Yaw(_) => false,
+            Rotation::Euler(angles) => angles.x.abs() > f32::EPSILON || angles.z.abs() > f32::EPSILON,
+        };
+        !tilted && self.scale_vec().abs_diff_eq(Vec3::ONE, 1e-6)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Room {
     pub position: Vec3,
     pub dimensions: Dimensions,
     pub walls: Vec<WallSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<Transform>,
+}
+
+impl Room {
+    /// Floor area inside the room's walls.
+    pub fn floor_area(&self) -> f32 {
+        let thickness = wall_graph::ROOM_WALL_THICKNESS;
+        ((self.dimensions.width - thickness) * (self.dimensions.depth - thickness)).max(0.0)
+    }
+
+    /// Length of the inside faces of the room's walls, all round.
+    pub fn perimeter(&self) -> f32 {
+        let thickness = wall_graph::ROOM_WALL_THICKNESS;
+        2.0 * ((self.dimensions.width - thickness).max(0.0) + (self.dimensions.depth - thickness).max(0.0))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +402,30 @@ pub enum WallFeature {
     Door(DoorFeature),
 }
 
+impl WallFeature {
+    /// Position on the wall face, `x` measured from the wall centre.
+    pub fn position(&self) -> Vec2 {
+        match self {
+            WallFeature::Window(window) => window.position,
+            WallFeature::Door(door) => door.position,
+        }
+    }
+
+    pub fn set_position(&mut self, position: Vec2) {
+        match self {
+            WallFeature::Window(window) => window.position = position,
+            WallFeature::Door(door) => door.position = position,
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        match self {
+            WallFeature::Window(window) => window.size,
+            WallFeature::Door(door) => door.size,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WindowFeature {
     pub position: Vec2,
@@ -89,6 +449,18 @@ pub enum DoorType {
     Revolving,
 }
 
+impl DoorType {
+    /// The door's `door_type` in scene files.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DoorType::Single => "single",
+            DoorType::Double => "double",
+            DoorType::Sliding => "sliding",
+            DoorType::Revolving => "revolving",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Wall {
     pub start: Vec3,
@@ -96,6 +468,10 @@ pub struct Wall {
     pub height: f32,
     pub thickness: f32,
     pub features: Vec<WallFeature>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<Transform>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -103,6 +479,8 @@ pub struct Floor {
     pub position: Vec3,
     pub dimensions: Vec2,
     pub material: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<Transform>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -111,6 +489,8 @@ pub struct Ceiling {
     pub dimensions: Vec2,
     pub height: f32,
     pub material: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<Transform>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,6 +501,10 @@ pub struct Stairs {
     pub step_height: f32,
     pub step_depth: f32,
     pub has_railing: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<Transform>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -129,6 +513,10 @@ pub struct Column {
     pub radius: f32,
     pub height: f32,
     pub shape: ColumnShape,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<Transform>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -144,6 +532,10 @@ pub struct Furniture {
     pub position: Vec3,
     pub rotation: f32,
     pub furniture_type: FurnitureType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<Transform>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -155,4 +547,32 @@ pub enum FurnitureType {
     Bed { width: f32, length: f32 },
     Desk { width: f32, depth: f32 },
     Cabinet { width: f32, depth: f32, height: f32 },
+    Shelf { width: f32, depth: f32, height: f32, shelves: u32 },
+    Wardrobe { width: f32, depth: f32, height: f32 },
+    Toilet,
+    Sink { width: f32, depth: f32 },
+    Bathtub { width: f32, length: f32 },
+    KitchenCounter { width: f32, depth: f32, height: f32 },
+    Fridge { width: f32, depth: f32, height: f32 },
+}
+
+impl FurnitureType {
+    /// The piece's `type` in scene files.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FurnitureType::Table { .. } => "table",
+            FurnitureType::Chair { .. } => "chair",
+            FurnitureType::Sofa { .. } => "sofa",
+            FurnitureType::Bed { .. } => "bed",
+            FurnitureType::Desk { .. } => "desk",
+            FurnitureType::Cabinet { .. } => "cabinet",
+            FurnitureType::Shelf { .. } => "shelf",
+            FurnitureType::Wardrobe { .. } => "wardrobe",
+            FurnitureType::Toilet => "toilet",
+            FurnitureType::Sink { .. } => "sink",
+            FurnitureType::Bathtub { .. } => "bathtub",
+            FurnitureType::KitchenCounter { .. } => "kitchen_counter",
+            FurnitureType::Fridge { .. } => "fridge",
+        }
+    }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 use bytemuck::{Pod, Zeroable};
-use glam::{Vec2, Vec3};
+use glam::{Mat3, Mat4, Vec2, Vec3};
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
@@ -7,6 +7,8 @@ pub struct Vertex {
     pub position: [f32; 3],
     pub normal: [f32; 3],
     pub tex_coords: [f32; 2],
+    /// Index into the scene's material table.
+    pub material: u32,
 }
 
 impl Vertex {
@@ -15,13 +17,14 @@ impl Vertex {
             position: position.to_array(),
             normal: normal.to_array(),
             tex_coords: tex_coords.to_array(),
+            material: 0,
         }
     }
 }
 
 pub struct Mesh {
     pub vertices: Vec<Vertex>,
-    pub indices: Vec<u16>,
+    pub indices: Vec<u32>,
 }
 
 impl Mesh {
@@ -32,21 +35,60 @@ impl Mesh {
         }
     }
 
-    pub fn add_vertex(&mut self, vertex: Vertex) -> u16 {
-        let index = self.vertices.len() as u16;
+    pub fn add_vertex(&mut self, vertex: Vertex) -> u32 {
+        let index = self.vertices.len() as u32;
         self.vertices.push(vertex);
         index
     }
 
-    pub fn add_triangle(&mut self, i0: u16, i1: u16, i2: u16) {
+    pub fn add_triangle(&mut self, i0: u32, i1: u32, i2: u32) {
         self.indices.extend_from_slice(&[i0, i1, i2]);
     }
 
-    pub fn add_quad(&mut self, i0: u16, i1: u16, i2: u16, i3: u16) {
+    pub fn add_quad(&mut self, i0: u32, i1: u32, i2: u32, i3: u32) {
         self.indices.extend_from_slice(&[i0, i1, i2, i0, i2, i3]);
     }
+
+    /// Draw every vertex of the mesh with one material.
+    pub fn set_material(&mut self, material: u32) {
+        for vertex in &mut self.vertices {
+            vertex.material = material;
+        }
+    }
+
+    /// Append another mesh, offsetting its indices past our vertices.
+    pub fn append(&mut self, other: &Mesh) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend_from_slice(&other.vertices);
+        self.indices.extend(other.indices.iter().map(|&i| i + offset));
+    }
+
+    /// Transform positions by `matrix` and normals by its inverse transpose.
+    /// Mirroring matrices also flip the triangle winding so faces stay outward.
+    pub fn transform(&mut self, matrix: Mat4) {
+        let normal_matrix = Mat3::from_mat4(matrix).inverse().transpose();
+        for vertex in &mut self.vertices {
+            let position = matrix.transform_point3(Vec3::from(vertex.position));
+            let normal = (normal_matrix * Vec3::from(vertex.normal)).normalize_or_zero();
+            vertex.position = position.to_array();
+            vertex.normal = normal.to_array();
+        }
+        if matrix.determinant() < 0.0 {
+            for triangle in self.indices.chunks_exact_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+    }
+}
+
+impl Default for Mesh {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
+/// Axis-aligned box. Texture coordinates are in metres across each face, so
+/// materials tile at the same scale whatever the box size.
 pub fn create_box(center: Vec3, size: Vec3) -> Mesh {
     let mut mesh = Mesh::new();
     let half = size * 0.5;
@@ -55,17 +97,17 @@ pub fn create_box(center: Vec3, size: Vec3) -> Mesh {
     let v0 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(-half.x, -half.y, half.z),
         Vec3::Z,
-        Vec2::new(0.0, 1.0),
+        Vec2::new(0.0, size.y),
     ));
     let v1 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(half.x, -half.y, half.z),
         Vec3::Z,
-        Vec2::new(1.0, 1.0),
+        Vec2::new(size.x, size.y),
     ));
     let v2 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(half.x, half.y, half.z),
         Vec3::Z,
-        Vec2::new(1.0, 0.0),
+        Vec2::new(size.x, 0.0),
     ));
     let v3 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(-half.x, half.y, half.z),
@@ -78,17 +120,17 @@ pub fn create_box(center: Vec3, size: Vec3) -> Mesh {
     let v4 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(half.x, -half.y, -half.z),
         -Vec3::Z,
-        Vec2::new(0.0, 1.0),
+        Vec2::new(0.0, size.y),
     ));
     let v5 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(-half.x, -half.y, -half.z),
         -Vec3::Z,
-        Vec2::new(1.0, 1.0),
+        Vec2::new(size.x, size.y),
     ));
     let v6 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(-half.x, half.y, -half.z),
         -Vec3::Z,
-        Vec2::new(1.0, 0.0),
+        Vec2::new(size.x, 0.0),
     ));
     let v7 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(half.x, half.y, -half.z),
@@ -101,17 +143,17 @@ pub fn create_box(center: Vec3, size: Vec3) -> Mesh {
     let v8 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(half.x, -half.y, half.z),
         Vec3::X,
-        Vec2::new(0.0, 1.0),
+        Vec2::new(0.0, size.y),
     ));
     let v9 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(half.x, -half.y, -half.z),
         Vec3::X,
-        Vec2::new(1.0, 1.0),
+        Vec2::new(size.z, size.y),
     ));
     let v10 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(half.x, half.y, -half.z),
         Vec3::X,
-        Vec2::new(1.0, 0.0),
+        Vec2::new(size.z, 0.0),
     ));
     let v11 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(half.x, half.y, half.z),
@@ -124,17 +166,17 @@ pub fn create_box(center: Vec3, size: Vec3) -> Mesh {
     let v12 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(-half.x, -half.y, -half.z),
         -Vec3::X,
-        Vec2::new(0.0, 1.0),
+        Vec2::new(0.0, size.y),
     ));
     let v13 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(-half.x, -half.y, half.z),
         -Vec3::X,
-        Vec2::new(1.0, 1.0),
+        Vec2::new(size.z, size.y),
     ));
     let v14 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(-half.x, half.y, half.z),
         -Vec3::X,
-        Vec2::new(1.0, 0.0),
+        Vec2::new(size.z, 0.0),
     ));
     let v15 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(-half.x, half.y, -half.z),
@@ -152,17 +194,17 @@ pub fn create_box(center: Vec3, size: Vec3) -> Mesh {
     let v17 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(half.x, half.y, half.z),
         Vec3::Y,
-        Vec2::new(1.0, 0.0),
+        Vec2::new(size.x, 0.0),
     ));
     let v18 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(half.x, half.y, -half.z),
         Vec3::Y,
-        Vec2::new(1.0, 1.0),
+        Vec2::new(size.x, size.z),
     ));
     let v19 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(-half.x, half.y, -half.z),
         Vec3::Y,
-        Vec2::new(0.0, 1.0),
+        Vec2::new(0.0, size.z),
     ));
     mesh.add_quad(v16, v17, v18, v19);
 
@@ -175,28 +217,29 @@ pub fn create_box(center: Vec3, size: Vec3) -> Mesh {
     let v21 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(half.x, -half.y, -half.z),
         -Vec3::Y,
-        Vec2::new(1.0, 0.0),
+        Vec2::new(size.x, 0.0),
     ));
     let v22 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(half.x, -half.y, half.z),
         -Vec3::Y,
-        Vec2::new(1.0, 1.0),
+        Vec2::new(size.x, size.z),
     ));
     let v23 = mesh.add_vertex(Vertex::new(
         center + Vec3::new(-half.x, -half.y, half.z),
         -Vec3::Y,
-        Vec2::new(0.0, 1.0),
+        Vec2::new(0.0, size.z),
     ));
     mesh.add_quad(v20, v21, v22, v23);
 
     mesh
 }
 
+/// Rectangle facing `normal`, with texture coordinates in metres.
 pub fn create_plane(center: Vec3, size: Vec2, normal: Vec3) -> Mesh {
     let mut mesh = Mesh::new();
     
     // Calculate basis vectors for the plane
-    let up = if normal.abs_diff_eq(Vec3::Y, 0.01) {
+    let up = if normal.y.abs() > 0.99 {
         Vec3::Z
     } else {
         Vec3::Y
@@ -215,104 +258,100 @@ pub fn create_plane(center: Vec3, size: Vec2, normal: Vec3) -> Mesh {
     let v1 = mesh.add_vertex(Vertex::new(
         center + right * half_width - forward * half_height,
         normal,
-        Vec2::new(1.0, 0.0),
+        Vec2::new(size.x, 0.0),
     ));
     let v2 = mesh.add_vertex(Vertex::new(
         center + right * half_width + forward * half_height,
         normal,
-        Vec2::new(1.0, 1.0),
+        Vec2::new(size.x, size.y),
     ));
     let v3 = mesh.add_vertex(Vertex::new(
         center - right * half_width + forward * half_height,
         normal,
-        Vec2::new(0.0, 1.0),
+        Vec2::new(0.0, size.y),
     ));
 
-    // Reverse winding order for upward-facing planes so they're visible from above
-    if normal.y > 0.5 {
-        mesh.add_quad(v0, v3, v2, v1);
-    } else {
-        mesh.add_quad(v0, v1, v2, v3);
-    }
+    // `right` x `forward` points against the normal, so wind the other way
+    // round to face the front towards `normal`
+    mesh.add_quad(v0, v3, v2, v1);
     mesh
 }
 
+/// Upright cylinder centred on `center`. Texture coordinates are in metres:
+/// around the circumference and up the side, and across the caps.
 pub fn create_cylinder(center: Vec3, radius: f32, height: f32, segments: u32) -> Mesh {
     let mut mesh = Mesh::new();
     let half_height = height * 0.5;
+    let circumference = std::f32::consts::TAU * radius;
 
-    // Create vertices for top and bottom circles
-    for i in 0..segments {
-        let angle = (i as f32 / segments as f32) * std::f32::consts::TAU;
+    // Side vertices, with the first column repeated to close the texture seam
+    for i in 0..=segments {
+        let t = i as f32 / segments as f32;
+        let angle = t * std::f32::consts::TAU;
         let x = angle.cos() * radius;
         let z = angle.sin() * radius;
+        let normal = Vec3::new(x, 0.0, z).normalize();
 
         // Bottom vertex
         mesh.add_vertex(Vertex::new(
             center + Vec3::new(x, -half_height, z),
-            Vec3::new(x, 0.0, z).normalize(),
-            Vec2::new(i as f32 / segments as f32, 0.0),
+            normal,
+            Vec2::new(t * circumference, height),
         ));
 
         // Top vertex
         mesh.add_vertex(Vertex::new(
             center + Vec3::new(x, half_height, z),
-            Vec3::new(x, 0.0, z).normalize(),
-            Vec2::new(i as f32 / segments as f32, 1.0),
+            normal,
+            Vec2::new(t * circumference, 0.0),
         ));
     }
 
     // Create side faces
     for i in 0..segments {
-        let next = (i + 1) % segments;
         let bottom_current = i * 2;
         let top_current = bottom_current + 1;
-        let bottom_next = next * 2;
-        let top_next = bottom_next + 1;
+        let bottom_next = bottom_current + 2;
+        let top_next = bottom_current + 3;
 
         // Reverse winding order for outward-facing surfaces
         mesh.add_quad(
-            bottom_current as u16,
-            top_current as u16,
-            top_next as u16,
-            bottom_next as u16,
+            bottom_current,
+            top_current,
+            top_next,
+            bottom_next,
         );
     }
 
-    // Add center vertices for caps
-    let bottom_center = mesh.add_vertex(Vertex::new(
-        center + Vec3::new(0.0, -half_height, 0.0),
-        -Vec3::Y,
-        Vec2::new(0.5, 0.5),
-    ));
-    let top_center = mesh.add_vertex(Vertex::new(
-        center + Vec3::new(0.0, half_height, 0.0),
-        Vec3::Y,
-        Vec2::new(0.5, 0.5),
-    ));
-
-    // Create cap faces
-    for i in 0..segments {
-        let next = (i + 1) % segments;
-        let bottom_current = i * 2;
-        let top_current = bottom_current + 1;
-        let bottom_next = next * 2;
-        let top_next = bottom_next + 1;
-
-        // Bottom cap (viewed from below, so reversed)
-        mesh.add_triangle(
-            bottom_center,
-            bottom_current as u16,
-            bottom_next as u16,
-        );
+    // Caps get their own vertices so they can face straight up and down
+    for (y, normal) in [(-half_height, -Vec3::Y), (half_height, Vec3::Y)] {
+        let cap_center = mesh.add_vertex(Vertex::new(
+            center + Vec3::new(0.0, y, 0.0),
+            normal,
+            Vec2::ZERO,
+        ));
+        for i in 0..segments {
+            let angle = (i as f32 / segments as f32) * std::f32::consts::TAU;
+            let offset = Vec2::new(angle.cos(), angle.sin()) * radius;
+            mesh.add_vertex(Vertex::new(
+                center + Vec3::new(offset.x, y, offset.y),
+                normal,
+                offset,
+            ));
+        }
 
-        // Top cap (viewed from above)
-        mesh.add_triangle(
-            top_center,
-            top_next as u16,
-            top_current as u16,
-        );
+        for i in 0..segments {
+            let current = cap_center + 1 + i;
+            let next = cap_center + 1 + (i + 1) % segments;
+            if normal.y < 0.0 {
+                // Bottom cap (viewed from below, so reversed)
+                mesh.add_triangle(cap_center, current, next);
+            } else {
+                // Top cap (viewed from above)
+                mesh.add_triangle(cap_center, next, current);
+            }
+        }
     }
 
     mesh
-}
\ No newline at end of file
+}
@@ -0,0 +1,101 @@
+use super::Site;
+use chrono::{DateTime, Duration, NaiveDateTime, Timelike, Utc};
+use glam::{Quat, Vec3};
+
+/// Where the sun is in the sky, as seen from a site.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolarPosition {
+    /// Degrees clockwise from true north.
+    pub azimuth: f64,
+    /// Degrees above the horizon; negative at night.
+    pub elevation: f64,
+}
+
+impl SolarPosition {
+    pub fn is_above_horizon(&self) -> bool {
+        self.elevation > 0.0
+    }
+}
+
+/// Position of the sun at `latitude`/`longitude` (degrees) at `time`, using
+/// NOAA's low-accuracy solar equations (about 0.01° until 2100), without
+/// atmospheric refraction.
+pub fn solar_position(latitude: f64, longitude: f64, time: DateTime<Utc>) -> SolarPosition {
+    // Julian centuries since J2000.0
+    let julian_day = time.timestamp() as f64 / 86400.0 + 2440587.5;
+    let t = (julian_day - 2451545.0) / 36525.0;
+
+    let mean_longitude = (280.46646 + t * (36000.76983 + t * 0.0003032)).rem_euclid(360.0);
+    let mean_anomaly = (357.52911 + t * (35999.05029 - 0.0001537 * t)).to_radians();
+    let eccentricity = 0.016708634 - t * (0.000042037 + 0.0000001267 * t);
+
+    let equation_of_center = mean_anomaly.sin() * (1.914602 - t * (0.004817 + 0.000014 * t))
+        + (2.0 * mean_anomaly).sin() * (0.019993 - 0.000101 * t)
+        + (3.0 * mean_anomaly).sin() * 0.000289;
+    let true_longitude = mean_longitude + equation_of_center;
+    let omega = (125.04 - 1934.136 * t).to_radians();
+    let apparent_longitude = (true_longitude - 0.00569 - 0.00478 * omega.sin()).to_radians();
+
+    let mean_obliquity = 23.0 + (26.0 + (21.448 - t * (46.815 + t * (0.00059 - t * 0.001813))) / 60.0) / 60.0;
+    let obliquity = (mean_obliquity + 0.00256 * omega.cos()).to_radians();
+    let declination = (obliquity.sin() * apparent_longitude.sin()).asin();
+
+    // Equation of time, in minutes
+    let y = (obliquity / 2.0).tan().powi(2);
+    let l0 = mean_longitude.to_radians();
+    let equation_of_time = 4.0
+        * (y * (2.0 * l0).sin() - 2.0 * eccentricity * mean_anomaly.sin()
+            + 4.0 * eccentricity * y * mean_anomaly.sin() * (2.0 * l0).cos()
+            - 0.5 * y * y * (4.0 * l0).sin()
+            - 1.25 * eccentricity * eccentricity * (2.0 * mean_anomaly).sin())
+        .to_degrees();
+
+    let utc_minutes = time.num_seconds_from_midnight() as f64 / 60.0;
+    let true_solar_time = (utc_minutes + equation_of_time + 4.0 * longitude).rem_euclid(1440.0);
+    let hour_angle = (true_solar_time / 4.0 - 180.0).to_radians();
+
+    let latitude = latitude.to_radians();
+    let cos_zenith = latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+    let elevation = 90.0 - cos_zenith.clamp(-1.0, 1.0).acos().to_degrees();
+    let azimuth = hour_angle
+        .sin()
+        .atan2(hour_angle.cos() * latitude.sin() - declination.tan() * latitude.cos())
+        .to_degrees()
+        + 180.0;
+
+    SolarPosition {
+        azimuth: azimuth.rem_euclid(360.0),
+        elevation,
+    }
+}
+
+impl Site {
+    /// UTC time of a local time at the site.
+    pub fn to_utc(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        let offset = Duration::seconds((self.timezone * 3600.0).round() as i64);
+        (local - offset).and_utc()
+    }
+
+    /// Position of the sun at the site at local time `local`.
+    pub fn solar_position(&self, local: NaiveDateTime) -> SolarPosition {
+        solar_position(self.latitude, self.longitude, self.to_utc(local))
+    }
+
+    /// Direction sunlight travels in scene coordinates at local time `local`,
+    /// taking `north_angle` into account. Points upwards when the sun is below
+    /// the horizon.
+    pub fn sun_direction(&self, local: NaiveDateTime) -> Vec3 {
+        let position = self.solar_position(local);
+        let azimuth = (position.azimuth as f32).to_radians();
+        let elevation = (position.elevation as f32).to_radians();
+
+        // With north along -Z and east along +X
+        let to_sun = Vec3::new(
+            azimuth.sin() * elevation.cos(),
+            elevation.sin(),
+            -azimuth.cos() * elevation.cos(),
+        );
+        // Clockwise seen from above is a negative turn about +Y
+        -(Quat::from_rotation_y(-self.north_angle.to_radians()) * to_sun)
+    }
+}
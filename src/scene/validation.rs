@@ -0,0 +1,246 @@
+use super::material::MaterialLibrary;
+use super::wall::{Opening, WallSegment};
+use super::wall_graph::room_wall_segment;
+use super::{Element, FurnitureType, Scene, Viewpoint, WallFeature};
+use std::fmt;
+
+/// Slack for comparing lengths in metres, so features placed flush against a
+/// wall edge or the floor by hand-written JSON aren't reported.
+const TOLERANCE: f32 = 1e-3;
+
+/// Something in a scene that parses but can't be built as described.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// Index into the scene's `elements`, if the problem is inside one.
+    pub element: Option<usize>,
+    /// JSON path of the offending value, e.g. `elements[3].walls[1].features[0]`.
+    pub path: String,
+    pub kind: ValidationErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationErrorKind {
+    /// A size that must be positive is zero or negative.
+    NonPositiveDimension { value: f32 },
+    /// Stairs without any steps.
+    NoSteps,
+    /// A window or door reaching past the ends or top of its wall, or below its base.
+    FeatureOutsideWall { wall_length: f32, wall_height: f32 },
+    /// A door whose threshold isn't at the base of its wall.
+    DoorAboveFloor { threshold: f32 },
+    /// Two openings in the same wall overlap; `other` is the index of the
+    /// earlier feature.
+    OverlappingFeatures { other: usize },
+    /// A material name that is neither built in nor defined by the scene.
+    UnknownMaterial { name: String },
+    /// A camera whose target is where it stands, so it looks nowhere.
+    CameraWithoutDirection,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.path)?;
+        match &self.kind {
+            ValidationErrorKind::NonPositiveDimension { value } => write!(f, "must be positive, got {}", value),
+            ValidationErrorKind::NoSteps => write!(f, "stairs need at least one step"),
+            ValidationErrorKind::FeatureOutsideWall { wall_length, wall_height } => write!(
+                f,
+                "opening does not fit in its {:.2}m long, {:.2}m high wall",
+                wall_length, wall_height
+            ),
+            ValidationErrorKind::DoorAboveFloor { threshold } => {
+                write!(f, "door threshold is {:.2}m above the floor", threshold)
+            }
+            ValidationErrorKind::OverlappingFeatures { other } => write!(f, "overlaps features[{}]", other),
+            ValidationErrorKind::UnknownMaterial { name } => write!(f, "unknown material '{}'", name),
+            ValidationErrorKind::CameraWithoutDirection => write!(f, "camera target is the same as its position"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Check `scene` for problems serde can't catch, in document order.
+pub fn validate_scene(scene: &Scene) -> Vec<ValidationError> {
+    let materials = MaterialLibrary::from_scene(scene);
+    let mut errors = validate_viewpoints(&scene.viewpoints);
+
+    for (index, element) in scene.elements.iter().enumerate() {
+        let mut check = Checker {
+            element: index,
+            path: format!("elements[{}]", index),
+            errors: &mut errors,
+        };
+
+        if let Some(name) = element.material() {
+            if materials.get(name).is_none() {
+                check.report("material", ValidationErrorKind::UnknownMaterial { name: name.to_string() });
+            }
+        }
+
+        match element {
+            Element::Room(room) => {
+                check.positive("dimensions.width", room.dimensions.width);
+                check.positive("dimensions.height", room.dimensions.height);
+                check.positive("dimensions.depth", room.dimensions.depth);
+                for (wall_index, wall_spec) in room.walls.iter().enumerate() {
+                    check.features(
+                        &format!("walls[{}].features", wall_index),
+                        &room_wall_segment(room, &wall_spec.side),
+                        &wall_spec.features,
+                    );
+                }
+            }
+            Element::Wall(wall) => {
+                let segment = WallSegment::from(wall);
+                check.positive("end", segment.length());
+                check.positive("height", wall.height);
+                check.positive("thickness", wall.thickness);
+                check.features("features", &segment, &wall.features);
+            }
+            Element::Floor(floor) => {
+                check.positive("dimensions[0]", floor.dimensions.x);
+                check.positive("dimensions[1]", floor.dimensions.y);
+            }
+            Element::Ceiling(ceiling) => {
+                check.positive("dimensions[0]", ceiling.dimensions.x);
+                check.positive("dimensions[1]", ceiling.dimensions.y);
+            }
+            Element::Stairs(stairs) => {
+                if stairs.steps == 0 {
+                    check.report("steps", ValidationErrorKind::NoSteps);
+                }
+                check.positive("width", stairs.width);
+                check.positive("step_height", stairs.step_height);
+                check.positive("step_depth", stairs.step_depth);
+            }
+            Element::Column(column) => {
+                check.positive("radius", column.radius);
+                check.positive("height", column.height);
+            }
+            Element::Furniture(furniture) => {
+                for (field, value) in furniture_dimensions(&furniture.furniture_type) {
+                    check.positive(&format!("furniture_type.{}", field), value);
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+/// Check the cameras of `viewpoints`, which are in the scene's `viewpoints`
+/// or saved alongside it.
+pub fn validate_viewpoints(viewpoints: &[Viewpoint]) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
+    for (index, viewpoint) in viewpoints.iter().enumerate() {
+        let camera = &viewpoint.camera;
+        let mut report = |field: &str, kind| {
+            errors.push(ValidationError {
+                element: None,
+                path: format!("viewpoints[{}].{}", index, field),
+                kind,
+            })
+        };
+        if camera.fov <= 0.0 || camera.fov.is_nan() {
+            report("fov", ValidationErrorKind::NonPositiveDimension { value: camera.fov });
+        }
+        if camera.position.distance_squared(camera.target) < TOLERANCE * TOLERANCE {
+            report("target", ValidationErrorKind::CameraWithoutDirection);
+        }
+    }
+    errors
+}
+
+/// Collects the problems of one element under its JSON path.
+struct Checker<'a> {
+    element: usize,
+    path: String,
+    errors: &'a mut Vec<ValidationError>,
+}
+
+impl Checker<'_> {
+    fn report(&mut self, field: &str, kind: ValidationErrorKind) {
+        self.errors.push(ValidationError {
+            element: Some(self.element),
+            path: format!("{}.{}", self.path, field),
+            kind,
+        });
+    }
+
+    fn positive(&mut self, field: &str, value: f32) {
+        // NaN is reported too
+        if value <= 0.0 || value.is_nan() {
+            self.report(field, ValidationErrorKind::NonPositiveDimension { value });
+        }
+    }
+
+    /// Check the windows and doors `features` of the wall `segment`.
+    fn features(&mut self, field: &str, segment: &WallSegment, features: &[WallFeature]) {
+        let half_length = segment.length() * 0.5;
+        let openings: Vec<Opening> = features.iter().map(Opening::from_feature).collect();
+
+        for (index, (feature, opening)) in features.iter().zip(&openings).enumerate() {
+            let path = format!("{}[{}]", field, index);
+            let size = feature.size();
+            self.positive(&format!("{}.size[0]", path), size.x);
+            self.positive(&format!("{}.size[1]", path), size.y);
+
+            if opening.min.x < -half_length - TOLERANCE
+                || opening.max.x > half_length + TOLERANCE
+                || opening.min.y < -TOLERANCE
+                || opening.max.y > segment.height + TOLERANCE
+            {
+                self.report(
+                    &path,
+                    ValidationErrorKind::FeatureOutsideWall {
+                        wall_length: segment.length(),
+                        wall_height: segment.height,
+                    },
+                );
+            }
+
+            if let WallFeature::Door(door) = feature {
+                if door.position.y.abs() > TOLERANCE {
+                    self.report(
+                        &format!("{}.position[1]", path),
+                        ValidationErrorKind::DoorAboveFloor { threshold: door.position.y },
+                    );
+                }
+            }
+
+            if let Some(other) = openings[..index].iter().position(|earlier| overlaps(earlier, opening)) {
+                self.report(&path, ValidationErrorKind::OverlappingFeatures { other });
+            }
+        }
+    }
+}
+
+fn overlaps(a: &Opening, b: &Opening) -> bool {
+    let min = a.min.max(b.min);
+    let max = a.max.min(b.max);
+    max.x - min.x > TOLERANCE && max.y - min.y > TOLERANCE
+}
+
+/// Named sizes of a piece of furniture, as they appear in its JSON.
+fn furniture_dimensions(furniture_type: &FurnitureType) -> Vec<(&'static str, f32)> {
+    match *furniture_type {
+        FurnitureType::Table { width, depth, height }
+        | FurnitureType::Cabinet { width, depth, height }
+        | FurnitureType::Shelf { width, depth, height, .. }
+        | FurnitureType::Wardrobe { width, depth, height }
+        | FurnitureType::KitchenCounter { width, depth, height }
+        | FurnitureType::Fridge { width, depth, height } => {
+            vec![("width", width), ("depth", depth), ("height", height)]
+        }
+        FurnitureType::Chair { seat_height } => vec![("seat_height", seat_height)],
+        FurnitureType::Sofa { width, depth }
+        | FurnitureType::Desk { width, depth }
+        | FurnitureType::Sink { width, depth } => vec![("width", width), ("depth", depth)],
+        FurnitureType::Bed { width, length } | FurnitureType::Bathtub { width, length } => {
+            vec![("width", width), ("length", length)]
+        }
+        FurnitureType::Toilet => Vec::new(),
+    }
+}
@@ -0,0 +1,71 @@
+use super::loader::{inline_scalar_arrays, SceneError};
+use super::validation::validate_viewpoints;
+use super::{Scene, Viewpoint};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// End of the name of the file next to a scene that viewpoints saved for it
+/// are kept in, as in `7_multi_level.views.json`.
+pub const VIEWPOINTS_SUFFIX: &str = ".views.json";
+
+#[derive(Serialize, Deserialize)]
+struct ViewpointsFile {
+    viewpoints: Vec<Viewpoint>,
+}
+
+/// Path of the file viewpoints saved for the scene at `scene_path` are kept in.
+pub fn viewpoints_path(scene_path: &Path) -> PathBuf {
+    let stem = scene_path.file_stem().unwrap_or_default().to_string_lossy();
+    scene_path.with_file_name(format!("{}{}", stem, VIEWPOINTS_SUFFIX))
+}
+
+/// Whether `path` is a file of saved viewpoints rather than a scene.
+pub fn is_viewpoints_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(VIEWPOINTS_SUFFIX))
+}
+
+/// The viewpoints saved for the scene at `scene_path`, or none if there is
+/// no file of them.
+pub fn load_saved_viewpoints(scene_path: &Path) -> Result<Vec<Viewpoint>, SceneError> {
+    let path = viewpoints_path(scene_path);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => return Err(SceneError::Io { path, source }),
+    };
+    let file: ViewpointsFile = match serde_json::from_str(&contents) {
+        Ok(file) => file,
+        Err(source) => return Err(SceneError::Parse { path, source }),
+    };
+
+    let errors = validate_viewpoints(&file.viewpoints);
+    if !errors.is_empty() {
+        return Err(SceneError::Invalid { path, errors });
+    }
+    Ok(file.viewpoints)
+}
+
+/// Replace the viewpoints saved for the scene at `scene_path` with `viewpoints`.
+pub fn save_viewpoints(scene_path: &Path, viewpoints: &[Viewpoint]) -> Result<(), SceneError> {
+    let file = ViewpointsFile {
+        viewpoints: viewpoints.to_vec(),
+    };
+    let json = serde_json::to_string_pretty(&file).expect("viewpoints serialize to JSON");
+    let mut contents = inline_scalar_arrays(&json);
+    contents.push('\n');
+
+    let path = viewpoints_path(scene_path);
+    fs::write(&path, contents).map_err(|source| SceneError::Io { path, source })
+}
+
+/// The viewpoints of `scene`, loaded from `scene_path`, followed by those
+/// saved for it.
+pub fn scene_viewpoints(scene_path: &Path, scene: &Scene) -> Result<Vec<Viewpoint>, SceneError> {
+    let mut viewpoints = scene.viewpoints.clone();
+    viewpoints.extend(load_saved_viewpoints(scene_path)?);
+    Ok(viewpoints)
+}
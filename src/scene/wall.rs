@@ -0,0 +1,255 @@
+use super::{Wall, WallFeature, WindowFeature, DoorFeature};
+use super::primitives::{Mesh, Vertex};
+use glam::{Mat4, Quat, Vec2, Vec3};
+
+/// Straight wall running from `start` to `end` on the floor plan.
+///
+/// The segment is measured in the XZ plane; the wall stands on the lower of
+/// the two endpoint elevations so a slightly uneven segment never floats.
+#[derive(Debug, Clone, Copy)]
+pub struct WallSegment {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub height: f32,
+    pub thickness: f32,
+    pub ends: WallEnds,
+}
+
+impl WallSegment {
+    pub fn new(start: Vec3, end: Vec3, height: f32, thickness: f32) -> Self {
+        Self { start, end, height, thickness, ends: WallEnds::default() }
+    }
+
+    pub fn with_ends(mut self, ends: WallEnds) -> Self {
+        self.ends = ends;
+        self
+    }
+
+    /// Horizontal length of the segment.
+    pub fn length(&self) -> f32 {
+        Vec2::new(self.end.x - self.start.x, self.end.z - self.start.z).length()
+    }
+
+    /// Unit direction from `start` to `end` in the XZ plane.
+    pub fn direction(&self) -> Vec3 {
+        Vec3::new(self.end.x - self.start.x, 0.0, self.end.z - self.start.z).normalize_or_zero()
+    }
+
+    pub fn base_elevation(&self) -> f32 {
+        self.start.y.min(self.end.y)
+    }
+
+    /// Matrix taking the wall's local frame (see [`create_wall_mesh`]) into
+    /// world space, with local X along `start → end`.
+    pub fn local_to_world(&self) -> Mat4 {
+        let direction = self.direction();
+        let yaw = (-direction.z).atan2(direction.x);
+        let base = Vec3::new(
+            (self.start.x + self.end.x) * 0.5,
+            self.base_elevation(),
+            (self.start.z + self.end.z) * 0.5,
+        );
+        Mat4::from_rotation_translation(Quat::from_rotation_y(yaw), base)
+    }
+
+    pub fn to_mesh(&self, features: &[WallFeature]) -> Mesh {
+        let mut mesh = create_wall_mesh(self.length(), self.height, self.thickness, self.ends, features);
+        mesh.transform(self.local_to_world());
+        mesh
+    }
+}
+
+impl From<&Wall> for WallSegment {
+    fn from(wall: &Wall) -> Self {
+        Self::new(wall.start, wall.end, wall.height, wall.thickness)
+    }
+}
+
+/// How far each face of the wall reaches past the segment's endpoints.
+///
+/// "Front" is the local +Z face, i.e. the left-hand side when walking from
+/// `start` to `end`. Positive values extend the face beyond the endpoint,
+/// negative values trim it back; a square butt end is all zeros.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WallEnds {
+    pub start_front: f32,
+    pub start_back: f32,
+    pub end_front: f32,
+    pub end_back: f32,
+}
+
+/// Rectangular hole in wall-local coordinates: `x` runs along the wall from
+/// its centre, `y` is the height above the wall base.
+#[derive(Debug, Clone, Copy)]
+pub struct Opening {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Opening {
+    pub fn from_feature(feature: &WallFeature) -> Self {
+        match feature {
+            WallFeature::Window(window) => Self::from_window(window),
+            WallFeature::Door(door) => Self::from_door(door),
+        }
+    }
+
+    fn from_window(window: &WindowFeature) -> Self {
+        // `position.y` is the centre of the window unless an explicit sill is given
+        let bottom = window
+            .sill_height
+            .unwrap_or(window.position.y - window.size.y * 0.5);
+        Self {
+            min: Vec2::new(window.position.x - window.size.x * 0.5, bottom),
+            max: Vec2::new(window.position.x + window.size.x * 0.5, bottom + window.size.y),
+        }
+    }
+
+    fn from_door(door: &DoorFeature) -> Self {
+        // Doors stand on the floor: `position.y` is the threshold height
+        Self {
+            min: Vec2::new(door.position.x - door.size.x * 0.5, door.position.y),
+            max: Vec2::new(door.position.x + door.size.x * 0.5, door.position.y + door.size.y),
+        }
+    }
+
+    /// Clamp the opening to the wall face, returning `None` if nothing is left.
+    fn clamped(&self, min_x: f32, max_x: f32, height: f32) -> Option<Self> {
+        let min = self.min.max(Vec2::new(min_x, 0.0));
+        let max = self.max.min(Vec2::new(max_x, height));
+        if max.x - min.x > f32::EPSILON && max.y - min.y > f32::EPSILON {
+            Some(Self { min, max })
+        } else {
+            None
+        }
+    }
+}
+
+/// Build a wall in its local frame: centred on the origin along X, standing
+/// on `y = 0` and extruded `thickness` along Z.
+///
+/// The wall face is split into a grid along every opening edge; solid cells
+/// produce the front and back panels, and every boundary between a solid cell
+/// and an empty one (an opening or the outside) produces a thickness face, so
+/// jambs, heads and sills get their reveals alongside the end caps. The outer
+/// columns stretch to the per-face extents in `ends`, which turns the end caps
+/// into miters where walls are joined.
+pub fn create_wall_mesh(
+    length: f32,
+    height: f32,
+    thickness: f32,
+    ends: WallEnds,
+    features: &[WallFeature],
+) -> Mesh {
+    let half_t = thickness * 0.5;
+    let left = (-length * 0.5 - ends.start_front, -length * 0.5 - ends.start_back);
+    let right = (length * 0.5 + ends.end_front, length * 0.5 + ends.end_back);
+
+    // Trimmed past itself: nothing of this wall is left to draw
+    if right.0 <= left.0 || right.1 <= left.1 {
+        return Mesh::new();
+    }
+
+    // Openings may only occupy the part of the wall where both faces exist
+    let inner_min = left.0.max(left.1);
+    let inner_max = right.0.min(right.1);
+    let openings: Vec<Opening> = features
+        .iter()
+        .filter_map(|f| Opening::from_feature(f).clamped(inner_min, inner_max, height))
+        .collect();
+
+    let xs = breakpoints(
+        inner_min.min(inner_max),
+        inner_max.max(inner_min),
+        openings.iter().flat_map(|o| [o.min.x, o.max.x]),
+    );
+    let ys = breakpoints(0.0, height, openings.iter().flat_map(|o| [o.min.y, o.max.y]));
+
+    let columns = xs.len() - 1;
+    let rows = ys.len() - 1;
+    let solid = |i: isize, j: isize| -> bool {
+        if i < 0 || j < 0 || i as usize >= columns || j as usize >= rows {
+            return false;
+        }
+        let centre = Vec2::new(
+            (xs[i as usize] + xs[i as usize + 1]) * 0.5,
+            (ys[j as usize] + ys[j as usize + 1]) * 0.5,
+        );
+        !openings.iter().any(|o| {
+            centre.x > o.min.x && centre.x < o.max.x && centre.y > o.min.y && centre.y < o.max.y
+        })
+    };
+
+    let mut mesh = Mesh::new();
+
+    for i in 0..columns {
+        // (front, back) x extents of this column
+        let (lf, lb) = if i == 0 { left } else { (xs[i], xs[i]) };
+        let (rf, rb) = if i == columns - 1 { right } else { (xs[i + 1], xs[i + 1]) };
+
+        for j in 0..rows {
+            let (ii, jj) = (i as isize, j as isize);
+            if !solid(ii, jj) {
+                continue;
+            }
+            let (y0, y1) = (ys[j], ys[j + 1]);
+            let front = |x: f32, y: f32| Vec3::new(x, y, half_t);
+            let back = |x: f32, y: f32| Vec3::new(x, y, -half_t);
+
+            add_face(&mut mesh, [front(lf, y0), front(rf, y0), front(rf, y1), front(lf, y1)]);
+            add_face(&mut mesh, [back(rb, y0), back(lb, y0), back(lb, y1), back(rb, y1)]);
+
+            // Left side: end cap or the jamb of an opening to the left
+            if !solid(ii - 1, jj) {
+                add_face(&mut mesh, [back(lb, y0), front(lf, y0), front(lf, y1), back(lb, y1)]);
+            }
+            // Right side
+            if !solid(ii + 1, jj) {
+                add_face(&mut mesh, [front(rf, y0), back(rb, y0), back(rb, y1), front(rf, y1)]);
+            }
+            // Top: wall top or the sill of an opening above
+            if !solid(ii, jj + 1) {
+                add_face(&mut mesh, [front(lf, y1), front(rf, y1), back(rb, y1), back(lb, y1)]);
+            }
+            // Bottom: wall base or the head of an opening below
+            if !solid(ii, jj - 1) {
+                add_face(&mut mesh, [back(lb, y0), back(rb, y0), front(rf, y0), front(lf, y0)]);
+            }
+        }
+    }
+
+    mesh
+}
+
+/// `start`, the de-duplicated cuts strictly between the two, then `end`.
+fn breakpoints(start: f32, end: f32, cuts: impl Iterator<Item = f32>) -> Vec<f32> {
+    const TOLERANCE: f32 = 1e-4;
+    let mut inner: Vec<f32> = cuts
+        .filter(|&c| c > start + TOLERANCE && c < end - TOLERANCE)
+        .collect();
+    inner.sort_by(|a, b| a.total_cmp(b));
+    inner.dedup_by(|a, b| (*a - *b).abs() < TOLERANCE);
+
+    let mut values = Vec::with_capacity(inner.len() + 2);
+    values.push(start);
+    values.extend(inner);
+    values.push(end);
+    values
+}
+
+/// Add a planar quad. Corners wind counter-clockwise when seen from outside,
+/// which also fixes the face normal.
+fn add_face(mesh: &mut Mesh, corners: [Vec3; 4]) {
+    let u = corners[1] - corners[0];
+    let v = corners[3] - corners[0];
+    let normal = u.cross(v).normalize_or_zero();
+    if normal == Vec3::ZERO {
+        return;
+    }
+    let (w, h) = (u.length(), v.length());
+    let v0 = mesh.add_vertex(Vertex::new(corners[0], normal, Vec2::new(0.0, h)));
+    let v1 = mesh.add_vertex(Vertex::new(corners[1], normal, Vec2::new(w, h)));
+    let v2 = mesh.add_vertex(Vertex::new(corners[2], normal, Vec2::new(w, 0.0)));
+    let v3 = mesh.add_vertex(Vertex::new(corners[3], normal, Vec2::new(0.0, 0.0)));
+    mesh.add_quad(v0, v1, v2, v3);
+}
@@ -0,0 +1,557 @@
+use super::{Element, Room, Scene, WallFeature, WallSide};
+use super::primitives::{Mesh, Vertex};
+use super::material::MaterialLibrary;
+use super::wall::{Opening, WallSegment};
+use glam::{Mat4, Vec2, Vec3};
+
+/// Thickness of the walls generated for `Room` elements, which don't specify one.
+pub const ROOM_WALL_THICKNESS: f32 = 0.2;
+
+/// Distance under which points and lines are treated as coincident.
+const TOLERANCE: f32 = 1e-3;
+
+/// Longest miter allowed, as a multiple of the thicker wall. Sharper corners
+/// fall back to square butt ends instead of growing long spikes.
+const MAX_MITER: f32 = 4.0;
+
+/// A wall after merging and splitting, ready to be meshed.
+#[derive(Debug, Clone)]
+pub struct GraphWall {
+    pub segment: WallSegment,
+    pub features: Vec<WallFeature>,
+    pub material: Option<String>,
+    /// Index of the scene element that declared the wall. Merged walls
+    /// belong to the element declared first.
+    pub element: Option<usize>,
+}
+
+/// Filler for the gap left in the middle of a junction of three or more walls.
+#[derive(Debug, Clone)]
+pub struct Hub {
+    /// Outline in the XZ plane.
+    pub outline: Vec<Vec2>,
+    pub elevation: f32,
+    pub height: f32,
+    pub material: Option<String>,
+    /// Element of the first wall meeting at the hub.
+    pub element: Option<usize>,
+}
+
+/// Every wall of a scene, joined into a single graph.
+///
+/// Building the graph merges walls that lie on the same line (such as the
+/// party wall of two adjacent rooms), splits walls wherever another wall ends
+/// on or crosses them, and then miters the wall ends meeting at each node so
+/// L, T and X junctions close without overlapping geometry.
+#[derive(Debug, Clone)]
+pub struct WallGraph {
+    pub walls: Vec<GraphWall>,
+    pub hubs: Vec<Hub>,
+}
+
+impl WallGraph {
+    /// Graph of the walls of every `Room` and `Wall` element.
+    ///
+    /// Elements moved or turned about the vertical axis join the graph at
+    /// their final place. Elements with any other transform can't keep
+    /// upright, joinable walls, so they are left out and meshed on their own
+    /// by `scene_to_mesh`.
+    pub fn from_scene(scene: &Scene) -> Self {
+        let mut walls = Vec::new();
+        for (index, element) in scene.elements.iter().enumerate() {
+            if !joins_scene_graph(element) {
+                continue;
+            }
+            let matrix = element.transform_matrix();
+            walls.extend(element_walls(element).into_iter().map(|mut wall| {
+                wall.element = Some(index);
+                match matrix {
+                    Some(matrix) => wall.transformed(matrix),
+                    None => wall,
+                }
+            }));
+        }
+        Self::from_walls(walls)
+    }
+
+    pub fn from_walls(walls: Vec<GraphWall>) -> Self {
+        let walls = merge_collinear(walls);
+        let mut walls = split_at_junctions(walls);
+        let hubs = join_ends(&mut walls);
+        Self { walls, hubs }
+    }
+
+    pub fn to_mesh(&self, materials: &MaterialLibrary) -> Mesh {
+        self.mesh_where(materials, |_| true)
+    }
+
+    /// Mesh of the walls and hubs belonging to scene element `element`.
+    pub fn element_mesh(&self, materials: &MaterialLibrary, element: usize) -> Mesh {
+        self.mesh_where(materials, |owner| owner == Some(element))
+    }
+
+    fn mesh_where(&self, materials: &MaterialLibrary, belongs: impl Fn(Option<usize>) -> bool) -> Mesh {
+        let mut mesh = Mesh::new();
+        for wall in self.walls.iter().filter(|wall| belongs(wall.element)) {
+            let mut wall_mesh = wall.segment.to_mesh(&wall.features);
+            wall_mesh.set_material(materials.id(wall.material.as_deref()));
+            mesh.append(&wall_mesh);
+        }
+        for hub in self.hubs.iter().filter(|hub| belongs(hub.element)) {
+            let mut hub_mesh = hub_mesh(hub);
+            hub_mesh.set_material(materials.id(hub.material.as_deref()));
+            mesh.append(&hub_mesh);
+        }
+        mesh
+    }
+}
+
+impl GraphWall {
+    /// Move the wall's endpoints by a matrix that keeps walls upright.
+    fn transformed(mut self, matrix: Mat4) -> Self {
+        self.segment.start = matrix.transform_point3(self.segment.start);
+        self.segment.end = matrix.transform_point3(self.segment.end);
+        self
+    }
+}
+
+/// Whether an element's walls take part in the scene-wide wall graph.
+pub fn joins_scene_graph(element: &Element) -> bool {
+    element.transform().is_none_or(|transform| transform.is_planar_rigid())
+}
+
+/// Walls declared by a single element, before its transform.
+pub fn element_walls(element: &Element) -> Vec<GraphWall> {
+    match element {
+        Element::Room(room) => room
+            .walls
+            .iter()
+            .map(|wall_spec| GraphWall {
+                segment: room_wall_segment(room, &wall_spec.side),
+                features: wall_spec.features.clone(),
+                material: room.material.clone(),
+                element: None,
+            })
+            .collect(),
+        Element::Wall(wall) => vec![GraphWall {
+            segment: WallSegment::from(wall),
+            features: wall.features.clone(),
+            material: wall.material.clone(),
+            element: None,
+        }],
+        _ => Vec::new(),
+    }
+}
+
+/// Centreline of one side of a room: the walls run around the room
+/// counter-clockwise when seen from above, and sit on the room boundary.
+pub fn room_wall_segment(room: &Room, side: &WallSide) -> WallSegment {
+    let half_w = room.dimensions.width * 0.5;
+    let half_d = room.dimensions.depth * 0.5;
+    let (start, end) = match side {
+        WallSide::North => (Vec3::new(-half_w, 0.0, half_d), Vec3::new(half_w, 0.0, half_d)),
+        WallSide::South => (Vec3::new(half_w, 0.0, -half_d), Vec3::new(-half_w, 0.0, -half_d)),
+        WallSide::East => (Vec3::new(half_w, 0.0, half_d), Vec3::new(half_w, 0.0, -half_d)),
+        WallSide::West => (Vec3::new(-half_w, 0.0, -half_d), Vec3::new(-half_w, 0.0, half_d)),
+    };
+    WallSegment::new(
+        room.position + start,
+        room.position + end,
+        room.dimensions.height,
+        ROOM_WALL_THICKNESS,
+    )
+}
+
+/// Corners of the centrelines of a room's walls, in the order they run.
+pub fn room_corners(room: &Room) -> [Vec3; 4] {
+    [WallSide::North, WallSide::West, WallSide::South, WallSide::East].map(|side| room_wall_segment(room, &side).start)
+}
+
+fn xz(v: Vec3) -> Vec2 {
+    Vec2::new(v.x, v.z)
+}
+
+/// Left-hand normal of a direction in the XZ plane, matching the wall's front face.
+fn left_of(direction: Vec2) -> Vec2 {
+    Vec2::new(-direction.y, direction.x)
+}
+
+fn cross(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+/// Parameters `(s, t)` where `a + da * s == b + db * t`, if the lines aren't parallel.
+fn intersect_lines(a: Vec2, da: Vec2, b: Vec2, db: Vec2) -> Option<(f32, f32)> {
+    let denom = cross(da, db);
+    if denom.abs() < 1e-4 {
+        return None;
+    }
+    let diff = b - a;
+    Some((cross(diff, db) / denom, cross(diff, da) / denom))
+}
+
+fn same_opening(a: &WallFeature, b: &WallFeature) -> bool {
+    let (oa, ob) = (Opening::from_feature(a), Opening::from_feature(b));
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+        && oa.min.abs_diff_eq(ob.min, TOLERANCE)
+        && oa.max.abs_diff_eq(ob.max, TOLERANCE)
+}
+
+/// Interval of a line covered by one input wall.
+struct Span {
+    from: f32,
+    to: f32,
+    height: f32,
+    thickness: f32,
+    material: Option<String>,
+    element: Option<usize>,
+}
+
+/// Walls sharing a line and base elevation, measured along `direction`.
+struct Collinear {
+    direction: Vec2,
+    offset: f32,
+    elevation: f32,
+    spans: Vec<Span>,
+    /// Features with their position along the line.
+    features: Vec<(f32, WallFeature)>,
+}
+
+impl Collinear {
+    fn point(&self, t: f32) -> Vec3 {
+        let p = self.direction * t + left_of(self.direction) * self.offset;
+        Vec3::new(p.x, self.elevation, p.y)
+    }
+}
+
+/// Replace overlapping walls on the same line by their union.
+///
+/// Where overlapping walls differ, the taller and thicker one wins; runs with
+/// different heights or thicknesses stay separate walls meeting end to end.
+/// Features keep their place along the line, and one that falls in a gap
+/// between runs goes to the nearest run.
+fn merge_collinear(walls: Vec<GraphWall>) -> Vec<GraphWall> {
+    let mut lines: Vec<Collinear> = Vec::new();
+
+    for wall in walls {
+        let segment = wall.segment;
+        let mut direction = xz(segment.direction());
+        if direction == Vec2::ZERO {
+            continue;
+        }
+        // Canonical direction so opposite walls on one line compare equal
+        let flipped = direction.x < -TOLERANCE || (direction.x.abs() <= TOLERANCE && direction.y < 0.0);
+        if flipped {
+            direction = -direction;
+        }
+        let (start, end) = (xz(segment.start), xz(segment.end));
+        let offset = start.dot(left_of(direction));
+        let elevation = segment.base_elevation();
+
+        let index = match lines.iter().position(|line| {
+            line.direction.abs_diff_eq(direction, TOLERANCE)
+                && (line.offset - offset).abs() < TOLERANCE
+                && (line.elevation - elevation).abs() < TOLERANCE
+        }) {
+            Some(index) => index,
+            None => {
+                lines.push(Collinear { direction, offset, elevation, spans: Vec::new(), features: Vec::new() });
+                lines.len() - 1
+            }
+        };
+        let line = &mut lines[index];
+
+        let (a, b) = (start.dot(direction), end.dot(direction));
+        let centre = (a + b) * 0.5;
+        let sign = if flipped { -1.0 } else { 1.0 };
+        line.spans.push(Span {
+            from: a.min(b),
+            to: a.max(b),
+            height: segment.height,
+            thickness: segment.thickness,
+            material: wall.material,
+            element: wall.element,
+        });
+        for feature in wall.features {
+            line.features.push((centre + sign * feature.position().x, feature));
+        }
+    }
+
+    let mut merged = Vec::new();
+    for line in &lines {
+        let mut breaks: Vec<f32> = line.spans.iter().flat_map(|s| [s.from, s.to]).collect();
+        breaks.sort_by(|a, b| a.total_cmp(b));
+        breaks.dedup_by(|a, b| (*a - *b).abs() < TOLERANCE);
+
+        let mut runs: Vec<Span> = Vec::new();
+        for window in breaks.windows(2) {
+            let middle = (window[0] + window[1]) * 0.5;
+            let mut covering = line.spans.iter().filter(|s| s.from < middle && middle < s.to);
+            // The first wall declared here decides the material and owner
+            let Some(first) = covering.next() else {
+                continue;
+            };
+            let (height, thickness) = covering.fold((first.height, first.thickness), |(h, t), s| {
+                (h.max(s.height), t.max(s.thickness))
+            });
+            if let Some(last) = runs.last_mut() {
+                if (last.to - window[0]).abs() < TOLERANCE
+                    && (last.height - height).abs() < TOLERANCE
+                    && (last.thickness - thickness).abs() < TOLERANCE
+                    && last.material == first.material
+                    && last.element == first.element
+                {
+                    last.to = window[1];
+                    continue;
+                }
+            }
+            runs.push(Span {
+                from: window[0],
+                to: window[1],
+                height,
+                thickness,
+                material: first.material.clone(),
+                element: first.element,
+            });
+        }
+
+        // Each feature goes to the run it lies on, or to the nearest run if it
+        // falls in a gap between them, so no opening is lost
+        let mut run_features: Vec<Vec<WallFeature>> = vec![Vec::new(); runs.len()];
+        for (t, feature) in &line.features {
+            let distance = |run: &Span| (run.from - t).max(t - run.to).max(0.0);
+            let Some(nearest) = (0..runs.len()).min_by(|&a, &b| distance(&runs[a]).total_cmp(&distance(&runs[b]))) else {
+                continue;
+            };
+            let run = &runs[nearest];
+            let mut feature = feature.clone();
+            feature.set_position(Vec2::new(t - (run.from + run.to) * 0.5, feature.position().y));
+            // Both rooms of a party wall usually declare the same opening
+            let features = &mut run_features[nearest];
+            if !features.iter().any(|f| same_opening(f, &feature)) {
+                features.push(feature);
+            }
+        }
+
+        for (run, features) in runs.into_iter().zip(run_features) {
+            merged.push(GraphWall {
+                segment: WallSegment::new(line.point(run.from), line.point(run.to), run.height, run.thickness),
+                features,
+                material: run.material,
+                element: run.element,
+            });
+        }
+    }
+    merged
+}
+
+/// Distance along `segment` from its start to `point`, if the point lies on
+/// the centreline strictly between the endpoints.
+fn interior_distance(segment: &WallSegment, point: Vec2) -> Option<f32> {
+    let direction = xz(segment.direction());
+    let offset = point - xz(segment.start);
+    let along = offset.dot(direction);
+    let across = offset.dot(left_of(direction));
+    (across.abs() < TOLERANCE && along > TOLERANCE && along < segment.length() - TOLERANCE).then_some(along)
+}
+
+/// Split walls at every point where another wall ends on or crosses them, so
+/// T and X junctions become nodes shared by wall ends.
+fn split_at_junctions(walls: Vec<GraphWall>) -> Vec<GraphWall> {
+    let mut cuts: Vec<Vec<f32>> = vec![Vec::new(); walls.len()];
+    for (i, a) in walls.iter().enumerate() {
+        for (j, b) in walls.iter().enumerate() {
+            let (sa, sb) = (&a.segment, &b.segment);
+            if i == j || (sa.base_elevation() - sb.base_elevation()).abs() > TOLERANCE {
+                continue;
+            }
+            for end in [sb.start, sb.end] {
+                cuts[i].extend(interior_distance(sa, xz(end)));
+            }
+            let crossing = intersect_lines(xz(sa.start), xz(sa.direction()), xz(sb.start), xz(sb.direction()));
+            if let Some((s, t)) = crossing {
+                let inside = |d: f32, l: f32| d > TOLERANCE && d < l - TOLERANCE;
+                if inside(s, sa.length()) && inside(t, sb.length()) {
+                    cuts[i].push(s);
+                }
+            }
+        }
+    }
+
+    let mut split = Vec::new();
+    for (wall, mut wall_cuts) in walls.into_iter().zip(cuts) {
+        if wall_cuts.is_empty() {
+            split.push(wall);
+            continue;
+        }
+        let segment = wall.segment;
+        let length = segment.length();
+        let direction = segment.direction();
+        let base = Vec3::new(segment.start.x, segment.base_elevation(), segment.start.z);
+
+        wall_cuts.sort_by(|a, b| a.total_cmp(b));
+        wall_cuts.dedup_by(|a, b| (*a - *b).abs() < TOLERANCE);
+        let mut stops = vec![0.0];
+        stops.extend(wall_cuts);
+        stops.push(length);
+
+        let mut taken = vec![false; wall.features.len()];
+        for window in stops.windows(2) {
+            let centre = (window[0] + window[1]) * 0.5;
+            let mut features = Vec::new();
+            for (i, feature) in wall.features.iter().enumerate() {
+                let along = length * 0.5 + feature.position().x;
+                if taken[i] || along < window[0] - TOLERANCE || along > window[1] + TOLERANCE {
+                    continue;
+                }
+                taken[i] = true;
+                let mut feature = feature.clone();
+                feature.set_position(Vec2::new(along - centre, feature.position().y));
+                features.push(feature);
+            }
+            split.push(GraphWall {
+                segment: WallSegment::new(
+                    base + direction * window[0],
+                    base + direction * window[1],
+                    segment.height,
+                    segment.thickness,
+                ),
+                features,
+                material: wall.material.clone(),
+                element: wall.element,
+            });
+        }
+    }
+    split
+}
+
+/// One wall end arriving at a node.
+struct WallEnd {
+    wall: usize,
+    at_start: bool,
+    /// Direction from the node into the wall.
+    direction: Vec2,
+    half_thickness: f32,
+    height: f32,
+}
+
+/// Miter the wall ends meeting at each node, returning hubs for the nodes
+/// where three or more walls meet.
+///
+/// Walking around a node counter-clockwise, the left face of each wall end
+/// is intersected with the right face of the next one; each face is then
+/// extended or trimmed to that point. With two walls this is a plain miter,
+/// with more it leaves a convex gap in the middle that the hub closes.
+fn join_ends(walls: &mut [GraphWall]) -> Vec<Hub> {
+    let mut nodes: Vec<(Vec3, Vec<WallEnd>)> = Vec::new();
+    for (i, wall) in walls.iter().enumerate() {
+        let segment = &wall.segment;
+        let direction = xz(segment.direction());
+        let elevation = segment.base_elevation();
+        for (at_start, point, direction) in [
+            (true, segment.start, direction),
+            (false, segment.end, -direction),
+        ] {
+            let point = Vec3::new(point.x, elevation, point.z);
+            let end = WallEnd {
+                wall: i,
+                at_start,
+                direction,
+                half_thickness: segment.thickness * 0.5,
+                height: segment.height,
+            };
+            match nodes.iter_mut().find(|(p, _)| p.distance(point) < TOLERANCE) {
+                Some((_, ends)) => ends.push(end),
+                None => nodes.push((point, vec![end])),
+            }
+        }
+    }
+
+    let mut hubs = Vec::new();
+    for (point, mut ends) in nodes {
+        if ends.len() < 2 {
+            continue;
+        }
+        ends.sort_by(|a, b| a.direction.y.atan2(a.direction.x).total_cmp(&b.direction.y.atan2(b.direction.x)));
+
+        let node = xz(point);
+        let mut outline: Vec<Vec2> = Vec::new();
+        for k in 0..ends.len() {
+            let a = &ends[k];
+            let b = &ends[(k + 1) % ends.len()];
+            let a_face = node + left_of(a.direction) * a.half_thickness;
+            let b_face = node - left_of(b.direction) * b.half_thickness;
+            let limit = MAX_MITER * 2.0 * a.half_thickness.max(b.half_thickness);
+
+            match intersect_lines(a_face, a.direction, b_face, b.direction) {
+                Some((s, t)) if s.abs() < limit && t.abs() < limit => {
+                    set_extension(&mut walls[a.wall].segment, a.at_start, true, -s);
+                    set_extension(&mut walls[b.wall].segment, b.at_start, false, -t);
+                    outline.push(a_face + a.direction * s);
+                }
+                // Parallel or too sharp: leave both faces square
+                _ => outline.extend([a_face, b_face]),
+            }
+        }
+
+        if ends.len() >= 3 {
+            outline.dedup_by(|a, b| a.distance(*b) < TOLERANCE);
+            if outline.len() >= 3 {
+                hubs.push(Hub {
+                    outline,
+                    elevation: point.y,
+                    height: ends.iter().map(|e| e.height).fold(f32::INFINITY, f32::min),
+                    material: walls[ends[0].wall].material.clone(),
+                    element: walls[ends[0].wall].element,
+                });
+            }
+        }
+    }
+    hubs
+}
+
+/// Set the extension of the face on the left or right of a wall end, as seen
+/// from the node looking into the wall.
+fn set_extension(segment: &mut WallSegment, at_start: bool, left: bool, extension: f32) {
+    let ends = &mut segment.ends;
+    match (at_start, left) {
+        (true, true) => ends.start_front = extension,
+        (true, false) => ends.start_back = extension,
+        (false, true) => ends.end_back = extension,
+        (false, false) => ends.end_front = extension,
+    }
+}
+
+fn hub_mesh(hub: &Hub) -> Mesh {
+    let mut mesh = Mesh::new();
+    // The outline is collected counter-clockwise in XZ, which faces down
+    for (y, normal, reverse) in [
+        (hub.elevation, -Vec3::Y, false),
+        (hub.elevation + hub.height, Vec3::Y, true),
+    ] {
+        let first = mesh.vertices.len() as u32;
+        let mut points: Vec<Vec2> = hub.outline.clone();
+        if reverse {
+            points.reverse();
+        }
+        for p in &points {
+            mesh.add_vertex(Vertex::new(Vec3::new(p.x, y, p.y), normal, *p));
+        }
+        for i in 1..points.len() as u32 - 1 {
+            mesh.add_triangle(first, first + i, first + i + 1);
+        }
+    }
+
+    // Sides against the wall ends, so the hub is a closed solid of its own
+    let (bottom, top) = (hub.elevation, hub.elevation + hub.height);
+    for (i, &a) in hub.outline.iter().enumerate() {
+        let b = hub.outline[(i + 1) % hub.outline.len()];
+        let corners = [(b, bottom), (a, bottom), (a, top), (b, top)].map(|(p, y)| Vec3::new(p.x, y, p.y));
+        let normal = (corners[1] - corners[0]).cross(corners[2] - corners[0]).normalize_or_zero();
+        let length = a.distance(b);
+        let uvs = [Vec2::new(0.0, hub.height), Vec2::new(length, hub.height), Vec2::new(length, 0.0), Vec2::ZERO];
+        let side = [0, 1, 2, 3].map(|k| mesh.add_vertex(Vertex::new(corners[k], normal, uvs[k])));
+        mesh.add_quad(side[0], side[1], side[2], side[3]);
+    }
+    mesh
+}
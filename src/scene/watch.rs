@@ -0,0 +1,79 @@
+use super::material::MaterialLibrary;
+use super::viewpoints::viewpoints_path;
+use super::Scene;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Notices when a scene file or the other files it uses change on disk, by
+/// polling their modification times.
+///
+/// Files that can't be read are skipped until they reappear, so an editor
+/// replacing a file as it saves is seen as a single change.
+#[derive(Debug, Clone)]
+pub struct SceneWatcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl SceneWatcher {
+    /// Watch the scene file at `scene_path`, and the textures of `scene` if
+    /// it could be loaded.
+    pub fn new(scene_path: &Path, scene: Option<&Scene>) -> Self {
+        let mut watcher = Self { files: Vec::new() };
+        watcher.watch(scene_path, scene);
+        watcher
+    }
+
+    /// Watch `scene_path` and `scene`'s textures instead of the files watched
+    /// so far, starting from their current modification times. Without a
+    /// scene the textures watched so far are kept, so a scene that fails to
+    /// load is still reloaded when one of them changes.
+    pub fn watch(&mut self, scene_path: &Path, scene: Option<&Scene>) {
+        let files = match scene {
+            Some(scene) => scene_dependencies(scene_path, scene),
+            None => {
+                let mut files = vec![scene_path.to_path_buf()];
+                files.extend(self.files.iter().skip(1).map(|(file, _)| file.clone()));
+                files
+            }
+        };
+        self.files = files
+            .into_iter()
+            .map(|file| {
+                let modified = modified(&file);
+                (file, modified)
+            })
+            .collect();
+    }
+
+    /// The watched files, the scene file first.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|(file, _)| file.as_path())
+    }
+
+    /// Whether any watched file has been modified since the last call, or
+    /// since it started being watched.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        for (file, last_modified) in &mut self.files {
+            let Some(modified) = modified(file) else { continue };
+            if *last_modified != Some(modified) {
+                *last_modified = Some(modified);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// The scene file at `scene_path`, the texture files `scene` uses, which are
+/// relative to the scene file's directory, and its saved viewpoints.
+pub fn scene_dependencies(scene_path: &Path, scene: &Scene) -> Vec<PathBuf> {
+    let dir = scene_path.parent().unwrap_or(Path::new(""));
+    let mut files = vec![scene_path.to_path_buf(), viewpoints_path(scene_path)];
+    files.extend(MaterialLibrary::from_scene(scene).textures().into_iter().map(|texture| dir.join(texture)));
+    files
+}
+
+fn modified(file: &Path) -> Option<SystemTime> {
+    std::fs::metadata(file).and_then(|metadata| metadata.modified()).ok()
+}
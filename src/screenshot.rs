@@ -0,0 +1,186 @@
+use crate::camera_controller::CameraPose;
+use glam::Vec3;
+use std::fmt;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+const SCENE_KEY: &str = "Scene";
+const SCENE_HASH_KEY: &str = "Scene Hash";
+const POSITION_KEY: &str = "Camera Position";
+const YAW_KEY: &str = "Camera Yaw";
+const PITCH_KEY: &str = "Camera Pitch";
+const FOV_KEY: &str = "Camera FOV";
+const RESOLUTION_KEY: &str = "Resolution";
+/// The keyword PNG reserves for the program that made the image.
+const SOFTWARE_KEY: &str = "Software";
+
+/// What a screenshot shows and how it was taken, kept in its PNG text
+/// chunks so that the view can be rendered again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenshotInfo {
+    /// Path of the scene file, as it was given.
+    pub scene: String,
+    /// [`scene_hash`] of the scene file, to tell whether it has changed since.
+    pub scene_hash: String,
+    pub pose: CameraPose,
+    pub width: u32,
+    pub height: u32,
+    /// Name and version of the program that rendered the screenshot.
+    pub renderer: String,
+}
+
+impl ScreenshotInfo {
+    /// Info for a screenshot of the scene file at `scene`, rendered by this
+    /// version of the renderer.
+    pub fn new(scene: &str, scene_contents: &[u8], pose: CameraPose, width: u32, height: u32) -> Self {
+        Self {
+            scene: scene.to_string(),
+            scene_hash: scene_hash(scene_contents),
+            pose,
+            width,
+            height,
+            renderer: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        }
+    }
+
+    /// The text chunks recording the info. Angles are in degrees, and numbers
+    /// are written with as many digits as it takes to read them back exactly.
+    fn text_chunks(&self) -> Vec<(&'static str, String)> {
+        let position = self.pose.position;
+        vec![
+            (SCENE_KEY, self.scene.clone()),
+            (SCENE_HASH_KEY, self.scene_hash.clone()),
+            (POSITION_KEY, format!("{},{},{}", position.x, position.y, position.z)),
+            (YAW_KEY, self.pose.yaw.to_degrees().to_string()),
+            (PITCH_KEY, self.pose.pitch.to_degrees().to_string()),
+            (FOV_KEY, self.pose.fov.to_string()),
+            (RESOLUTION_KEY, format!("{}x{}", self.width, self.height)),
+            (SOFTWARE_KEY, self.renderer.clone()),
+        ]
+    }
+
+    /// Info read back from a PNG's text chunks, or `None` if it has no
+    /// camera recorded.
+    fn from_text_chunks(path: &Path, chunks: &[(String, String)]) -> Result<Option<Self>, ScreenshotError> {
+        let text = |key: &'static str| chunks.iter().find(|(keyword, _)| keyword == key).map(|(_, text)| text.as_str());
+        let Some(position) = text(POSITION_KEY) else { return Ok(None) };
+
+        let invalid = |key: &'static str| ScreenshotError::InvalidText {
+            path: path.to_path_buf(),
+            key,
+            value: text(key).unwrap_or_default().to_string(),
+        };
+        let number = |key: &'static str| text(key).and_then(|value| value.parse::<f32>().ok()).ok_or_else(|| invalid(key));
+
+        let coordinates: Vec<f32> = position
+            .split(',')
+            .map(|coordinate| coordinate.trim().parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| invalid(POSITION_KEY))?;
+        let [x, y, z] = coordinates[..] else { return Err(invalid(POSITION_KEY)) };
+        let (width, height) = text(RESOLUTION_KEY)
+            .and_then(|resolution| resolution.split_once('x'))
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+            .ok_or_else(|| invalid(RESOLUTION_KEY))?;
+
+        Ok(Some(Self {
+            scene: text(SCENE_KEY).unwrap_or_default().to_string(),
+            scene_hash: text(SCENE_HASH_KEY).unwrap_or_default().to_string(),
+            pose: CameraPose {
+                position: Vec3::new(x, y, z),
+                yaw: number(YAW_KEY)?.to_radians(),
+                pitch: number(PITCH_KEY)?.to_radians(),
+                fov: number(FOV_KEY)?,
+            },
+            width,
+            height,
+            renderer: text(SOFTWARE_KEY).unwrap_or_default().to_string(),
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub enum ScreenshotError {
+    Io { path: PathBuf, source: std::io::Error },
+    Encoding { path: PathBuf, source: png::EncodingError },
+    Decoding { path: PathBuf, source: png::DecodingError },
+    /// A text chunk of the screenshot info doesn't hold what it should.
+    InvalidText { path: PathBuf, key: &'static str, value: String },
+}
+
+impl fmt::Display for ScreenshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScreenshotError::Io { path, source } => write!(f, "failed to access {}: {}", path.display(), source),
+            ScreenshotError::Encoding { path, source } => write!(f, "failed to write {}: {}", path.display(), source),
+            ScreenshotError::Decoding { path, source } => write!(f, "failed to read {}: {}", path.display(), source),
+            ScreenshotError::InvalidText { path, key, value } => {
+                write!(f, "{} records an invalid {}: '{}'", path.display(), key, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScreenshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ScreenshotError::Io { source, .. } => Some(source),
+            ScreenshotError::Encoding { source, .. } => Some(source),
+            ScreenshotError::Decoding { source, .. } => Some(source),
+            ScreenshotError::InvalidText { .. } => None,
+        }
+    }
+}
+
+/// Hash of a scene file's contents: 64-bit FNV-1a, in hex. It only needs to
+/// tell versions of a file apart, and stays the same across builds.
+pub fn scene_hash(contents: &[u8]) -> String {
+    let hash = contents
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3));
+    format!("{:016x}", hash)
+}
+
+/// Write `image` to `path` as a PNG, with `info` in its text chunks.
+pub fn save_screenshot(image: &image::RgbaImage, path: &Path, info: &ScreenshotInfo) -> Result<(), ScreenshotError> {
+    let io_error = |source| ScreenshotError::Io { path: path.to_path_buf(), source };
+    let encoding_error = |source| ScreenshotError::Encoding { path: path.to_path_buf(), source };
+
+    let file = File::create(path).map_err(io_error)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), image.width(), image.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    for (key, text) in info.text_chunks() {
+        // tEXt only holds Latin-1, so other text, such as some paths, goes in iTXt
+        if text.chars().all(|c| (c as u32) < 0x100) {
+            encoder.add_text_chunk(key.to_string(), text).map_err(encoding_error)?;
+        } else {
+            encoder.add_itxt_chunk(key.to_string(), text).map_err(encoding_error)?;
+        }
+    }
+
+    let mut writer = encoder.write_header().map_err(encoding_error)?;
+    writer.write_image_data(image.as_raw()).map_err(encoding_error)?;
+    writer.finish().map_err(encoding_error)
+}
+
+/// The info recorded in the screenshot at `path` by [`save_screenshot`], or
+/// `None` if it doesn't have any, as with screenshots from older versions.
+pub fn read_screenshot_info(path: &Path) -> Result<Option<ScreenshotInfo>, ScreenshotError> {
+    let decoding_error = |source| ScreenshotError::Decoding { path: path.to_path_buf(), source };
+
+    let file = File::open(path).map_err(|source| ScreenshotError::Io { path: path.to_path_buf(), source })?;
+    let reader = png::Decoder::new(std::io::BufReader::new(file)).read_info().map_err(decoding_error)?;
+    let info = reader.info();
+
+    let mut chunks: Vec<(String, String)> = info
+        .uncompressed_latin1_text
+        .iter()
+        .map(|chunk| (chunk.keyword.clone(), chunk.text.clone()))
+        .collect();
+    for chunk in &info.utf8_text {
+        chunks.push((chunk.keyword.clone(), chunk.get_text().map_err(decoding_error)?));
+    }
+    ScreenshotInfo::from_text_chunks(path, &chunks)
+}
@@ -1,68 +1,14 @@
-pub const SCENE_SHADER: &str = r#"
-struct CameraUniform {
-    view_proj: mat4x4<f32>,
-    camera_pos: vec4<f32>,
-}
-
-@group(0) @binding(0)
-var<uniform> camera: CameraUniform;
-
-struct VertexInput {
-    @location(0) position: vec3<f32>,
-    @location(1) normal: vec3<f32>,
-    @location(2) tex_coords: vec2<f32>,
-}
-
-struct VertexOutput {
-    @builtin(position) clip_position: vec4<f32>,
-    @location(0) world_normal: vec3<f32>,
-    @location(1) tex_coords: vec2<f32>,
-    @location(2) world_position: vec3<f32>,
-}
-
-@vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
-    var out: VertexOutput;
-    out.clip_position = camera.view_proj * vec4<f32>(in.position, 1.0);
-    out.world_normal = in.normal;
-    out.tex_coords = in.tex_coords;
-    out.world_position = in.position;
-    return out;
-}
-
+/// Scene shader for untextured materials, drawing the measurement grid on
+/// materials flagged for it.
+pub const SCENE_SHADER: &str = concat!(include_str!("../../assets/shaders/scene_common.wgsl"), r#"
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    // Simple lighting - light coming from opposite side of typical camera position
-    // This creates shadows that help understand geometry
-    let light_dir = normalize(vec3<f32>(-0.5, -1.0, 0.5));
-    let ambient = 0.3;
-    let diffuse = max(dot(normalize(in.world_normal), light_dir), 0.0) * 0.7;
-    let light = ambient + diffuse;
-    
-    // Check if this is a floor (normal pointing up AND at ground level)
-    let is_floor = in.world_normal.y > 0.9 && abs(in.world_position.y) < 0.1;
-    
-    // Check if this is a ceiling/roof (normal pointing down AND above 2m)
-    let is_ceiling = in.world_normal.y < -0.9 && in.world_position.y > 2.0;
+    let material = materials[in.material];
+    let alpha = surface_alpha(material.base_color.a, in.world_position, in.world_normal);
     
-    // Calculate distance from camera to fragment
-    let camera_distance = length(camera.camera_pos.xyz - in.world_position);
+    var base_color = material.base_color.rgb;
     
-    // Calculate transparency based on distance and type
-    var alpha = 1.0;
-    if (is_ceiling) {
-        // Fade ceiling when camera is close (within 15 meters)
-        if (camera_distance < 15.0) {
-            alpha = smoothstep(5.0, 15.0, camera_distance);
-        }
-    }
-    
-    var base_color = vec3<f32>(0.9, 0.9, 0.9);  // Default wall color
-    
-    if (is_floor) {
-        // Floor base color
-        base_color = vec3<f32>(0.85, 0.82, 0.78);
-        
+    if ((material.flags & MATERIAL_FLAG_GRID) != 0u) {
         // Grid parameters
         let grid_size = 1.0; // 1 meter grid
         let line_width = 0.02;
@@ -107,6 +53,30 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         }
     }
     
-    return vec4<f32>(base_color * light, alpha);
+    return vec4<f32>(shade(material, base_color, in.world_position, in.world_normal), alpha);
+}
+"#);
+
+/// Scene shader for textured materials, with the texture at group 2.
+pub const TEXTURED_SHADER: &str = concat!(
+    include_str!("../../assets/shaders/scene_common.wgsl"),
+    include_str!("../../assets/shaders/textured.wgsl"),
+);
+
+/// Depth-only shader rendering the scene mesh from the shadow-casting light.
+pub const SHADOW_SHADER: &str = r#"
+struct ShadowUniform {
+    light_view_proj: mat4x4<f32>,
+    light_index: u32,
+    depth_bias: f32,
+    texel_size: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> shadow: ShadowUniform;
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return shadow.light_view_proj * vec4<f32>(position, 1.0);
 }
-"#;
\ No newline at end of file
+"#;
@@ -0,0 +1,259 @@
+use crate::export::element_name;
+use crate::scene::levels::{level_of, levels};
+use crate::scene::wall::{Opening, WallSegment};
+use crate::scene::wall_graph::{element_walls, joins_scene_graph, room_corners, room_wall_segment, WallGraph};
+use crate::scene::{Element, Scene, WallFeature};
+use crate::utils::polygon_contains;
+use glam::Vec2;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Amounts of building work in one part of a scene, in metres, square metres
+/// and cubic metres.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Quantities {
+    /// Floor area inside the walls of the rooms.
+    #[serde(serialize_with = "rounded")]
+    pub floor_area: f32,
+    /// Area of one side of the walls, measured along their centrelines, less
+    /// the doors and windows in them.
+    #[serde(serialize_with = "rounded")]
+    pub wall_area: f32,
+    /// Net wall area times the walls' thickness.
+    #[serde(serialize_with = "rounded")]
+    pub wall_volume: f32,
+    /// Length of the inside faces of the rooms' walls.
+    #[serde(serialize_with = "rounded")]
+    pub perimeter: f32,
+    /// Doors by `door_type`.
+    pub doors: BTreeMap<&'static str, u32>,
+    pub windows: u32,
+    pub columns: u32,
+    /// Pieces of furniture by `furniture_type`.
+    pub furniture: BTreeMap<&'static str, u32>,
+}
+
+impl Quantities {
+    fn add(&mut self, other: &Quantities) {
+        self.floor_area += other.floor_area;
+        self.wall_area += other.wall_area;
+        self.wall_volume += other.wall_volume;
+        self.perimeter += other.perimeter;
+        for (kind, count) in &other.doors {
+            *self.doors.entry(kind).or_default() += count;
+        }
+        self.windows += other.windows;
+        self.columns += other.columns;
+        for (kind, count) in &other.furniture {
+            *self.furniture.entry(kind).or_default() += count;
+        }
+    }
+
+    /// Add a wall with `features`, less the parts of them inside the wall.
+    fn add_wall(&mut self, segment: &WallSegment, features: &[WallFeature]) {
+        let half_length = segment.length() * 0.5;
+        let openings: f32 = features
+            .iter()
+            .map(|feature| {
+                let opening = Opening::from_feature(feature);
+                let min = opening.min.max(Vec2::new(-half_length, 0.0));
+                let max = opening.max.min(Vec2::new(half_length, segment.height));
+                (max - min).max(Vec2::ZERO).element_product()
+            })
+            .sum();
+        let area = (segment.length() * segment.height - openings).max(0.0);
+        self.wall_area += area;
+        self.wall_volume += area * segment.thickness;
+
+        for feature in features {
+            match feature {
+                WallFeature::Window(_) => self.windows += 1,
+                WallFeature::Door(door) => *self.doors.entry(door.door_type.kind()).or_default() += 1,
+            }
+        }
+    }
+
+    /// Count `element` if it is a column or a piece of furniture.
+    fn add_fitting(&mut self, element: &Element) {
+        match element {
+            Element::Column(_) => self.columns += 1,
+            Element::Furniture(furniture) => *self.furniture.entry(furniture.furniture_type.kind()).or_default() += 1,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomQuantities {
+    /// Name of the room, as in exported files: `room_1`.
+    pub name: String,
+    /// Index of the room in the scene's `elements`.
+    pub element: usize,
+    #[serde(flatten)]
+    pub quantities: Quantities,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LevelQuantities {
+    #[serde(serialize_with = "rounded")]
+    pub elevation: f32,
+    #[serde(flatten)]
+    pub quantities: Quantities,
+    pub rooms: Vec<RoomQuantities>,
+}
+
+/// Quantities of a whole scene, by level and by room.
+#[derive(Debug, Clone, Serialize)]
+pub struct Takeoff {
+    pub scene: String,
+    pub levels: Vec<LevelQuantities>,
+    pub total: Quantities,
+}
+
+/// Measure `scene` for estimating.
+///
+/// Each room counts its own walls, so a wall between two rooms appears in
+/// both, along with the doors in it. Levels count every wall once, after
+/// walls have been joined the way they are built, and every column and piece
+/// of furniture standing on them; rooms count those standing inside them.
+/// Quantities come from the declared sizes, before any scaling transform.
+pub fn takeoff(scene: &Scene) -> Takeoff {
+    let levels = levels(scene);
+    let graph = WallGraph::from_scene(scene);
+    let mut total = Quantities::default();
+
+    let levels = levels
+        .iter()
+        .map(|&elevation| {
+            let on_level = |element: &Element| level_of(&levels, element.base_elevation()) == elevation;
+            let mut level = LevelQuantities {
+                elevation,
+                quantities: Quantities::default(),
+                rooms: Vec::new(),
+            };
+
+            for wall in graph.walls.iter().filter(|wall| level_of(&levels, wall.segment.base_elevation()) == elevation) {
+                level.quantities.add_wall(&wall.segment, &wall.features);
+            }
+
+            for (index, element) in scene.elements.iter().enumerate().filter(|(_, element)| on_level(element)) {
+                // Walls of elements the graph leaves out are measured on their own
+                if !joins_scene_graph(element) {
+                    for wall in element_walls(element) {
+                        level.quantities.add_wall(&wall.segment, &wall.features);
+                    }
+                }
+                level.quantities.add_fitting(element);
+
+                if let Element::Room(room) = element {
+                    let mut quantities = Quantities {
+                        floor_area: room.floor_area(),
+                        perimeter: room.perimeter(),
+                        ..Quantities::default()
+                    };
+                    level.quantities.floor_area += quantities.floor_area;
+                    level.quantities.perimeter += quantities.perimeter;
+                    for wall_spec in &room.walls {
+                        quantities.add_wall(&room_wall_segment(room, &wall_spec.side), &wall_spec.features);
+                    }
+
+                    let matrix = element.transform_matrix().unwrap_or_default();
+                    let outline = room_corners(room).map(|corner| {
+                        let corner = matrix.transform_point3(corner);
+                        Vec2::new(corner.x, corner.z)
+                    });
+                    for fitting in scene.elements.iter().filter(|fitting| on_level(fitting)) {
+                        let position = fitting.transform_matrix().unwrap_or_default().transform_point3(fitting.anchor());
+                        if polygon_contains(&outline, Vec2::new(position.x, position.z)) {
+                            quantities.add_fitting(fitting);
+                        }
+                    }
+
+                    level.rooms.push(RoomQuantities {
+                        name: element_name(element, index),
+                        element: index,
+                        quantities,
+                    });
+                }
+            }
+
+            total.add(&level.quantities);
+            level
+        })
+        .collect();
+
+    Takeoff {
+        scene: scene.name.clone(),
+        levels,
+        total,
+    }
+}
+
+/// Serialize a quantity to three decimals, which also hides the noise of
+/// summing many `f32`s.
+fn rounded<S: serde::Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64((*value as f64 * 1000.0).round() / 1000.0)
+}
+
+/// `takeoff` as a JSON document.
+pub fn takeoff_to_json(takeoff: &Takeoff) -> String {
+    let mut json = serde_json::to_string_pretty(takeoff).expect("takeoffs serialize to JSON");
+    json.push('\n');
+    json
+}
+
+/// `takeoff` as CSV with a row for every room, every level and the whole
+/// scene, told apart by the `scope` column. Doors and furniture get a column
+/// per type found in the scene.
+pub fn takeoff_to_csv(takeoff: &Takeoff) -> String {
+    let door_types: Vec<&str> = takeoff.total.doors.keys().copied().collect();
+    let furniture_types: Vec<&str> = takeoff.total.furniture.keys().copied().collect();
+
+    let mut csv = String::from("scope,level,elevation,name,floor_area,wall_area,wall_volume,perimeter,windows,columns");
+    for door_type in &door_types {
+        write!(csv, ",doors_{}", door_type).unwrap();
+    }
+    for furniture_type in &furniture_types {
+        write!(csv, ",furniture_{}", furniture_type).unwrap();
+    }
+    csv.push('\n');
+
+    let mut row = |scope: &str, level: Option<(usize, f32)>, name: &str, quantities: &Quantities| {
+        let (index, elevation) = match level {
+            Some((index, elevation)) => (index.to_string(), format!("{:.3}", elevation)),
+            None => (String::new(), String::new()),
+        };
+        write!(
+            csv,
+            "{},{},{},{},{:.3},{:.3},{:.3},{:.3},{},{}",
+            scope,
+            index,
+            elevation,
+            name,
+            quantities.floor_area,
+            quantities.wall_area,
+            quantities.wall_volume,
+            quantities.perimeter,
+            quantities.windows,
+            quantities.columns
+        )
+        .unwrap();
+        for door_type in &door_types {
+            write!(csv, ",{}", quantities.doors.get(door_type).copied().unwrap_or(0)).unwrap();
+        }
+        for furniture_type in &furniture_types {
+            write!(csv, ",{}", quantities.furniture.get(furniture_type).copied().unwrap_or(0)).unwrap();
+        }
+        csv.push('\n');
+    };
+
+    for (index, level) in takeoff.levels.iter().enumerate() {
+        for room in &level.rooms {
+            row("room", Some((index, level.elevation)), &room.name, &room.quantities);
+        }
+        row("level", Some((index, level.elevation)), "", &level.quantities);
+    }
+    row("total", None, "", &takeoff.total);
+    csv
+}
@@ -3,4 +3,16 @@ pub fn create_shader_module(device: &wgpu::Device, source: &str, label: &str) ->
         label: Some(label),
         source: wgpu::ShaderSource::Wgsl(source.into()),
     })
-}
\ No newline at end of file
+}
+
+/// Whether `point` lies inside the polygon `outline`.
+pub fn polygon_contains(outline: &[glam::Vec2], point: glam::Vec2) -> bool {
+    let mut inside = false;
+    for (k, &a) in outline.iter().enumerate() {
+        let b = outline[(k + 1) % outline.len()];
+        if (a.y > point.y) != (b.y > point.y) && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
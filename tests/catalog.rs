@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+use webgpu_rust_architecture::scene::catalog::{
+    find_scene, scene_files, scene_name, scene_number, SceneCatalog, EXAMPLES_DIR,
+};
+
+#[test]
+fn examples_are_in_numeric_order() {
+    let files = scene_files(Path::new(EXAMPLES_DIR)).unwrap();
+    let numbers: Vec<Option<u32>> = files.iter().map(|file| scene_number(file)).collect();
+
+    assert!(files.len() >= 10);
+    assert!(numbers.iter().all(Option::is_some), "{:?}", files);
+    assert!(numbers.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", numbers);
+    // 10 sorts after 9, not after 1
+    let position = |number: u32| numbers.iter().position(|&n| n == Some(number)).unwrap();
+    assert!(position(10) > position(9));
+}
+
+#[test]
+fn unnumbered_scenes_come_last() {
+    let dir = std::env::temp_dir().join(format!("scene_catalog_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for name in ["b.json", "10_ten.json", "a.json", "2_two.json", "notes.txt"] {
+        std::fs::write(dir.join(name), "{}").unwrap();
+    }
+    let files = scene_files(&dir).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let names: Vec<PathBuf> = files.iter().map(|file| PathBuf::from(file.file_name().unwrap())).collect();
+    assert_eq!(names, ["2_two.json", "10_ten.json", "a.json", "b.json"].map(PathBuf::from));
+    assert_eq!(scene_name(Path::new("examples/7_multi_level.json")), "multi_level");
+    assert_eq!(scene_name(Path::new("plain.json")), "plain");
+    assert_eq!(scene_number(Path::new("plain.json")), None);
+}
+
+#[test]
+fn scenes_are_found_by_number_or_name() {
+    let files = scene_files(Path::new(EXAMPLES_DIR)).unwrap();
+    let found = |name: &str| find_scene(&files, name).map(|file| file.file_name().unwrap().to_string_lossy().into_owned());
+
+    assert_eq!(found("7").as_deref(), Some("7_multi_level.json"));
+    assert_eq!(found("10").as_deref(), Some("10_full_house.json"));
+    assert_eq!(found("7_multi_level").as_deref(), Some("7_multi_level.json"));
+    assert_eq!(found("multi_level").as_deref(), Some("7_multi_level.json"));
+    assert_eq!(found("999"), None);
+    assert_eq!(found("no_such_scene"), None);
+}
+
+#[test]
+fn paging_wraps_around_the_catalog() {
+    let catalog = SceneCatalog::scan(EXAMPLES_DIR).unwrap();
+    let first = &catalog.files()[0];
+    let last = catalog.files().last().unwrap();
+    let second = &catalog.files()[1];
+
+    assert_eq!(catalog.step(first, 1), Some(second));
+    assert_eq!(catalog.step(second, -1), Some(first));
+    assert_eq!(catalog.step(first, -1), Some(last));
+    assert_eq!(catalog.step(last, 1), Some(first));
+
+    // However the current scene's path is spelled
+    let spelled = Path::new(".").join(second);
+    assert_eq!(catalog.position(&spelled), Some(1));
+
+    // Scenes from elsewhere start paging at either end
+    let elsewhere = Path::new("elsewhere/scene.json");
+    assert_eq!(catalog.position(elsewhere), None);
+    assert_eq!(catalog.step(elsewhere, 1), Some(first));
+    assert_eq!(catalog.step(elsewhere, -1), Some(last));
+
+    assert_eq!(SceneCatalog::empty("nowhere").step(first, 1), None);
+}
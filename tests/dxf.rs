@@ -0,0 +1,147 @@
+use glam::{Vec2, Vec3};
+use std::path::Path;
+use webgpu_rust_architecture::plan::dxf::plan_to_dxf;
+use webgpu_rust_architecture::plan::{floor_plan, floor_plans, Layer, Shape, DEFAULT_CUT_HEIGHT};
+use webgpu_rust_architecture::scene::loader::load_scene_from_file;
+use webgpu_rust_architecture::scene::migration::CURRENT_FORMAT_VERSION;
+use webgpu_rust_architecture::scene::{
+    Camera, Column, ColumnShape, DoorFeature, DoorType, Element, Scene, Wall, WallFeature,
+};
+
+fn scene(elements: Vec<Element>) -> Scene {
+    Scene {
+        format_version: CURRENT_FORMAT_VERSION,
+        name: "DXF".to_string(),
+        camera: Camera {
+            position: Vec3::new(0.0, 2.0, -5.0),
+            target: Vec3::ZERO,
+            fov: 45.0,
+        },
+        viewpoints: Vec::new(),
+        materials: Vec::new(),
+        lights: Vec::new(),
+        site: None,
+        elements,
+    }
+}
+
+/// Group code and value pairs of a DXF file, after checking it is made of them.
+fn pairs(dxf: &str) -> Vec<(u32, String)> {
+    let lines: Vec<&str> = dxf.lines().collect();
+    assert!(lines.len().is_multiple_of(2));
+    let pairs: Vec<(u32, String)> = lines
+        .chunks_exact(2)
+        .map(|pair| (pair[0].trim().parse().unwrap(), pair[1].to_string()))
+        .collect();
+    assert_eq!(pairs.last().unwrap(), &(0, "EOF".to_string()));
+    pairs
+}
+
+/// The entities of a DXF file, each as its type and following pairs.
+fn entities(pairs: &[(u32, String)]) -> Vec<(String, Vec<(u32, String)>)> {
+    let start = pairs.iter().position(|pair| pair == &(2, "ENTITIES".to_string())).unwrap() + 1;
+    let mut entities: Vec<(String, Vec<(u32, String)>)> = Vec::new();
+    for (code, value) in &pairs[start..] {
+        if *code == 0 {
+            if value == "ENDSEC" {
+                break;
+            }
+            entities.push((value.clone(), Vec::new()));
+        } else {
+            entities.last_mut().unwrap().1.push((*code, value.clone()));
+        }
+    }
+    entities
+}
+
+fn value(entity: &[(u32, String)], code: u32) -> f32 {
+    entity.iter().find(|pair| pair.0 == code).unwrap().1.parse().unwrap()
+}
+
+#[test]
+fn every_plan_item_is_an_entity_on_its_layer() {
+    let scene = load_scene_from_file(Path::new("examples/10_full_house.json")).unwrap();
+    let plan = &floor_plans(&scene, DEFAULT_CUT_HEIGHT)[0];
+    let pairs = pairs(&plan_to_dxf(plan));
+
+    assert!(pairs.contains(&(1, "AC1009".to_string())));
+    let layers: Vec<&str> = pairs
+        .windows(2)
+        .filter(|window| window[0] == (0, "LAYER".to_string()))
+        .map(|window| window[1].1.as_str())
+        .collect();
+    assert_eq!(layers, ["WALLS", "OPENINGS", "COLUMNS", "STAIRS", "FURNITURE", "ROOMS", "DIMENSIONS"]);
+
+    let entities = entities(&pairs);
+    let drawn: Vec<&(String, Vec<(u32, String)>)> =
+        entities.iter().filter(|(kind, _)| kind != "VERTEX" && kind != "SEQEND").collect();
+    assert_eq!(drawn.len(), plan.items.len());
+    for ((_, entity), item) in drawn.iter().zip(&plan.items) {
+        assert_eq!(entity[0], (8, item.layer.name().to_ascii_uppercase()));
+    }
+}
+
+#[test]
+fn columns_keep_their_footprint() {
+    let column = |x: f32, shape: ColumnShape| {
+        Element::Column(Column {
+            position: Vec3::new(x, 0.0, 0.0),
+            radius: 0.3,
+            height: 3.0,
+            shape,
+            material: None,
+            transform: None,
+        })
+    };
+    let scene = scene(vec![
+        column(0.0, ColumnShape::Round),
+        column(2.0, ColumnShape::Square),
+        column(4.0, ColumnShape::Hexagonal),
+    ]);
+    let entities = entities(&pairs(&plan_to_dxf(&floor_plan(&scene, 0.0, DEFAULT_CUT_HEIGHT))));
+    let columns: Vec<&str> = entities
+        .iter()
+        .filter(|(_, entity)| entity.first() == Some(&(8, "COLUMNS".to_string())))
+        .map(|(kind, _)| kind.as_str())
+        .collect();
+    let polyline = |corners: usize| [vec!["POLYLINE"], vec!["VERTEX"; corners], vec!["SEQEND"]].concat();
+    assert_eq!(columns, [vec!["CIRCLE"], polyline(4), polyline(6)].concat());
+}
+
+#[test]
+fn door_swings_end_on_the_leaf_and_the_jamb() {
+    let wall = Element::Wall(Wall {
+        start: Vec3::new(-2.0, 0.0, 1.0),
+        end: Vec3::new(2.0, 0.0, 3.0),
+        height: 3.0,
+        thickness: 0.2,
+        features: vec![WallFeature::Door(DoorFeature {
+            position: Vec2::new(0.0, 0.0),
+            size: Vec2::new(0.9, 2.1),
+            door_type: DoorType::Single,
+        })],
+        material: None,
+        transform: None,
+    });
+    let plan = floor_plan(&scene(vec![wall]), 0.0, DEFAULT_CUT_HEIGHT);
+    let Some(Shape::Arc { centre, radius, start, sweep }) =
+        plan.items.iter().filter(|item| item.layer == Layer::Openings).map(|item| &item.shape).find(|shape| matches!(shape, Shape::Arc { .. }))
+    else {
+        panic!("a single door has a swing");
+    };
+    let plan_points = [*start, start + sweep * 0.5, start + sweep].map(|angle| *centre + Vec2::from_angle(angle) * *radius);
+
+    let entities = entities(&pairs(&plan_to_dxf(&plan)));
+    let (_, arc) = entities.iter().find(|(kind, _)| kind == "ARC").unwrap();
+    let dxf_centre = Vec2::new(value(arc, 10), value(arc, 20));
+    // DXF arcs run counter-clockwise from angle 50 to angle 51
+    let (from, to) = (value(arc, 50), value(arc, 51));
+    let middle = from + (to - from).rem_euclid(360.0) * 0.5;
+    let dxf_points = [from, middle, to].map(|degrees| dxf_centre + Vec2::from_angle(degrees.to_radians()) * value(arc, 40));
+
+    // The same points, with Y flipped for DXF
+    for point in plan_points {
+        let flipped = Vec2::new(point.x, -point.y);
+        assert!(dxf_points.iter().any(|dxf_point| dxf_point.abs_diff_eq(flipped, 1e-3)), "{} not in {:?}", flipped, dxf_points);
+    }
+}
@@ -0,0 +1,69 @@
+use glam::Vec3;
+use webgpu_rust_architecture::scene::furniture::furniture_to_mesh;
+use webgpu_rust_architecture::scene::primitives::Mesh;
+use webgpu_rust_architecture::scene::{Furniture, FurnitureType};
+
+fn furniture(furniture_type: FurnitureType, rotation: f32) -> Furniture {
+    Furniture {
+        position: Vec3::new(1.0, 0.0, 1.0),
+        rotation,
+        furniture_type,
+        material: None,
+        transform: None,
+    }
+}
+
+fn bounds(mesh: &Mesh) -> (Vec3, Vec3) {
+    mesh.vertices.iter().map(|vertex| Vec3::from(vertex.position)).fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), position| (min.min(position), max.max(position)),
+    )
+}
+
+#[test]
+fn quarter_turn_swaps_width_and_depth() {
+    let table = FurnitureType::Table { width: 2.0, depth: 1.0, height: 0.75 };
+
+    let (min, max) = bounds(&furniture_to_mesh(&furniture(table.clone(), 0.0)));
+    assert!(min.abs_diff_eq(Vec3::new(0.0, 0.0, 0.5), 1e-4), "{:?}", min);
+    assert!(max.abs_diff_eq(Vec3::new(2.0, 0.75, 1.5), 1e-4), "{:?}", max);
+
+    // Turned about its position, so the footprint stays centred there
+    let (min, max) = bounds(&furniture_to_mesh(&furniture(table, 90.0)));
+    assert!(min.abs_diff_eq(Vec3::new(0.5, 0.0, 0.0), 1e-4), "{:?}", min);
+    assert!(max.abs_diff_eq(Vec3::new(1.5, 0.75, 2.0), 1e-4), "{:?}", max);
+}
+
+#[test]
+fn short_fridge_handles_stay_above_the_floor() {
+    for height in [0.8, 0.3, 0.1] {
+        let fridge = FurnitureType::Fridge { width: 0.6, depth: 0.6, height };
+        let mesh = furniture_to_mesh(&furniture(fridge, 0.0));
+        let (min, max) = bounds(&mesh);
+
+        assert_eq!(min.y, 0.0, "{} m fridge", height);
+        assert!((max.y - height).abs() < 1e-4, "{} m fridge", height);
+        // Handles and the door split stick out of the front
+        let handles = mesh.vertices.iter().filter(|vertex| vertex.position[2] > 1.3 + 0.01);
+        assert!(handles.clone().all(|vertex| vertex.position[1] > 0.0), "{} m fridge", height);
+        if height == 0.8 {
+            assert!(handles.count() > 0);
+        }
+    }
+}
+
+#[test]
+fn rotation_is_counter_clockwise_from_above() {
+    // The backrest starts on the -Z side; a quarter turn brings it round to -X
+    let mesh = furniture_to_mesh(&furniture(FurnitureType::Chair { seat_height: 0.45 }, 90.0));
+    let (_, max) = bounds(&mesh);
+    let backrest: Vec<Vec3> = mesh
+        .vertices
+        .iter()
+        .map(|vertex| Vec3::from(vertex.position))
+        .filter(|position| position.y > max.y - 1e-4)
+        .collect();
+
+    assert!(!backrest.is_empty());
+    assert!(backrest.iter().all(|position| position.x < 1.0 - 0.15), "{:?}", backrest);
+}
@@ -0,0 +1,88 @@
+use glam::Vec3;
+use serde_json::Value;
+use std::path::Path;
+use webgpu_rust_architecture::export::gltf::scene_to_glb;
+use webgpu_rust_architecture::scene::loader::{load_scene_from_file, scene_to_mesh};
+
+/// The JSON chunk of a GLB file, after checking the container's layout.
+fn glb_json(glb: &[u8]) -> Value {
+    let word = |offset: usize| u32::from_le_bytes(glb[offset..offset + 4].try_into().unwrap()) as usize;
+    assert_eq!(&glb[0..4], b"glTF");
+    assert_eq!(word(4), 2);
+    assert_eq!(word(8), glb.len());
+
+    let json_length = word(12);
+    assert_eq!(&glb[16..20], b"JSON");
+    let json: Value = serde_json::from_slice(&glb[20..20 + json_length]).unwrap();
+
+    let bin = 20 + json_length;
+    assert_eq!(&glb[bin + 4..bin + 8], b"BIN\0");
+    assert_eq!(word(bin) as u64, json["buffers"][0]["byteLength"].as_u64().unwrap());
+    assert_eq!(bin + 8 + word(bin), glb.len());
+    json
+}
+
+/// Total vertex count and bounding box of every primitive's positions.
+fn position_stats(json: &Value) -> (usize, Vec3, Vec3) {
+    let (mut count, mut min, mut max) = (0, Vec3::INFINITY, Vec3::NEG_INFINITY);
+    for mesh in json["meshes"].as_array().unwrap() {
+        for primitive in mesh["primitives"].as_array().unwrap() {
+            let accessor = &json["accessors"][primitive["attributes"]["POSITION"].as_u64().unwrap() as usize];
+            let vec3 = |value: &Value| {
+                let v: Vec<f32> = serde_json::from_value(value.clone()).unwrap();
+                Vec3::from_slice(&v)
+            };
+            count += accessor["count"].as_u64().unwrap() as usize;
+            min = min.min(vec3(&accessor["min"]));
+            max = max.max(vec3(&accessor["max"]));
+        }
+    }
+    (count, min, max)
+}
+
+#[test]
+fn glb_holds_the_scene_mesh() {
+    for example in ["3_simple_room.json", "9_complex_floor_plan.json", "11_apartment_interior.json"] {
+        let scene = load_scene_from_file(&Path::new("examples").join(example)).unwrap();
+        let mesh = scene_to_mesh(&scene);
+        let (mesh_min, mesh_max) = mesh.vertices.iter().fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), v| {
+            (min.min(Vec3::from(v.position)), max.max(Vec3::from(v.position)))
+        });
+
+        let json = glb_json(&scene_to_glb(&scene, Path::new("examples")));
+        let (count, min, max) = position_stats(&json);
+        assert_eq!(count, mesh.vertices.len(), "{example}");
+        assert!(min.abs_diff_eq(mesh_min, 1e-5) && max.abs_diff_eq(mesh_max, 1e-5), "{example}");
+    }
+}
+
+#[test]
+fn every_element_gets_a_named_node() {
+    let scene = load_scene_from_file(Path::new("examples/11_apartment_interior.json")).unwrap();
+    let json = glb_json(&scene_to_glb(&scene, Path::new("examples")));
+
+    let nodes = json["nodes"].as_array().unwrap();
+    let root = &nodes[json["scenes"][0]["nodes"][0].as_u64().unwrap() as usize];
+    assert_eq!(root["name"], "Apartment Interior");
+    let children = root["children"].as_array().unwrap();
+    assert_eq!(children.len(), scene.elements.len() + 1);
+
+    for (index, child) in children.iter().skip(1).enumerate() {
+        let node = &nodes[child.as_u64().unwrap() as usize];
+        assert_eq!(node["extras"]["element"], index);
+    }
+    assert!(nodes.iter().any(|node| node["name"] == "sofa_9" && node.get("mesh").is_some()));
+
+    let materials: Vec<&str> = json["materials"].as_array().unwrap().iter().map(|m| m["name"].as_str().unwrap()).collect();
+    assert!(materials.contains(&"sofa_fabric"), "{materials:?}");
+}
+
+#[test]
+fn textures_are_embedded() {
+    let scene = load_scene_from_file(Path::new("examples/12_textured_walls.json")).unwrap();
+    let json = glb_json(&scene_to_glb(&scene, Path::new("examples")));
+
+    assert_eq!(json["images"][0]["mimeType"], "image/png");
+    let brick = json["materials"].as_array().unwrap().iter().find(|m| m["name"] == "brick").unwrap();
+    assert_eq!(brick["pbrMetallicRoughness"]["baseColorTexture"]["index"], 0);
+}
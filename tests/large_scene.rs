@@ -0,0 +1,66 @@
+use glam::Vec3;
+use webgpu_rust_architecture::scene::migration::CURRENT_FORMAT_VERSION;
+use webgpu_rust_architecture::scene::loader::scene_to_mesh;
+use webgpu_rust_architecture::scene::{Camera, Column, ColumnShape, Element, Scene};
+
+/// A grid of round columns, each built from a few dozen vertices.
+fn column_grid(side: usize) -> Scene {
+    let mut elements = Vec::new();
+    for i in 0..side {
+        for j in 0..side {
+            elements.push(Element::Column(Column {
+                position: Vec3::new(i as f32 * 2.0, 0.0, j as f32 * 2.0),
+                radius: 0.3,
+                height: 3.0,
+                shape: ColumnShape::Round,
+                material: None,
+                transform: None,
+            }));
+        }
+    }
+    Scene {
+        format_version: CURRENT_FORMAT_VERSION,
+        name: "Column grid".to_string(),
+        camera: Camera {
+            position: Vec3::new(0.0, 2.0, -5.0),
+            target: Vec3::ZERO,
+            fov: 45.0,
+        },
+        viewpoints: Vec::new(),
+        materials: Vec::new(),
+        lights: Vec::new(),
+        site: None,
+        elements,
+    }
+}
+
+#[test]
+fn scene_above_u16_limit_keeps_indices_in_range() {
+    let scene = column_grid(50);
+    let mesh = scene_to_mesh(&scene);
+
+    assert!(mesh.vertices.len() > u16::MAX as usize);
+    assert!(mesh.indices.iter().all(|&i| (i as usize) < mesh.vertices.len()));
+    assert_eq!(
+        mesh.indices.iter().copied().max(),
+        Some(mesh.vertices.len() as u32 - 1)
+    );
+}
+
+#[test]
+fn last_element_is_not_wrapped_onto_earlier_geometry() {
+    let scene = column_grid(50);
+    let mesh = scene_to_mesh(&scene);
+
+    // The last column sits in the far corner; its triangles must reference it
+    let corner = Vec3::new(98.0, 0.0, 98.0);
+    let last_triangle = &mesh.indices[mesh.indices.len() - 3..];
+    for &index in last_triangle {
+        let position = Vec3::from(mesh.vertices[index as usize].position);
+        let offset = position - corner;
+        assert!(
+            Vec3::new(offset.x, 0.0, offset.z).length() <= 0.3 + 1e-4,
+            "vertex {index} at {position} is not part of the last column"
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use glam::Vec3;
+use std::path::Path;
+use webgpu_rust_architecture::export::obj::scene_to_obj;
+use webgpu_rust_architecture::export::shell::{close_shells, is_watertight, SHEET_THICKNESS};
+use webgpu_rust_architecture::export::stl::scene_to_stl;
+use webgpu_rust_architecture::export::{element_objects, MeshExportOptions};
+use webgpu_rust_architecture::scene::loader::{element_meshes, load_scene_from_file};
+use webgpu_rust_architecture::scene::{Element, Scene};
+
+fn load_example(name: &str) -> Scene {
+    load_scene_from_file(&Path::new("examples").join(name)).unwrap()
+}
+
+fn example_names() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir("examples")
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".json"))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn every_element_closes_into_watertight_shells() {
+    for name in example_names() {
+        let scene = load_example(&name);
+        for (index, mesh) in element_meshes(&scene).iter().enumerate() {
+            let closed = close_shells(mesh, SHEET_THICKNESS);
+            assert!(is_watertight(&closed), "{} element {} isn't watertight", name, index);
+        }
+    }
+}
+
+#[test]
+fn stl_is_scaled_and_z_up() {
+    let scene = load_example("3_simple_room.json");
+    let options = MeshExportOptions {
+        print_scale: Some(100.0),
+        watertight: true,
+    };
+    let stl = scene_to_stl(&scene, options);
+
+    let count = u32::from_le_bytes(stl[80..84].try_into().unwrap()) as usize;
+    assert_eq!(stl.len(), 84 + count * 50);
+    let triangles: usize = element_objects(&scene, options).iter().map(|(_, mesh)| mesh.indices.len() / 3).sum();
+    assert!(count > 0 && count <= triangles);
+
+    let (mut min, mut max) = (Vec3::INFINITY, Vec3::NEG_INFINITY);
+    for facet in stl[84..].chunks_exact(50) {
+        for corner in 1..4 {
+            let v: Vec<f32> = facet[corner * 12..corner * 12 + 12]
+                .chunks_exact(4)
+                .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+                .collect();
+            min = min.min(Vec3::from_slice(&v));
+            max = max.max(Vec3::from_slice(&v));
+        }
+    }
+
+    // The 4 x 5 m room is 40 x 50 mm at 1:100 plus its walls' thickness,
+    // standing on the build plate with its 3 m walls 30 mm tall
+    let room = scene
+        .elements
+        .iter()
+        .find_map(|element| match element {
+            Element::Room(room) => Some(room),
+            _ => None,
+        })
+        .unwrap();
+    let size = max - min;
+    let within = |value: f32, metres: f32| value >= metres * 10.0 - 0.01 && value <= metres * 10.0 + 3.0;
+    assert!(within(size.x, room.dimensions.width), "width {}", size.x);
+    assert!(within(size.y, room.dimensions.depth), "depth {}", size.y);
+    assert!(within(size.z, room.dimensions.height), "height {}", size.z);
+    assert!(min.z.abs() <= 1.0, "model starts at {}", min.z);
+}
+
+#[test]
+fn obj_names_elements_and_materials() {
+    let scene = load_example("11_apartment_interior.json");
+    let (obj, mtl) = scene_to_obj(&scene, Path::new("examples"), "house.mtl", MeshExportOptions::default());
+
+    assert!(obj.lines().any(|line| line == "mtllib house.mtl"));
+    for (name, _) in element_objects(&scene, MeshExportOptions::default()) {
+        assert!(obj.lines().any(|line| line == format!("o {}", name)), "missing object {}", name);
+    }
+
+    // Floors and ceilings keep their material names
+    let materials: Vec<&str> = mtl.lines().filter_map(|line| line.strip_prefix("newmtl ")).collect();
+    for line in obj.lines().filter_map(|line| line.strip_prefix("usemtl ")) {
+        assert!(materials.contains(&line), "material {} isn't defined", line);
+    }
+    for element in &scene.elements {
+        if let Some(material) = element.material() {
+            assert!(materials.contains(&material), "material {} is missing", material);
+        }
+    }
+
+    // Every face refers to vertices defined before it
+    let (mut v, mut vt, mut vn) = (0, 0, 0);
+    for line in obj.lines() {
+        match line.split_whitespace().next() {
+            Some("v") => v += 1,
+            Some("vt") => vt += 1,
+            Some("vn") => vn += 1,
+            Some("f") => {
+                for corner in line.split_whitespace().skip(1) {
+                    let indices: Vec<usize> = corner.split('/').map(|i| i.parse().unwrap()).collect();
+                    assert!(indices[0] <= v && indices[1] <= vt && indices[2] <= vn, "{}", line);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use webgpu_rust_architecture::scene::loader::{load_scene_from_file, scene_to_json, upgrade_scene_file, SceneError};
+use webgpu_rust_architecture::scene::migration::{migrate, MigrationError, CURRENT_FORMAT_VERSION, UNVERSIONED};
+
+/// Copy of `example` in the temporary directory, to be rewritten by a test.
+fn scratch_copy(example: &str, name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{}_{}.json", name, std::process::id()));
+    std::fs::copy(Path::new("examples").join(example), &path).unwrap();
+    path
+}
+
+#[test]
+fn unversioned_documents_are_migrated_to_the_current_version() {
+    let mut document = json!({ "name": "Old", "elements": [] });
+    assert_eq!(migrate(&mut document), Ok(UNVERSIONED));
+    assert_eq!(document["format_version"], CURRENT_FORMAT_VERSION);
+}
+
+#[test]
+fn documents_from_newer_versions_are_rejected() {
+    let mut document = json!({ "format_version": CURRENT_FORMAT_VERSION + 1, "elements": [] });
+    assert_eq!(
+        migrate(&mut document),
+        Err(MigrationError::UnsupportedVersion(CURRENT_FORMAT_VERSION as u64 + 1))
+    );
+
+    let path = std::env::temp_dir().join(format!("future_scene_{}.json", std::process::id()));
+    std::fs::write(&path, document.to_string()).unwrap();
+    let error = load_scene_from_file(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(error, SceneError::Migration { .. }), "{error}");
+}
+
+#[test]
+fn upgrade_rewrites_scenes_in_the_current_format_once() {
+    let path = scratch_copy("11_apartment_interior.json", "upgraded_scene");
+    let original = load_scene_from_file(&path).unwrap();
+
+    assert_eq!(upgrade_scene_file(&path).unwrap(), Some(UNVERSIONED));
+    assert_eq!(upgrade_scene_file(&path).unwrap(), None);
+
+    let upgraded = load_scene_from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(upgraded.format_version, CURRENT_FORMAT_VERSION);
+    assert_eq!(upgraded.elements.len(), original.elements.len());
+    assert_eq!(scene_to_json(&upgraded), scene_to_json(&original));
+}
+
+#[test]
+fn vectors_stay_on_one_line() {
+    let scene = load_scene_from_file(Path::new("examples/2_wall_with_window.json")).unwrap();
+    let json = scene_to_json(&scene);
+    assert!(json.contains("\"start\": [-3.0, 0.0, 0.0]"), "{json}");
+    assert!(json.contains("\"name\": \"Wall with Window\""), "{json}");
+}
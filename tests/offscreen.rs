@@ -0,0 +1,101 @@
+use glam::{Mat4, Vec3};
+use std::path::Path;
+use webgpu_rust_architecture::camera::Camera;
+use webgpu_rust_architecture::core::state::GpuError;
+use webgpu_rust_architecture::renderer::offscreen::OffscreenRenderer;
+use webgpu_rust_architecture::renderer::shadow::ShadowSettings;
+use webgpu_rust_architecture::scene::loader::load_scene_from_file;
+use webgpu_rust_architecture::scene::Scene;
+
+/// Offscreen renderer, or `None` on machines with neither a GPU nor a
+/// software adapter, where these tests have nothing to run on.
+fn renderer() -> Option<OffscreenRenderer> {
+    match pollster::block_on(OffscreenRenderer::new()) {
+        Ok(renderer) => Some(renderer.with_asset_dir("examples")),
+        Err(GpuError::NoAdapter) => {
+            eprintln!("skipping: no GPU or fallback adapter");
+            None
+        }
+        Err(e) => panic!("{e}"),
+    }
+}
+
+#[test]
+fn renders_scene_at_requested_resolution() {
+    let Some(renderer) = renderer() else { return };
+    let scene = load_scene_from_file(Path::new("examples/3_simple_room.json")).unwrap();
+    let camera = Camera::from_scene(&scene.camera, 1.0);
+
+    let image = renderer.render(&scene, &camera, 200, 150);
+
+    assert_eq!(image.dimensions(), (200, 150));
+}
+
+#[test]
+fn scene_geometry_covers_part_of_the_sky() {
+    let Some(renderer) = renderer() else { return };
+    let scene = load_scene_from_file(Path::new("examples/12_textured_walls.json")).unwrap();
+    let camera = Camera::from_scene(&scene.camera, 1.0);
+
+    let image = renderer.render(&scene, &camera, 128, 96);
+
+    // The top-left corner is sky; something in the frame must differ from it
+    let sky = *image.get_pixel(0, 0);
+    assert!(image.pixels().any(|pixel| *pixel != sky));
+}
+
+#[test]
+fn sun_casts_shadow_of_column_onto_ground() {
+    let Some(renderer) = renderer() else { return };
+    let renderer = renderer.with_shadow_settings(ShadowSettings {
+        resolution: 1024,
+        ..ShadowSettings::default()
+    });
+    // A 3 m column lit by a sun at 45° to the east, seen from above
+    let scene: Scene = serde_json::from_value(serde_json::json!({
+        "name": "Shadow",
+        "camera": { "position": [0.0, 20.0, 5.0], "target": [0.0, 0.0, 0.0], "fov": 30.0 },
+        "lights": [
+            { "type": "ambient", "intensity": 0.2 },
+            { "type": "sun", "direction": [1.0, -1.0, 0.0], "intensity": 0.8 }
+        ],
+        "elements": [
+            { "type": "column", "position": [0.0, 0.0, 0.0], "radius": 0.5, "height": 3.0, "shape": "square" }
+        ]
+    }))
+    .unwrap();
+    let camera = Camera::from_scene(&scene.camera, 1.0);
+    let (width, height) = (256, 256);
+
+    let image = renderer.render(&scene, &camera, width, height);
+
+    let view_proj = Mat4::perspective_rh(camera.fov.to_radians(), 1.0, 0.1, 1000.0)
+        * Mat4::look_at_rh(camera.position, camera.target, Vec3::Y);
+    let brightness = |world: Vec3| {
+        let ndc = view_proj.project_point3(world);
+        let x = ((ndc.x * 0.5 + 0.5) * width as f32) as u32;
+        let y = ((0.5 - ndc.y * 0.5) * height as f32) as u32;
+        let pixel = image.get_pixel(x, y);
+        pixel[0] as u32 + pixel[1] as u32 + pixel[2] as u32
+    };
+    // The shadow falls east of the column, along +X
+    let shadowed = brightness(Vec3::new(2.5, 0.0, 0.25));
+    let lit = brightness(Vec3::new(-2.5, 0.0, 0.25));
+    assert!(shadowed < lit * 2 / 3, "shadowed {shadowed}, lit {lit}");
+}
+
+#[test]
+fn shadow_settings_are_clamped_to_the_device() {
+    let limits = wgpu::Limits::default();
+    let clamped = ShadowSettings { resolution: 0, bias: -1.0 }.clamped(&limits);
+    assert_eq!(clamped, ShadowSettings { resolution: 1, bias: 0.0 });
+    let clamped = ShadowSettings { resolution: u32::MAX, ..ShadowSettings::default() }.clamped(&limits);
+    assert_eq!(clamped.resolution, limits.max_texture_dimension_2d);
+
+    // An empty shadow map would fail wgpu validation
+    let Some(renderer) = renderer() else { return };
+    let renderer = renderer.with_shadow_settings(ShadowSettings { resolution: 0, ..ShadowSettings::default() });
+    let scene = load_scene_from_file(Path::new("examples/3_simple_room.json")).unwrap();
+    let image = renderer.render(&scene, &Camera::from_scene(&scene.camera, 1.0), 64, 48);
+    assert_eq!(image.dimensions(), (64, 48));
+}
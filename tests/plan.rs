@@ -0,0 +1,145 @@
+use glam::{Vec2, Vec3};
+use std::path::Path;
+use webgpu_rust_architecture::plan::svg::plan_to_svg;
+use webgpu_rust_architecture::plan::{floor_plan, floor_plans, FloorPlan, Layer, Shape, DEFAULT_CUT_HEIGHT};
+use webgpu_rust_architecture::scene::levels::levels;
+use webgpu_rust_architecture::scene::loader::load_scene_from_file;
+use webgpu_rust_architecture::scene::migration::CURRENT_FORMAT_VERSION;
+use webgpu_rust_architecture::scene::{Camera, DoorFeature, DoorType, Element, Scene, Wall, WallFeature, WindowFeature};
+
+fn scene(elements: Vec<Element>) -> Scene {
+    Scene {
+        format_version: CURRENT_FORMAT_VERSION,
+        name: "Plan".to_string(),
+        camera: Camera {
+            position: Vec3::new(0.0, 2.0, -5.0),
+            target: Vec3::ZERO,
+            fov: 45.0,
+        },
+        viewpoints: Vec::new(),
+        materials: Vec::new(),
+        lights: Vec::new(),
+        site: None,
+        elements,
+    }
+}
+
+/// A 6m long, 0.2m thick wall along X with `features`.
+fn wall(features: Vec<WallFeature>) -> Element {
+    Element::Wall(Wall {
+        start: Vec3::new(-3.0, 0.0, 0.0),
+        end: Vec3::new(3.0, 0.0, 0.0),
+        height: 3.0,
+        thickness: 0.2,
+        features,
+        material: None,
+        transform: None,
+    })
+}
+
+fn door(x: f32, door_type: DoorType) -> WallFeature {
+    WallFeature::Door(DoorFeature {
+        position: Vec2::new(x, 0.0),
+        size: Vec2::new(1.0, 2.1),
+        door_type,
+    })
+}
+
+fn area(outline: &[Vec2]) -> f32 {
+    let twice: f32 = (0..outline.len()).map(|k| outline[k].perp_dot(outline[(k + 1) % outline.len()])).sum();
+    twice.abs() * 0.5
+}
+
+fn shapes(plan: &FloorPlan, layer: Layer) -> Vec<&Shape> {
+    plan.items.iter().filter(|item| item.layer == layer).map(|item| &item.shape).collect()
+}
+
+#[test]
+fn every_level_gets_a_plan() {
+    let scene = load_scene_from_file(Path::new("examples/7_multi_level.json")).unwrap();
+    assert_eq!(levels(&scene), vec![0.0, 3.0]);
+
+    let plans = floor_plans(&scene, DEFAULT_CUT_HEIGHT);
+    assert_eq!(plans.len(), 2);
+    for plan in &plans {
+        assert!(!shapes(plan, Layer::Walls).is_empty());
+        assert_eq!(shapes(plan, Layer::Rooms).len(), 2, "name and area of the room");
+    }
+    // The stairs start on the ground floor
+    assert!(!shapes(&plans[0], Layer::Stairs).is_empty());
+    assert!(shapes(&plans[1], Layer::Stairs).is_empty());
+}
+
+#[test]
+fn walls_are_cut_around_openings() {
+    let window = WallFeature::Window(WindowFeature {
+        position: Vec2::new(-1.5, 1.5),
+        size: Vec2::new(1.0, 1.0),
+        sill_height: None,
+    });
+    // Above the cut, so the wall stays solid there
+    let transom = WallFeature::Window(WindowFeature {
+        position: Vec2::new(1.5, 2.5),
+        size: Vec2::new(1.0, 0.5),
+        sill_height: None,
+    });
+    let plan = floor_plan(&scene(vec![wall(vec![window, door(0.0, DoorType::Single), transom])]), 0.0, DEFAULT_CUT_HEIGHT);
+
+    let poche: f32 = shapes(&plan, Layer::Walls)
+        .iter()
+        .map(|shape| match shape {
+            Shape::Polygon(outline) => area(outline),
+            _ => panic!("walls are drawn as polygons"),
+        })
+        .sum();
+    assert!((poche - (6.0 - 2.0) * 0.2).abs() < 1e-4, "poché area {}", poche);
+    assert_eq!(shapes(&plan, Layer::Walls).len(), 3);
+}
+
+#[test]
+fn doors_are_drawn_by_type() {
+    let count = |door_type: DoorType| {
+        let plan = floor_plan(&scene(vec![wall(vec![door(0.0, door_type)])]), 0.0, DEFAULT_CUT_HEIGHT);
+        let openings = shapes(&plan, Layer::Openings);
+        let arcs = openings.iter().filter(|shape| matches!(shape, Shape::Arc { .. })).count();
+        let circles = openings.iter().filter(|shape| matches!(shape, Shape::Circle { .. })).count();
+        let panels = openings.iter().filter(|shape| matches!(shape, Shape::Polygon(_))).count();
+        (arcs, circles, panels)
+    };
+    assert_eq!(count(DoorType::Single), (1, 0, 0));
+    assert_eq!(count(DoorType::Double), (2, 0, 0));
+    assert_eq!(count(DoorType::Sliding), (0, 0, 2));
+    assert_eq!(count(DoorType::Revolving), (0, 1, 0));
+
+    // A single leaf swings a quarter turn as wide as the door
+    let plan = floor_plan(&scene(vec![wall(vec![door(0.0, DoorType::Single)])]), 0.0, DEFAULT_CUT_HEIGHT);
+    let arc = shapes(&plan, Layer::Openings)
+        .into_iter()
+        .find_map(|shape| match shape {
+            Shape::Arc { radius, sweep, .. } => Some((*radius, *sweep)),
+            _ => None,
+        })
+        .unwrap();
+    assert!((arc.0 - 1.0).abs() < 1e-4);
+    assert!((arc.1.abs() - std::f32::consts::FRAC_PI_2).abs() < 1e-4);
+}
+
+#[test]
+fn svg_has_a_group_per_layer() {
+    let scene = load_scene_from_file(Path::new("examples/10_full_house.json")).unwrap();
+    let plan = &floor_plans(&scene, DEFAULT_CUT_HEIGHT)[0];
+    let svg = plan_to_svg(plan, "Full house", 100.0);
+
+    assert!(svg.starts_with("<?xml"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    for layer in ["walls", "openings", "columns", "stairs", "furniture", "rooms", "dimensions"] {
+        assert!(svg.contains(&format!(r#"<g id="{}">"#, layer)), "missing layer {}", layer);
+    }
+    assert_eq!(svg.matches("<polygon").count(), plan.items.iter().filter(|item| matches!(item.shape, Shape::Polygon(_))).count());
+    assert_eq!(svg.matches("<g").count(), svg.matches("</g>").count());
+
+    // Drawn at 1:100, so 1m of building is 10mm on paper
+    let (min, max) = plan.bounds().unwrap();
+    let width: f32 = svg.split(r#"width=""#).nth(1).unwrap().split("mm").next().unwrap().parse().unwrap();
+    assert!(width > (max.x - min.x) * 10.0);
+}
@@ -0,0 +1,77 @@
+use glam::Vec3;
+use std::path::PathBuf;
+use webgpu_rust_architecture::camera_controller::CameraPose;
+use webgpu_rust_architecture::screenshot::{
+    read_screenshot_info, save_screenshot, scene_hash, ScreenshotError, ScreenshotInfo,
+};
+
+fn temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("screenshot_{}_{}", std::process::id(), name))
+}
+
+fn image() -> image::RgbaImage {
+    image::RgbaImage::from_fn(8, 4, |x, y| image::Rgba([x as u8 * 30, y as u8 * 60, 128, 255]))
+}
+
+fn info(scene: &str) -> ScreenshotInfo {
+    let pose = CameraPose {
+        position: Vec3::new(-12.345678, 6.5, 1.0 / 3.0),
+        yaw: 2.1547,
+        pitch: -0.18354,
+        fov: 52.5,
+    };
+    ScreenshotInfo::new(scene, b"{\"name\": \"Scene\"}", pose, 8, 4)
+}
+
+#[test]
+fn info_survives_exactly() {
+    let path = temp_file("round_trip.png");
+    let info = info("examples/10_full_house.json");
+    save_screenshot(&image(), &path, &info).unwrap();
+    let read = read_screenshot_info(&path).unwrap();
+    let pixels = image::open(&path).unwrap().to_rgba8();
+    std::fs::remove_file(&path).unwrap();
+
+    let read = read.expect("screenshot records its camera");
+    assert_eq!(read.scene, info.scene);
+    assert_eq!(read.scene_hash, info.scene_hash);
+    assert_eq!(read.pose.position, info.pose.position);
+    assert!((read.pose.yaw - info.pose.yaw).abs() < 1e-6);
+    assert!((read.pose.pitch - info.pose.pitch).abs() < 1e-6);
+    assert_eq!(read.pose.fov, 52.5);
+    assert_eq!((read.width, read.height), (8, 4));
+    assert!(read.renderer.starts_with("webgpu-rust-architecture "), "{}", read.renderer);
+    assert_eq!(pixels, image());
+}
+
+#[test]
+fn paths_outside_latin1_are_kept() {
+    let path = temp_file("unicode.png");
+    save_screenshot(&image(), &path, &info("scènes/集合住宅.json")).unwrap();
+    let read = read_screenshot_info(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(read.unwrap().scene, "scènes/集合住宅.json");
+}
+
+#[test]
+fn plain_pngs_record_nothing() {
+    let path = temp_file("plain.png");
+    image().save(&path).unwrap();
+    let read = read_screenshot_info(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(read, None);
+    assert!(matches!(
+        read_screenshot_info(&temp_file("missing.png")),
+        Err(ScreenshotError::Io { .. })
+    ));
+}
+
+#[test]
+fn scene_hashes_tell_contents_apart() {
+    // 64-bit FNV-1a, so hashes stay the same across builds
+    assert_eq!(scene_hash(b""), "cbf29ce484222325");
+    assert_eq!(scene_hash(b"a"), "af63dc4c8601ec8c");
+    assert_ne!(scene_hash(b"{\"fov\": 45}"), scene_hash(b"{\"fov\": 46}"));
+}
@@ -0,0 +1,55 @@
+use chrono::{NaiveDate, NaiveDateTime, TimeZone, Utc};
+use webgpu_rust_architecture::scene::solar::solar_position;
+use webgpu_rust_architecture::scene::Site;
+
+fn barcelona(north_angle: f32) -> Site {
+    Site {
+        latitude: 41.39,
+        longitude: 2.17,
+        north_angle,
+        timezone: 2.0,
+        date_time: None,
+    }
+}
+
+fn local(date: &str, hour: u32, minute: u32) -> NaiveDateTime {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .unwrap()
+        .and_hms_opt(hour, minute, 0)
+        .unwrap()
+}
+
+#[test]
+fn equinox_noon_sun_is_overhead_at_the_equator() {
+    // Solar noon at Greenwich is a few minutes after 12:00 UTC in March
+    let time = Utc.with_ymd_and_hms(2024, 3, 20, 12, 7, 0).unwrap();
+    let position = solar_position(0.0, 0.0, time);
+    assert!(position.elevation > 88.0, "{position:?}");
+}
+
+#[test]
+fn summer_solstice_peak_matches_latitude() {
+    let site = barcelona(0.0);
+    let peak = (0..24 * 60)
+        .map(|minutes| site.solar_position(local("2024-06-21", minutes / 60, minutes % 60)))
+        .max_by(|a, b| a.elevation.total_cmp(&b.elevation))
+        .unwrap();
+
+    // 90° - latitude + axial tilt, at solar noon due south
+    assert!((peak.elevation - (90.0 - 41.39 + 23.44)).abs() < 0.5, "{peak:?}");
+    assert!((peak.azimuth - 180.0).abs() < 2.0, "{peak:?}");
+    assert!(!site.solar_position(local("2024-06-21", 2, 0)).is_above_horizon());
+}
+
+#[test]
+fn sunlight_follows_north_angle() {
+    let noon = local("2024-06-21", 13, 52);
+
+    // The sun is in the south, so its light travels north: -Z by default...
+    let direction = barcelona(0.0).sun_direction(noon);
+    assert!(direction.y < 0.0 && direction.z < 0.0 && direction.z.abs() > direction.x.abs());
+
+    // ...and +X once north is turned a quarter clockwise
+    let direction = barcelona(90.0).sun_direction(noon);
+    assert!(direction.x > 0.0 && direction.x.abs() > direction.z.abs());
+}
@@ -0,0 +1,124 @@
+use glam::{Vec2, Vec3};
+use std::path::Path;
+use webgpu_rust_architecture::scene::loader::load_scene_from_file;
+use webgpu_rust_architecture::scene::migration::CURRENT_FORMAT_VERSION;
+use webgpu_rust_architecture::scene::{Camera, DoorFeature, DoorType, Element, Scene, Wall, WallFeature, WindowFeature};
+use webgpu_rust_architecture::takeoff::{takeoff, takeoff_to_csv, takeoff_to_json};
+
+fn scene(elements: Vec<Element>) -> Scene {
+    Scene {
+        format_version: CURRENT_FORMAT_VERSION,
+        name: "Takeoff".to_string(),
+        camera: Camera {
+            position: Vec3::new(0.0, 2.0, -5.0),
+            target: Vec3::ZERO,
+            fov: 45.0,
+        },
+        viewpoints: Vec::new(),
+        materials: Vec::new(),
+        lights: Vec::new(),
+        site: None,
+        elements,
+    }
+}
+
+fn door(x: f32, door_type: DoorType) -> WallFeature {
+    WallFeature::Door(DoorFeature {
+        position: Vec2::new(x, 0.0),
+        size: Vec2::new(1.0, 2.0),
+        door_type,
+    })
+}
+
+#[test]
+fn walls_are_measured_net_of_openings() {
+    let window = WallFeature::Window(WindowFeature {
+        position: Vec2::new(-1.5, 1.5),
+        size: Vec2::new(1.0, 1.0),
+        sill_height: None,
+    });
+    // Partly past the end of the wall, so only half of it is taken off
+    let overhanging = WallFeature::Window(WindowFeature {
+        position: Vec2::new(3.0, 1.5),
+        size: Vec2::new(1.0, 1.0),
+        sill_height: None,
+    });
+    let wall = Element::Wall(Wall {
+        start: Vec3::new(-3.0, 0.0, 0.0),
+        end: Vec3::new(3.0, 0.0, 0.0),
+        height: 3.0,
+        thickness: 0.2,
+        features: vec![window, overhanging, door(0.0, DoorType::Single), door(1.5, DoorType::Sliding)],
+        material: None,
+        transform: None,
+    });
+    let takeoff = takeoff(&scene(vec![wall]));
+
+    assert_eq!(takeoff.levels.len(), 1);
+    let total = &takeoff.total;
+    let area = 6.0 * 3.0 - 1.0 - 0.5 - 2.0 * 2.0;
+    assert!((total.wall_area - area).abs() < 1e-4, "wall area {}", total.wall_area);
+    assert!((total.wall_volume - area * 0.2).abs() < 1e-4, "wall volume {}", total.wall_volume);
+    assert_eq!(total.windows, 2);
+    assert_eq!(total.doors.get("single"), Some(&1));
+    assert_eq!(total.doors.get("sliding"), Some(&1));
+    assert_eq!(total.floor_area, 0.0);
+}
+
+#[test]
+fn rooms_are_measured_inside_their_walls() {
+    let scene = load_scene_from_file(Path::new("examples/3_simple_room.json")).unwrap();
+    let takeoff = takeoff(&scene);
+    let room = &takeoff.levels[0].rooms[0];
+
+    assert_eq!(room.name, "room_0");
+    // 4m by 5m between wall centrelines, with 0.2m thick walls
+    assert!((room.quantities.floor_area - 3.8 * 4.8).abs() < 1e-4);
+    assert!((room.quantities.perimeter - 2.0 * (3.8 + 4.8)).abs() < 1e-4);
+    assert!((room.quantities.wall_area - 2.0 * (4.0 + 5.0) * 3.0).abs() < 1e-3);
+    assert_eq!(takeoff.total, takeoff.levels[0].quantities);
+}
+
+#[test]
+fn levels_count_shared_walls_once() {
+    let scene = load_scene_from_file(Path::new("examples/11_apartment_interior.json")).unwrap();
+    let takeoff = takeoff(&scene);
+    let level = &takeoff.levels[0];
+
+    assert!(level.rooms.len() > 1);
+    let rooms_wall_area: f32 = level.rooms.iter().map(|room| room.quantities.wall_area).sum();
+    let rooms_floor_area: f32 = level.rooms.iter().map(|room| room.quantities.floor_area).sum();
+    assert!(level.quantities.wall_area < rooms_wall_area - 1.0, "{} vs {}", level.quantities.wall_area, rooms_wall_area);
+    assert!((level.quantities.floor_area - rooms_floor_area).abs() < 1e-4);
+}
+
+#[test]
+fn fittings_belong_to_the_room_they_stand_in() {
+    let scene = load_scene_from_file(Path::new("examples/11_apartment_interior.json")).unwrap();
+    let takeoff = takeoff(&scene);
+    let level = &takeoff.levels[0];
+
+    let furniture = |counts: &std::collections::BTreeMap<&str, u32>| counts.values().sum::<u32>();
+    let in_rooms: u32 = level.rooms.iter().map(|room| furniture(&room.quantities.furniture)).sum();
+    let pieces = scene.elements.iter().filter(|element| matches!(element, Element::Furniture(_))).count() as u32;
+    assert_eq!(furniture(&level.quantities.furniture), pieces);
+    assert!(in_rooms > 0 && in_rooms <= pieces);
+}
+
+#[test]
+fn csv_has_a_row_per_room_and_level() {
+    let scene = load_scene_from_file(Path::new("examples/7_multi_level.json")).unwrap();
+    let takeoff = takeoff(&scene);
+    let csv = takeoff_to_csv(&takeoff);
+    let lines: Vec<&str> = csv.lines().collect();
+
+    assert!(lines[0].starts_with("scope,level,elevation,name,floor_area,wall_area,wall_volume,perimeter,windows,columns"));
+    let rooms: usize = takeoff.levels.iter().map(|level| level.rooms.len()).sum();
+    assert_eq!(lines.len(), 1 + rooms + takeoff.levels.len() + 1);
+    let columns = lines[0].split(',').count();
+    assert!(lines.iter().all(|line| line.split(',').count() == columns));
+    assert!(lines.last().unwrap().starts_with("total,"));
+
+    let json: serde_json::Value = serde_json::from_str(&takeoff_to_json(&takeoff)).unwrap();
+    assert_eq!(json["levels"].as_array().unwrap().len(), takeoff.levels.len());
+}
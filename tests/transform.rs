@@ -0,0 +1,71 @@
+use glam::{Mat4, Quat, Vec3};
+use webgpu_rust_architecture::scene::primitives::{create_box, Mesh};
+use webgpu_rust_architecture::scene::{Rotation, Scale, Transform};
+
+/// Check every triangle of a box centred on `centre` winds counter-clockwise
+/// when seen from outside and carries normals matching its winding.
+fn assert_outward(mesh: &Mesh, centre: Vec3) {
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(mesh.vertices[triangle[i] as usize].position));
+        let winding = (b - a).cross(c - a).normalize();
+        assert!(winding.dot((a + b + c) / 3.0 - centre) > 0.0, "inward triangle {:?}", [a, b, c]);
+        for &index in triangle {
+            let normal = Vec3::from(mesh.vertices[index as usize].normal);
+            assert!(normal.abs_diff_eq(winding, 1e-4), "normal {:?}, face {:?}", normal, winding);
+        }
+    }
+}
+
+#[test]
+fn normals_stay_perpendicular_under_non_uniform_scale() {
+    // Stretching a turned box skews its faces, so the normals must not just
+    // be scaled along with the positions
+    let mut mesh = create_box(Vec3::ZERO, Vec3::ONE);
+    let matrix = Mat4::from_scale(Vec3::new(3.0, 1.0, 0.5)) * Mat4::from_rotation_y(30_f32.to_radians());
+    mesh.transform(matrix);
+    assert_outward(&mesh, Vec3::ZERO);
+}
+
+#[test]
+fn mirroring_flips_the_winding() {
+    let centre = Vec3::new(2.0, 0.0, 0.0);
+    let mut mesh = create_box(Vec3::ZERO, Vec3::new(1.0, 2.0, 3.0));
+    mesh.transform(Mat4::from_translation(centre) * Mat4::from_scale(Vec3::new(-1.0, 1.0, 2.0)));
+    assert_outward(&mesh, centre);
+}
+
+#[test]
+fn transform_scales_and_turns_about_the_pivot() {
+    let transform = Transform {
+        translation: Vec3::new(1.0, 0.0, 0.0),
+        rotation: Rotation::Yaw(90.0),
+        scale: Scale::Uniform(2.0),
+    };
+    let pivot = Vec3::new(2.0, 0.0, 0.0);
+    let matrix = transform.matrix_about(pivot);
+
+    // The pivot only moves by the translation
+    assert!(matrix.transform_point3(pivot).abs_diff_eq(Vec3::new(3.0, 0.0, 0.0), 1e-5));
+    // A point 1m along +X from the pivot ends up 2m along -Z from the moved pivot
+    let point = matrix.transform_point3(pivot + Vec3::X);
+    assert!(point.abs_diff_eq(Vec3::new(3.0, 0.0, -2.0), 1e-5), "{:?}", point);
+}
+
+#[test]
+fn euler_rotation_applies_yaw_then_pitch_then_roll() {
+    let transform = Transform {
+        rotation: Rotation::Euler(Vec3::new(30.0, 45.0, 60.0)),
+        ..Default::default()
+    };
+    let expected = Quat::from_rotation_y(45_f32.to_radians())
+        * Quat::from_rotation_x(30_f32.to_radians())
+        * Quat::from_rotation_z(60_f32.to_radians());
+    assert!(transform.rotation_quat().abs_diff_eq(expected, 1e-5));
+
+    let scaled = Transform {
+        scale: Scale::PerAxis(Vec3::new(1.0, 2.0, 3.0)),
+        ..Default::default()
+    };
+    let point = scaled.matrix_about(Vec3::ONE).transform_point3(Vec3::new(2.0, 2.0, 2.0));
+    assert!(point.abs_diff_eq(Vec3::new(2.0, 3.0, 4.0), 1e-5), "{:?}", point);
+}
@@ -0,0 +1,130 @@
+use glam::{Vec2, Vec3};
+use std::path::Path;
+use webgpu_rust_architecture::scene::migration::CURRENT_FORMAT_VERSION;
+use webgpu_rust_architecture::scene::loader::{load_scene_from_file, SceneError};
+use webgpu_rust_architecture::scene::validation::{validate_scene, ValidationErrorKind};
+use webgpu_rust_architecture::scene::{
+    Camera, DoorFeature, DoorType, Element, Scene, Stairs, Viewpoint, Wall, WallFeature, WindowFeature,
+};
+
+fn scene(elements: Vec<Element>) -> Scene {
+    Scene {
+        format_version: CURRENT_FORMAT_VERSION,
+        name: "Validation".to_string(),
+        camera: Camera {
+            position: Vec3::new(0.0, 2.0, -5.0),
+            target: Vec3::ZERO,
+            fov: 45.0,
+        },
+        viewpoints: Vec::new(),
+        materials: Vec::new(),
+        lights: Vec::new(),
+        site: None,
+        elements,
+    }
+}
+
+fn wall(features: Vec<WallFeature>) -> Element {
+    Element::Wall(Wall {
+        start: Vec3::new(-2.0, 0.0, 0.0),
+        end: Vec3::new(2.0, 0.0, 0.0),
+        height: 3.0,
+        thickness: 0.2,
+        features,
+        material: None,
+        transform: None,
+    })
+}
+
+fn window(x: f32, width: f32) -> WallFeature {
+    WallFeature::Window(WindowFeature {
+        position: Vec2::new(x, 1.5),
+        size: Vec2::new(width, 1.0),
+        sill_height: None,
+    })
+}
+
+#[test]
+fn example_scenes_are_valid() {
+    for entry in std::fs::read_dir("examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|extension| extension == "json") {
+            if let Err(e) = load_scene_from_file(&path) {
+                panic!("{}", e);
+            }
+        }
+    }
+}
+
+#[test]
+fn problems_are_reported_with_their_json_path() {
+    let stairs = Element::Stairs(Stairs {
+        position: Vec3::ZERO,
+        width: -1.0,
+        steps: 0,
+        step_height: 0.18,
+        step_depth: 0.28,
+        has_railing: false,
+        material: Some("unobtainium".to_string()),
+        transform: None,
+    });
+    let door = WallFeature::Door(DoorFeature {
+        position: Vec2::new(1.0, 0.5),
+        size: Vec2::new(0.9, 2.1),
+        door_type: DoorType::Single,
+    });
+    let scene = scene(vec![wall(vec![window(-1.0, 1.0), window(-0.8, 1.0), window(1.5, 1.5)]), stairs, wall(vec![door])]);
+
+    let problems: Vec<(Option<usize>, String, ValidationErrorKind)> = validate_scene(&scene)
+        .into_iter()
+        .map(|error| (error.element, error.path, error.kind))
+        .collect();
+    let paths: Vec<&str> = problems.iter().map(|(_, path, _)| path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "elements[0].features[1]",
+            "elements[0].features[2]",
+            "elements[1].material",
+            "elements[1].steps",
+            "elements[1].width",
+            "elements[2].features[0].position[1]",
+        ]
+    );
+
+    assert_eq!(problems[0].2, ValidationErrorKind::OverlappingFeatures { other: 0 });
+    assert!(matches!(problems[1].2, ValidationErrorKind::FeatureOutsideWall { .. }));
+    assert_eq!(problems[2].0, Some(1));
+    assert_eq!(problems[3].2, ValidationErrorKind::NoSteps);
+    assert_eq!(problems[5].2, ValidationErrorKind::DoorAboveFloor { threshold: 0.5 });
+}
+
+#[test]
+fn viewpoints_need_a_direction_and_a_field_of_view() {
+    let mut scene = scene(Vec::new());
+    let camera = |target: Vec3, fov: f32| Camera {
+        position: Vec3::new(0.0, 1.6, 0.0),
+        target,
+        fov,
+    };
+    scene.viewpoints = vec![
+        Viewpoint { name: "Good".to_string(), camera: camera(Vec3::Z, 45.0) },
+        Viewpoint { name: "Blind".to_string(), camera: camera(Vec3::Z, 0.0) },
+        Viewpoint { name: "Aimless".to_string(), camera: camera(Vec3::new(0.0, 1.6, 0.0), 45.0) },
+    ];
+
+    let problems: Vec<(Option<usize>, String, ValidationErrorKind)> = validate_scene(&scene)
+        .into_iter()
+        .map(|error| (error.element, error.path, error.kind))
+        .collect();
+    let paths: Vec<&str> = problems.iter().map(|(_, path, _)| path.as_str()).collect();
+    assert_eq!(paths, ["viewpoints[1].fov", "viewpoints[2].target"]);
+    assert!(problems.iter().all(|(element, _, _)| element.is_none()));
+    assert_eq!(problems[1].2, ValidationErrorKind::CameraWithoutDirection);
+}
+
+#[test]
+fn missing_file_is_an_io_error() {
+    let error = load_scene_from_file(Path::new("examples/does_not_exist.json")).unwrap_err();
+    assert!(matches!(error, SceneError::Io { .. }));
+}
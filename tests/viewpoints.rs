@@ -0,0 +1,124 @@
+use glam::Vec3;
+use std::f32::consts::PI;
+use std::path::PathBuf;
+use webgpu_rust_architecture::camera_controller::{CameraPose, CameraTransition};
+use webgpu_rust_architecture::scene::catalog::scene_files;
+use webgpu_rust_architecture::scene::loader::{load_scene_from_file, SceneError};
+use webgpu_rust_architecture::scene::viewpoints::{
+    load_saved_viewpoints, save_viewpoints, scene_viewpoints, viewpoints_path,
+};
+use webgpu_rust_architecture::scene::{Camera, Viewpoint};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("scene_viewpoints_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn viewpoint(name: &str, position: Vec3, target: Vec3) -> Viewpoint {
+    Viewpoint {
+        name: name.to_string(),
+        camera: Camera { position, target, fov: 50.0 },
+    }
+}
+
+fn pose(yaw_degrees: f32) -> CameraPose {
+    CameraPose {
+        position: Vec3::ZERO,
+        yaw: yaw_degrees.to_radians(),
+        pitch: 0.0,
+        fov: 45.0,
+    }
+}
+
+#[test]
+fn scene_viewpoints_come_before_saved_ones() {
+    let dir = temp_dir("order");
+    let scene_path = dir.join("3_simple_room.json");
+    std::fs::copy("examples/3_simple_room.json", &scene_path).unwrap();
+    let scene = load_scene_from_file(&scene_path).unwrap();
+
+    assert_eq!(viewpoints_path(&scene_path), dir.join("3_simple_room.views.json"));
+    assert!(load_saved_viewpoints(&scene_path).unwrap().is_empty());
+
+    let saved = viewpoint("Saved", Vec3::new(1.0, 1.6, 2.0), Vec3::new(0.0, 1.6, 0.0));
+    save_viewpoints(&scene_path, &[saved]).unwrap();
+    let viewpoints = scene_viewpoints(&scene_path, &scene).unwrap();
+    // Saved viewpoints don't show up as scenes
+    let files = scene_files(&dir).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let names: Vec<&str> = viewpoints.iter().map(|viewpoint| viewpoint.name.as_str()).collect();
+    assert_eq!(names, ["Inside", "Overhead", "Saved"]);
+    assert_eq!(viewpoints[2].camera.position, Vec3::new(1.0, 1.6, 2.0));
+    assert_eq!(viewpoints[2].camera.fov, 50.0);
+    assert_eq!(files, [scene_path]);
+}
+
+#[test]
+fn invalid_saved_viewpoints_are_rejected() {
+    let dir = temp_dir("invalid");
+    let scene_path = dir.join("scene.json");
+    save_viewpoints(&scene_path, &[viewpoint("Nowhere", Vec3::ONE, Vec3::ONE)]).unwrap();
+    let result = load_saved_viewpoints(&scene_path);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    match result {
+        Err(SceneError::Invalid { path, errors }) => {
+            assert!(path.ends_with("scene.views.json"), "{}", path.display());
+            let paths: Vec<&str> = errors.iter().map(|error| error.path.as_str()).collect();
+            assert_eq!(paths, ["viewpoints[0].target"]);
+        }
+        other => panic!("expected invalid viewpoints, got {:?}", other.map(|viewpoints| viewpoints.len())),
+    }
+}
+
+#[test]
+fn poses_round_trip_through_scene_cameras() {
+    let camera = Camera {
+        position: Vec3::new(-2.91, 8.23, -6.19),
+        target: Vec3::new(0.0, 1.0, 0.0),
+        fov: 45.0,
+    };
+    let pose = CameraPose::from_scene(&camera);
+    let again = CameraPose::from_scene(&pose.to_scene());
+
+    assert!((again.yaw - pose.yaw).abs() < 1e-5);
+    assert!((again.pitch - pose.pitch).abs() < 1e-5);
+    assert_eq!(again.position, camera.position);
+    assert_eq!(again.fov, 45.0);
+    let direction = (pose.to_scene().target - camera.position).normalize();
+    assert!(direction.abs_diff_eq((camera.target - camera.position).normalize(), 1e-5));
+}
+
+#[test]
+fn poses_turn_the_short_way_round() {
+    let halfway = pose(170.0).lerp(&pose(-170.0), 0.5);
+    assert!((halfway.yaw.abs() - PI).abs() < 1e-5, "{}", halfway.yaw.to_degrees());
+
+    let halfway = pose(10.0).lerp(&pose(-10.0), 0.5);
+    assert!(halfway.yaw.abs() < 1e-5, "{}", halfway.yaw.to_degrees());
+}
+
+#[test]
+fn transitions_ease_from_one_pose_to_the_other() {
+    let from = pose(0.0);
+    let to = CameraPose {
+        position: Vec3::new(10.0, 0.0, 0.0),
+        fov: 65.0,
+        ..pose(90.0)
+    };
+    let mut transition = CameraTransition::new(from, to, 1.0);
+
+    let early = transition.advance(0.1);
+    // Easing in moves less than the linear share of the way
+    assert!(early.position.x > 0.0 && early.position.x < 1.0, "{}", early.position.x);
+    assert!(!transition.is_finished());
+
+    let middle = transition.advance(0.4);
+    assert!((middle.position.x - 5.0).abs() < 1e-4);
+    assert!((middle.fov - 55.0).abs() < 1e-4);
+
+    assert_eq!(transition.advance(2.0), to);
+    assert!(transition.is_finished());
+}
@@ -0,0 +1,93 @@
+use glam::{Vec2, Vec3};
+use webgpu_rust_architecture::scene::primitives::Mesh;
+use webgpu_rust_architecture::scene::wall::{create_wall_mesh, WallEnds, WallSegment};
+use webgpu_rust_architecture::scene::{WallFeature, WindowFeature};
+
+/// A 1m by 1m window centred 1.5m up in the middle of its wall.
+fn window() -> WallFeature {
+    WallFeature::Window(WindowFeature {
+        position: Vec2::new(0.0, 1.5),
+        size: Vec2::new(1.0, 1.0),
+        sill_height: None,
+    })
+}
+
+/// Corners of every triangle in `mesh`, with the normal of its first vertex.
+fn triangles(mesh: &Mesh) -> Vec<([Vec3; 3], Vec3)> {
+    mesh.indices
+        .chunks_exact(3)
+        .map(|triangle| {
+            let corner = |i: usize| Vec3::from(mesh.vertices[triangle[i] as usize].position);
+            ([corner(0), corner(1), corner(2)], Vec3::from(mesh.vertices[triangle[0] as usize].normal))
+        })
+        .collect()
+}
+
+/// Total area of the triangles facing `normal` whose corners all pass `on_plane`.
+fn area_facing(mesh: &Mesh, normal: Vec3, on_plane: impl Fn(Vec3) -> bool) -> f32 {
+    triangles(mesh)
+        .into_iter()
+        .filter(|(corners, n)| n.abs_diff_eq(normal, 1e-4) && corners.iter().all(|&c| on_plane(c)))
+        .map(|([a, b, c], _)| (b - a).cross(c - a).length() * 0.5)
+        .sum()
+}
+
+#[test]
+fn diagonal_wall_stays_within_its_footprint() {
+    let segment = WallSegment::new(Vec3::ZERO, Vec3::new(2.0, 0.0, 2.0), 3.0, 0.2);
+    let mesh = segment.to_mesh(&[]);
+    assert!(!mesh.vertices.is_empty());
+
+    let (mut min, mut max) = (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN));
+    for vertex in &mesh.vertices {
+        min = min.min(Vec3::from(vertex.position));
+        max = max.max(Vec3::from(vertex.position));
+    }
+    // The square ends stick out half the thickness across the diagonal
+    let corner = 0.1 / 2.0_f32.sqrt();
+    assert!(min.abs_diff_eq(Vec3::new(-corner, 0.0, -corner), 1e-4), "{:?}", min);
+    assert!(max.abs_diff_eq(Vec3::new(2.0 + corner, 3.0, 2.0 + corner), 1e-4), "{:?}", max);
+
+    // Every vertex lies within half the thickness of the centre line
+    let direction = segment.direction();
+    for vertex in &mesh.vertices {
+        let offset = Vec3::from(vertex.position) * Vec3::new(1.0, 0.0, 1.0);
+        let across = offset - direction * offset.dot(direction);
+        assert!(across.length() <= 0.1 + 1e-4, "{:?}", vertex.position);
+    }
+
+    // The front face is on the left when walking from start to end
+    let front = Vec3::new(-1.0, 0.0, 1.0).normalize();
+    assert!((area_facing(&mesh, front, |_| true) - segment.length() * 3.0).abs() < 1e-3);
+}
+
+#[test]
+fn window_opening_has_no_faces_across_it() {
+    let mesh = create_wall_mesh(4.0, 3.0, 0.2, WallEnds::default(), &[window()]);
+
+    // The opening spans x -0.5..0.5 and y 1.0..2.0
+    let inside = |p: Vec3| p.x > -0.5 + 1e-4 && p.x < 0.5 - 1e-4 && p.y > 1.0 + 1e-4 && p.y < 2.0 - 1e-4;
+    for ([a, b, c], _) in triangles(&mesh) {
+        assert!(!inside((a + b + c) / 3.0), "{:?}", [a, b, c]);
+    }
+
+    // Front and back lose exactly the opening's area
+    let face_area = 4.0 * 3.0 - 1.0;
+    assert!((area_facing(&mesh, Vec3::Z, |_| true) - face_area).abs() < 1e-3);
+    assert!((area_facing(&mesh, Vec3::NEG_Z, |_| true) - face_area).abs() < 1e-3);
+}
+
+#[test]
+fn window_opening_is_lined_with_reveals() {
+    let mesh = create_wall_mesh(4.0, 3.0, 0.2, WallEnds::default(), &[window()]);
+    let reveal = 1.0 * 0.2;
+
+    // Jambs face into the opening from either side, the sill up and the head down
+    let jamb_left = area_facing(&mesh, Vec3::X, |p| (p.x + 0.5).abs() < 1e-4);
+    let jamb_right = area_facing(&mesh, Vec3::NEG_X, |p| (p.x - 0.5).abs() < 1e-4);
+    let sill = area_facing(&mesh, Vec3::Y, |p| (p.y - 1.0).abs() < 1e-4);
+    let head = area_facing(&mesh, Vec3::NEG_Y, |p| (p.y - 2.0).abs() < 1e-4);
+    for area in [jamb_left, jamb_right, sill, head] {
+        assert!((area - reveal).abs() < 1e-4, "{} {} {} {}", jamb_left, jamb_right, sill, head);
+    }
+}
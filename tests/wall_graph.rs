@@ -0,0 +1,157 @@
+use glam::{Vec2, Vec3};
+use webgpu_rust_architecture::scene::wall::WallSegment;
+use webgpu_rust_architecture::scene::wall_graph::{GraphWall, WallGraph};
+use webgpu_rust_architecture::scene::{DoorFeature, DoorType, WallFeature, WindowFeature};
+
+/// A 3m high, 0.2m thick wall on the ground from `start` to `end`, given in XZ.
+fn graph_wall(start: [f32; 2], end: [f32; 2], features: Vec<WallFeature>) -> GraphWall {
+    GraphWall {
+        segment: WallSegment::new(Vec3::new(start[0], 0.0, start[1]), Vec3::new(end[0], 0.0, end[1]), 3.0, 0.2),
+        features,
+        material: None,
+        element: None,
+    }
+}
+
+/// A 1m wide, 2.1m high door `x` along its wall from the centre.
+fn door(x: f32) -> WallFeature {
+    WallFeature::Door(DoorFeature {
+        position: Vec2::new(x, 0.0),
+        size: Vec2::new(1.0, 2.1),
+        door_type: DoorType::Single,
+    })
+}
+
+/// A 1m high window centred 1.5m up and `x` along its wall from the centre.
+fn window(x: f32, width: f32) -> WallFeature {
+    WallFeature::Window(WindowFeature {
+        position: Vec2::new(x, 1.5),
+        size: Vec2::new(width, 1.0),
+        sill_height: None,
+    })
+}
+
+/// The corners of a wall's two faces at one end, in XZ, after mitering.
+fn face_corners(segment: &WallSegment, at_start: bool) -> [Vec2; 2] {
+    let direction = Vec2::new(segment.direction().x, segment.direction().z);
+    let left = Vec2::new(-direction.y, direction.x) * segment.thickness * 0.5;
+    let (point, front, back, outwards) = if at_start {
+        (segment.start, segment.ends.start_front, segment.ends.start_back, -direction)
+    } else {
+        (segment.end, segment.ends.end_front, segment.ends.end_back, direction)
+    };
+    let point = Vec2::new(point.x, point.z);
+    [point + left + outwards * front, point - left + outwards * back]
+}
+
+fn contains_point(points: &[Vec2], point: Vec2) -> bool {
+    points.iter().any(|p| p.abs_diff_eq(point, 1e-4))
+}
+
+/// Points in XZ where a wall of the graph ends.
+fn ends(graph: &WallGraph) -> Vec<Vec2> {
+    graph
+        .walls
+        .iter()
+        .flat_map(|wall| [wall.segment.start, wall.segment.end])
+        .map(|point| Vec2::new(point.x, point.z))
+        .collect()
+}
+
+#[test]
+fn l_corner_faces_meet_at_the_miter() {
+    let graph = WallGraph::from_walls(vec![
+        graph_wall([0.0, 0.0], [4.0, 0.0], Vec::new()),
+        graph_wall([4.0, 0.0], [4.0, 4.0], Vec::new()),
+    ]);
+    assert_eq!(graph.walls.len(), 2);
+    assert!(graph.hubs.is_empty());
+
+    // The inner faces meet at the inside corner, the outer ones at the outside corner
+    let inside = Vec2::new(3.9, 0.1);
+    let outside = Vec2::new(4.1, -0.1);
+    for (wall, at_start) in [(&graph.walls[0], false), (&graph.walls[1], true)] {
+        let corners = face_corners(&wall.segment, at_start);
+        assert!(contains_point(&corners, inside), "{:?}", corners);
+        assert!(contains_point(&corners, outside), "{:?}", corners);
+    }
+    // The far ends stay square
+    assert!(face_corners(&graph.walls[0].segment, true).iter().all(|corner| corner.x == 0.0));
+}
+
+#[test]
+fn t_junction_splits_the_through_wall_around_a_hub() {
+    let graph = WallGraph::from_walls(vec![
+        graph_wall([0.0, 0.0], [4.0, 0.0], vec![window(-1.0, 1.0)]),
+        graph_wall([2.0, 0.0], [2.0, 3.0], Vec::new()),
+    ]);
+
+    assert_eq!(graph.walls.len(), 3);
+    assert_eq!(ends(&graph).iter().filter(|end| end.abs_diff_eq(Vec2::new(2.0, 0.0), 1e-4)).count(), 3);
+    assert_eq!(graph.hubs.len(), 1);
+    let hub = &graph.hubs[0];
+    assert!(hub.outline.len() >= 3);
+    assert!(hub.outline.iter().all(|corner| corner.distance(Vec2::new(2.0, 0.0)) <= 0.1 * 2.0_f32.sqrt() + 1e-4));
+    assert_eq!(hub.height, 3.0);
+
+    // The window stays in the half of the through-wall it was in, at the same place
+    let with_window: Vec<&GraphWall> = graph.walls.iter().filter(|wall| !wall.features.is_empty()).collect();
+    assert_eq!(with_window.len(), 1);
+    let segment = &with_window[0].segment;
+    let centre = (segment.start.x + segment.end.x) * 0.5;
+    assert!((centre + with_window[0].features[0].position().x - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn x_junction_splits_both_walls_around_a_square_hub() {
+    let graph = WallGraph::from_walls(vec![
+        graph_wall([0.0, 0.0], [4.0, 0.0], Vec::new()),
+        graph_wall([2.0, -2.0], [2.0, 2.0], Vec::new()),
+    ]);
+
+    assert_eq!(graph.walls.len(), 4);
+    assert!(graph.walls.iter().all(|wall| (wall.segment.length() - 2.0).abs() < 1e-4));
+    assert_eq!(graph.hubs.len(), 1);
+    let outline = &graph.hubs[0].outline;
+    assert_eq!(outline.len(), 4);
+    for corner in [Vec2::new(1.9, -0.1), Vec2::new(2.1, -0.1), Vec2::new(2.1, 0.1), Vec2::new(1.9, 0.1)] {
+        assert!(contains_point(outline, corner), "{:?}", outline);
+    }
+}
+
+#[test]
+fn party_wall_of_adjacent_rooms_becomes_one_wall() {
+    // Rooms run their walls in opposite directions along a shared side, and
+    // both declare the door in it
+    let graph = WallGraph::from_walls(vec![
+        graph_wall([0.0, 0.0], [4.0, 0.0], vec![door(0.5)]),
+        graph_wall([4.0, 0.0], [0.0, 0.0], vec![door(-0.5)]),
+    ]);
+
+    assert_eq!(graph.walls.len(), 1);
+    let wall = &graph.walls[0];
+    assert!((wall.segment.length() - 4.0).abs() < 1e-4);
+    assert_eq!(wall.features.len(), 1);
+    let segment = &wall.segment;
+    let centre = (segment.start.x + segment.end.x) * 0.5;
+    let along = if segment.end.x > segment.start.x { 1.0 } else { -1.0 };
+    assert!((centre + along * wall.features[0].position().x - 2.5).abs() < 1e-4);
+}
+
+#[test]
+fn features_between_merged_runs_are_kept() {
+    // The window sits past the end of its wall, in the gap before the next
+    // one on the same line, and nearer to its own wall
+    let graph = WallGraph::from_walls(vec![
+        graph_wall([0.0, 0.0], [2.0, 0.0], vec![window(1.4, 0.2)]),
+        graph_wall([3.0, 0.0], [5.0, 0.0], Vec::new()),
+    ]);
+
+    assert_eq!(graph.walls.len(), 2);
+    let features: Vec<(f32, &GraphWall)> =
+        graph.walls.iter().flat_map(|wall| wall.features.iter().map(move |feature| (feature.position().x, wall))).collect();
+    assert_eq!(features.len(), 1);
+    let (x, wall) = features[0];
+    assert_eq!(wall.segment.end.x.max(wall.segment.start.x), 2.0);
+    assert!((x - 1.4).abs() < 1e-4);
+}
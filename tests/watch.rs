@@ -0,0 +1,73 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use webgpu_rust_architecture::scene::loader::load_scene_from_file;
+use webgpu_rust_architecture::scene::watch::{scene_dependencies, SceneWatcher};
+
+/// Directory of its own in the temporary directory, for a test to write files in.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Mark `file` as modified `seconds` from now, as a save would.
+fn touch(file: &Path, seconds: u64) {
+    let modified = SystemTime::now() + Duration::from_secs(seconds);
+    File::options().write(true).open(file).unwrap().set_modified(modified).unwrap();
+}
+
+#[test]
+fn changes_are_reported_once() {
+    let dir = scratch_dir("watch_changes");
+    let scene_path = dir.join("scene.json");
+    std::fs::copy("examples/3_simple_room.json", &scene_path).unwrap();
+    let scene = load_scene_from_file(&scene_path).unwrap();
+
+    let mut watcher = SceneWatcher::new(&scene_path, Some(&scene));
+    assert!(!watcher.poll());
+
+    touch(&scene_path, 10);
+    assert!(watcher.poll());
+    assert!(!watcher.poll());
+
+    // Missing while an editor replaces it, then back with a new time
+    std::fs::remove_file(&scene_path).unwrap();
+    assert!(!watcher.poll());
+    std::fs::copy("examples/3_simple_room.json", &scene_path).unwrap();
+    touch(&scene_path, 20);
+    assert!(watcher.poll());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn textures_are_watched_with_the_scene() {
+    let scene_path = Path::new("examples/12_textured_walls.json");
+    let scene = load_scene_from_file(scene_path).unwrap();
+
+    let files = scene_dependencies(scene_path, &scene);
+    assert_eq!(files[0], scene_path);
+    assert!(files.contains(&Path::new("examples").join("../assets/textures/brick.png")), "{:?}", files);
+
+    // A scene that failed to load keeps the textures of the last one that did
+    let mut watcher = SceneWatcher::new(scene_path, Some(&scene));
+    watcher.watch(scene_path, None);
+    assert_eq!(watcher.files().map(Path::to_path_buf).collect::<Vec<_>>(), files);
+}
+
+#[test]
+fn texture_changes_are_reported() {
+    let dir = scratch_dir("watch_textures");
+    let scene_path = dir.join("scene.json");
+    let scene = std::fs::read_to_string("examples/12_textured_walls.json").unwrap();
+    std::fs::write(&scene_path, scene.replace("../assets/textures/brick.png", "brick.png")).unwrap();
+    std::fs::copy("assets/textures/brick.png", dir.join("brick.png")).unwrap();
+    let scene = load_scene_from_file(&scene_path).unwrap();
+
+    let mut watcher = SceneWatcher::new(&scene_path, Some(&scene));
+    touch(&dir.join("brick.png"), 10);
+    assert!(watcher.poll());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}